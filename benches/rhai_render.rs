@@ -0,0 +1,73 @@
+//! Rhai dashboard rendering
+//!
+//! Compares rendering the embedded server's dashboards the old way, with a
+//! new engine and parse per render, against the shared engine and AST cache.
+//!
+//! Run with `cargo bench --no-default-features --bench rhai_render`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use dioxus_desktop_barebones::host::{theme_tokens, ConnectionManager, HostState};
+use dioxus_desktop_barebones::ui::{create_rhai_engine, ScriptLimits, ScriptRuntime, UiNode};
+use rhai::{Dynamic, Map, Scope};
+use rmcp::model::ResourceContents;
+
+const DASHBOARDS: [(&str, &str); 4] = [
+    ("get_weather", "ui://weather-server/dashboard"),
+    ("get_portfolio", "ui://portfolio-server/gallery"),
+    ("get_system_status", "ui://system-server/status"),
+    ("create_note", "ui://notes-server/editor"),
+];
+
+/// Script and tool result context for each dashboard
+fn dashboards() -> Vec<(&'static str, String, String)> {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        let manager = ConnectionManager::new(HostState::default());
+        let server = manager.connect_embedded().await.unwrap();
+        let mut dashboards = Vec::new();
+        for (tool, uri) in DASHBOARDS {
+            let script = match &manager.read_resource(&server, uri).await.unwrap().contents[0] {
+                ResourceContents::TextResourceContents { text, .. } => text.clone(),
+                _ => panic!("Expected a text resource"),
+            };
+            let arguments = serde_json::json!({ "location": "Oslo", "title": "Bench" });
+            let result = manager.call_tool(&server, tool, arguments).await.unwrap();
+            dashboards.push((uri, script, serde_json::to_string(&result).unwrap()));
+        }
+        dashboards
+    })
+}
+
+/// Build the engine, parse and evaluate on every render, as before the cache
+fn render_uncached(script: &str, context: &str) -> UiNode {
+    let engine = create_rhai_engine(&ScriptLimits::default());
+    let mut theme = Map::new();
+    theme.insert("name".into(), "light".into());
+    for (token, classes) in theme_tokens("light") {
+        theme.insert((*token).into(), (*classes).into());
+    }
+
+    let mut scope = Scope::new();
+    let data = serde_json::from_str::<serde_json::Value>(context).unwrap();
+    scope.push("data", rhai::serde::to_dynamic(data).unwrap());
+    scope.push("theme", theme);
+    scope.push("state", Map::new());
+    UiNode::from_dynamic(engine.eval_with_scope::<Dynamic>(&mut scope, script).unwrap()).unwrap()
+}
+
+fn bench_dashboards(c: &mut Criterion) {
+    for (uri, script, context) in dashboards() {
+        let mut group = c.benchmark_group(uri);
+        group.bench_function("new engine and parse", |b| b.iter(|| render_uncached(&script, &context)));
+        // New runtimes share the engine and AST but evaluate the script again
+        group.bench_function("cached AST", |b| {
+            b.iter(|| ScriptRuntime::new(&script).unwrap().render(&context, "light").unwrap())
+        });
+        let runtime = ScriptRuntime::new(&script).unwrap();
+        group.bench_function("unchanged inputs", |b| b.iter(|| runtime.render(&context, "light").unwrap()));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_dashboards);
+criterion_main!(benches);
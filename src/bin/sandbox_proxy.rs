@@ -0,0 +1,23 @@
+//! Standalone sandbox proxy server for the web build
+//!
+//! Serves the outer proxy page from its own origin. Usage:
+//! `sandbox-proxy [ADDR]` (defaults to 127.0.0.1:8765). Build with
+//! `--features sandbox-proxy`.
+
+use dioxus_desktop_barebones::ui::sandbox_proxy::{server::SandboxProxyServer, DEFAULT_SANDBOX_PROXY_ADDR};
+
+#[tokio::main]
+async fn main() {
+    let addr = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_SANDBOX_PROXY_ADDR.to_string());
+
+    let server = match SandboxProxyServer::bind(&addr).await {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Failed to bind sandbox proxy on {}: {}", addr, e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("Sandbox proxy serving {}", server.url());
+    let _ = tokio::signal::ctrl_c().await;
+}
@@ -0,0 +1,520 @@
+//! Security Audit Log
+//!
+//! Records every view load (content hash, effective CSP, requested and
+//! granted permissions) and every request a view makes of the host to an
+//! append-only JSONL file for security review. Files are rotated by size and
+//! can be queried back with [`AuditQuery`].
+
+use crate::host::now_millis;
+use crate::protocol::{CspPolicy, DroppedCspOrigin, UiPermissions, UiResourceMeta};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Default size at which the active file is rotated
+pub const DEFAULT_AUDIT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Default number of rotated files kept alongside the active one
+pub const DEFAULT_AUDIT_MAX_FILES: usize = 5;
+
+/// Largest `params` kept for a view request, in serialized bytes
+///
+/// Views control their params, so larger ones are replaced by their size and hash.
+pub const MAX_AUDIT_PARAMS_BYTES: usize = 4 * 1024;
+
+/// Environment variable overriding [`DEFAULT_AUDIT_LOG_PATH`]
+pub const AUDIT_LOG_ENV: &str = "MCP_AUDIT_LOG";
+
+/// Where the host writes its audit log by default
+pub const DEFAULT_AUDIT_LOG_PATH: &str = "logs/mcp-audit.jsonl";
+
+/// View → host methods that are recorded
+pub const AUDITED_METHODS: &[&str] = &[
+    "tools/call",
+    "resources/read",
+    "ui/open-link",
+    "ui/message",
+    "ui/update-model-context",
+    "ui/request-display-mode",
+    // Legacy bridge aliases
+    "link/open",
+    "context/update",
+];
+
+/// Something worth keeping for security review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AuditEvent {
+    /// A view was loaded into the host
+    #[serde(rename_all = "camelCase")]
+    ViewLoaded {
+        session_id: String,
+        server_id: String,
+        resource_uri: String,
+        /// SHA-256 of the resource content, hex encoded
+        content_hash: String,
+        /// Effective CSP header applied to the view
+        csp: String,
+        /// Declared CSP origins left out of the effective policy
+        dropped_csp: Vec<DroppedCspOrigin>,
+        /// Permissions the resource asked for
+        requested_permissions: Vec<String>,
+        /// Permissions the host granted
+        granted_permissions: Vec<String>,
+    },
+    /// A view sent the host a request or notification
+    #[serde(rename_all = "camelCase")]
+    ViewRequest {
+        session_id: String,
+        server_id: String,
+        resource_uri: String,
+        method: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        params: Option<Value>,
+    },
+}
+
+impl AuditEvent {
+    /// Event kind as written in the `type` field
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AuditEvent::ViewLoaded { .. } => "viewLoaded",
+            AuditEvent::ViewRequest { .. } => "viewRequest",
+        }
+    }
+
+    /// Session the event belongs to
+    pub fn session_id(&self) -> &str {
+        match self {
+            AuditEvent::ViewLoaded { session_id, .. } | AuditEvent::ViewRequest { session_id, .. } => session_id,
+        }
+    }
+
+    /// Server that owns the view
+    pub fn server_id(&self) -> &str {
+        match self {
+            AuditEvent::ViewLoaded { server_id, .. } | AuditEvent::ViewRequest { server_id, .. } => server_id,
+        }
+    }
+
+    /// UI resource of the view
+    pub fn resource_uri(&self) -> &str {
+        match self {
+            AuditEvent::ViewLoaded { resource_uri, .. } | AuditEvent::ViewRequest { resource_uri, .. } => resource_uri,
+        }
+    }
+
+    /// Build a view request event, capping the params at [`MAX_AUDIT_PARAMS_BYTES`]
+    pub fn view_request(
+        session_id: impl Into<String>,
+        server_id: impl Into<String>,
+        resource_uri: impl Into<String>,
+        method: impl Into<String>,
+        params: Option<Value>,
+    ) -> Self {
+        let params = params.map(|params| {
+            let text = params.to_string();
+            if text.len() <= MAX_AUDIT_PARAMS_BYTES {
+                return params;
+            }
+            serde_json::json!({ "truncated": true, "bytes": text.len(), "sha256": content_hash(&text) })
+        });
+
+        AuditEvent::ViewRequest {
+            session_id: session_id.into(),
+            server_id: server_id.into(),
+            resource_uri: resource_uri.into(),
+            method: method.into(),
+            params,
+        }
+    }
+
+    /// Build a view load event from the resource and the policy applied to it
+    pub fn view_loaded(
+        session_id: impl Into<String>,
+        server_id: impl Into<String>,
+        resource_uri: impl Into<String>,
+        content: &str,
+        metadata: Option<&UiResourceMeta>,
+        csp: &CspPolicy,
+        granted: &UiPermissions,
+    ) -> Self {
+        let requested = metadata
+            .and_then(|m| m.ui.as_ref())
+            .and_then(|u| u.permissions.as_ref())
+            .map(|p| UiPermissions::from(p).granted())
+            .unwrap_or_default();
+
+        AuditEvent::ViewLoaded {
+            session_id: session_id.into(),
+            server_id: server_id.into(),
+            resource_uri: resource_uri.into(),
+            content_hash: content_hash(content),
+            csp: csp.to_header(),
+            dropped_csp: csp.dropped.clone(),
+            requested_permissions: requested.iter().map(|p| p.to_string()).collect(),
+            granted_permissions: granted.granted().iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+/// A recorded event with its timestamp
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+/// SHA-256 of view content, hex encoded
+pub fn content_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Filter for [`AuditLog::query`]
+///
+/// Unset fields match everything. Results are oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub session_id: Option<String>,
+    pub server_id: Option<String>,
+    pub resource_uri: Option<String>,
+    /// Event kind (`viewLoaded` or `viewRequest`)
+    pub kind: Option<String>,
+    /// Request method, for `viewRequest` events
+    pub method: Option<String>,
+    /// Earliest timestamp (inclusive, ms)
+    pub since: Option<u64>,
+    /// Latest timestamp (inclusive, ms)
+    pub until: Option<u64>,
+    /// Keep only the most recent `limit` matches
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    pub fn server(mut self, server_id: impl Into<String>) -> Self {
+        self.server_id = Some(server_id.into());
+        self
+    }
+
+    pub fn resource(mut self, resource_uri: impl Into<String>) -> Self {
+        self.resource_uri = Some(resource_uri.into());
+        self
+    }
+
+    pub fn kind(mut self, kind: impl Into<String>) -> Self {
+        self.kind = Some(kind.into());
+        self
+    }
+
+    pub fn method(mut self, method: impl Into<String>) -> Self {
+        self.method = Some(method.into());
+        self
+    }
+
+    pub fn since(mut self, timestamp: u64) -> Self {
+        self.since = Some(timestamp);
+        self
+    }
+
+    pub fn until(mut self, timestamp: u64) -> Self {
+        self.until = Some(timestamp);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Check a record against the filter
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        let event = &record.event;
+        let method = match event {
+            AuditEvent::ViewRequest { method, .. } => Some(method.as_str()),
+            AuditEvent::ViewLoaded { .. } => None,
+        };
+
+        self.session_id.as_deref().is_none_or(|s| s == event.session_id())
+            && self.server_id.as_deref().is_none_or(|s| s == event.server_id())
+            && self.resource_uri.as_deref().is_none_or(|r| r == event.resource_uri())
+            && self.kind.as_deref().is_none_or(|k| k == event.kind())
+            && self.method.as_deref().is_none_or(|m| Some(m) == method)
+            && self.since.is_none_or(|t| record.timestamp >= t)
+            && self.until.is_none_or(|t| record.timestamp <= t)
+    }
+}
+
+/// Active file handle and its current size
+#[derive(Debug)]
+struct AuditFile {
+    file: File,
+    size: u64,
+}
+
+/// Append-only JSONL audit log with size-based rotation
+///
+/// Rotated files are named `<path>.1` (newest) to `<path>.<max_files>`
+/// (oldest). Cloning yields another handle to the same log.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: Arc<Mutex<AuditFile>>,
+}
+
+impl AuditLog {
+    /// Open (or create) the log at `path`
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            max_bytes: DEFAULT_AUDIT_MAX_BYTES,
+            max_files: DEFAULT_AUDIT_MAX_FILES,
+            file: Arc::new(Mutex::new(AuditFile { file, size })),
+        })
+    }
+
+    /// Open the log at `$MCP_AUDIT_LOG`, or [`DEFAULT_AUDIT_LOG_PATH`] if unset
+    pub fn open_default() -> io::Result<Self> {
+        let path = std::env::var_os(AUDIT_LOG_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_AUDIT_LOG_PATH));
+        Self::open(path)
+    }
+
+    /// Builder method: Set the rotation size and number of rotated files kept
+    ///
+    /// At least one rotated file must be kept, since rotating into none
+    /// would erase records from an append-only log.
+    pub fn with_rotation(mut self, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        if max_files == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "audit log must keep at least one rotated file"));
+        }
+        self.max_bytes = max_bytes;
+        self.max_files = max_files;
+        Ok(self)
+    }
+
+    /// Path of the active file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    /// Append an event stamped with the current time
+    pub fn record(&self, event: AuditEvent) -> io::Result<()> {
+        self.append(AuditRecord { timestamp: now_millis(), event })
+    }
+
+    /// Append a record
+    pub fn append(&self, record: AuditRecord) -> io::Result<()> {
+        let mut line = serde_json::to_string(&record).map_err(io::Error::other)?;
+        line.push('\n');
+
+        let mut active = self.file.lock().map_err(|_| io::Error::other("audit log lock poisoned"))?;
+        if active.size > 0 && active.size + line.len() as u64 > self.max_bytes {
+            self.rotate(&mut active)?;
+        }
+
+        active.file.write_all(line.as_bytes())?;
+        active.file.flush()?;
+        active.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift rotated files up by one and start a fresh active file
+    fn rotate(&self, active: &mut AuditFile) -> io::Result<()> {
+        let oldest = self.rotated_path(self.max_files);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for index in (1..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+
+        active.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        active.size = 0;
+        Ok(())
+    }
+
+    /// Read back matching records, oldest first, across rotated files
+    ///
+    /// Lines that fail to parse are skipped.
+    pub fn query(&self, query: &AuditQuery) -> io::Result<Vec<AuditRecord>> {
+        // Hold the lock so a rotation cannot move files mid-read
+        let _active = self.file.lock().map_err(|_| io::Error::other("audit log lock poisoned"))?;
+
+        let mut files: Vec<PathBuf> = (1..=self.max_files).rev().map(|i| self.rotated_path(i)).collect();
+        files.push(self.path.clone());
+
+        let mut records = Vec::new();
+        for path in files.iter().filter(|p| p.exists()) {
+            for line in BufReader::new(File::open(path)?).lines() {
+                let line = line?;
+                match serde_json::from_str::<AuditRecord>(&line) {
+                    Ok(record) if query.matches(&record) => records.push(record),
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Skipping unreadable audit record in {}: {}", path.display(), e),
+                }
+            }
+        }
+
+        if let Some(limit) = query.limit {
+            let skip = records.len().saturating_sub(limit);
+            records.drain(..skip);
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{McpUiResourceCsp, UiResourceDetails, UiResourcePermissions, Empty};
+    use serde_json::json;
+
+    fn temp_log_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mcp-audit-test-{}", uuid::Uuid::new_v4()));
+        dir.join(name)
+    }
+
+    fn request(session: &str, method: &str) -> AuditEvent {
+        AuditEvent::ViewRequest {
+            session_id: session.to_string(),
+            server_id: "embedded".to_string(),
+            resource_uri: "ui://test/view".to_string(),
+            method: method.to_string(),
+            params: Some(json!({ "name": "get_weather" })),
+        }
+    }
+
+    #[test]
+    fn test_view_loaded_event() {
+        let metadata = UiResourceMeta {
+            ui: Some(UiResourceDetails {
+                csp: Some(McpUiResourceCsp {
+                    connect_domains: Some(vec!["https://api.example.com".to_string(), "*".to_string()]),
+                    ..Default::default()
+                }),
+                permissions: Some(UiResourcePermissions {
+                    camera: Some(Empty {}),
+                    clipboard_write: Some(Empty {}),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        };
+        let policy = metadata.ui.as_ref().unwrap().csp.as_ref().unwrap().effective_policy(None);
+        let granted = UiPermissions { clipboard_write: Some(Empty {}), ..Default::default() };
+
+        let event = AuditEvent::view_loaded("s1", "embedded", "ui://test/view", "<p>hi</p>", Some(&metadata), &policy, &granted);
+        let AuditEvent::ViewLoaded { content_hash, csp, dropped_csp, requested_permissions, granted_permissions, .. } = event else {
+            panic!("expected a view load event");
+        };
+        assert_eq!(content_hash, super::content_hash("<p>hi</p>"));
+        assert_eq!(content_hash.len(), 64);
+        assert!(csp.contains("connect-src https://api.example.com"));
+        assert_eq!(dropped_csp.len(), 1);
+        assert_eq!(requested_permissions, vec!["camera", "clipboard-write"]);
+        assert_eq!(granted_permissions, vec!["clipboard-write"]);
+    }
+
+    #[test]
+    fn test_append_and_query() {
+        let path = temp_log_path("audit.jsonl");
+        let log = AuditLog::open(&path).unwrap();
+
+        log.append(AuditRecord { timestamp: 100, event: request("s1", "tools/call") }).unwrap();
+        log.append(AuditRecord { timestamp: 200, event: request("s2", "ui/open-link") }).unwrap();
+        log.append(AuditRecord { timestamp: 300, event: request("s1", "ui/update-model-context") }).unwrap();
+
+        assert_eq!(log.query(&AuditQuery::new()).unwrap().len(), 3);
+        assert_eq!(log.query(&AuditQuery::new().session("s1")).unwrap().len(), 2);
+        assert_eq!(log.query(&AuditQuery::new().method("ui/open-link")).unwrap()[0].timestamp, 200);
+        assert_eq!(log.query(&AuditQuery::new().since(150).until(250)).unwrap().len(), 1);
+        assert!(log.query(&AuditQuery::new().kind("viewLoaded")).unwrap().is_empty());
+
+        let latest = log.query(&AuditQuery::new().limit(1)).unwrap();
+        assert_eq!(latest[0].timestamp, 300);
+
+        // Reopening keeps appending to the same file
+        let reopened = AuditLog::open(&path).unwrap();
+        reopened.record(request("s3", "tools/call")).unwrap();
+        assert_eq!(reopened.query(&AuditQuery::new()).unwrap().len(), 4);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_rotation() {
+        let path = temp_log_path("audit.jsonl");
+        let line_len = serde_json::to_string(&AuditRecord { timestamp: 1, event: request("s1", "tools/call") })
+            .unwrap()
+            .len() as u64 + 1;
+        // Two records per file, two rotated files kept
+        let log = AuditLog::open(&path).unwrap().with_rotation(line_len * 2, 2).unwrap();
+        assert!(AuditLog::open(&path).unwrap().with_rotation(line_len, 0).is_err());
+
+        for timestamp in 1..=7 {
+            log.append(AuditRecord { timestamp, event: request("s1", "tools/call") }).unwrap();
+        }
+
+        assert!(log.rotated_path(1).exists());
+        assert!(log.rotated_path(2).exists());
+        assert!(!log.rotated_path(3).exists());
+
+        // Records 1 and 2 rotated out; the rest read back in order
+        let timestamps: Vec<u64> = log.query(&AuditQuery::new()).unwrap().iter().map(|r| r.timestamp).collect();
+        assert_eq!(timestamps, vec![3, 4, 5, 6, 7]);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_large_params_are_capped() {
+        let small = AuditEvent::view_request("s1", "embedded", "ui://test/view", "ui/message", Some(json!({ "text": "hi" })));
+        let AuditEvent::ViewRequest { params, .. } = small else { panic!("expected a view request") };
+        assert_eq!(params, Some(json!({ "text": "hi" })));
+
+        let text = "x".repeat(MAX_AUDIT_PARAMS_BYTES);
+        let large = AuditEvent::view_request("s1", "embedded", "ui://test/view", "ui/message", Some(json!({ "text": text })));
+        let AuditEvent::ViewRequest { params: Some(params), .. } = large else { panic!("expected params") };
+        assert_eq!(params["truncated"], json!(true));
+        assert_eq!(params["bytes"], json!(MAX_AUDIT_PARAMS_BYTES + 11));
+        assert_eq!(params["sha256"].as_str().unwrap().len(), 64);
+    }
+}
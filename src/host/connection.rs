@@ -4,8 +4,8 @@
 //! negotiation, capability exchange, and message routing.
 
 use crate::protocol::{
-    capabilities::{McpUiAppCapabilities, ServerCapabilities, UiHostCapabilities, negotiate_capabilities, NegotiatedCapabilities},
-    resources::{UiResource, UiResourceMeta},
+    capabilities::{DisplayMode, McpUiAppCapabilities, ServerCapabilities, UiHostCapabilities, negotiate_capabilities, NegotiatedCapabilities},
+    resources::{McpUiToolMeta, UiResource, UiResourceMeta},
     UI_EXTENSION_ID,
 };
 use rmcp::model::{Resource, Tool};
@@ -126,9 +126,14 @@ impl McpServerConnection {
     }
     
     /// Negotiate capabilities with the server
-    pub fn negotiate_capabilities(&mut self, host_caps: &UiHostCapabilities, app_caps: Option<&McpUiAppCapabilities>) {
+    pub fn negotiate_capabilities(
+        &mut self,
+        host_caps: &UiHostCapabilities,
+        host_modes: &[DisplayMode],
+        app_caps: Option<&McpUiAppCapabilities>,
+    ) {
         if let Some(server_caps) = &self.parsed_capabilities {
-            let negotiated = negotiate_capabilities(host_caps, server_caps, app_caps);
+            let negotiated = negotiate_capabilities(host_caps, host_modes, server_caps, app_caps);
             self.negotiated_capabilities = Some(negotiated);
         }
     }
@@ -213,29 +218,42 @@ impl McpServerConnection {
         })
     }
     
+    /// Parse the MCP Apps metadata attached to a tool
+    pub fn tool_ui_meta(tool: &Tool) -> McpUiToolMeta {
+        tool.meta
+            .as_ref()
+            .map(|m| McpUiToolMeta::from_meta(&m.0))
+            .unwrap_or_default()
+    }
+    
     /// Get tools that have UI metadata
     pub async fn get_tools_with_ui(&self) -> Vec<(Tool, String)> {
         let tools = self.tools.read().await;
         tools
             .iter()
             .filter_map(|t| {
-                let uri = t.meta.as_ref()
-                    .and_then(|m| m.0.get("ui"))
-                    .and_then(|u| u.get("resourceUri"))
-                    .and_then(|s| s.as_str())
-                    .map(|s| s.to_string())
-                    .or_else(|| {
-                        // Try deprecated format
-                        t.meta.as_ref()
-                            .and_then(|m| m.0.get("ui/resourceUri"))
-                            .and_then(|s| s.as_str())
-                            .map(|s| s.to_string())
-                    });
-                
-                uri.map(|u| (t.clone(), u))
+                Self::tool_ui_meta(t)
+                    .resource_uri
+                    .map(|u| (t.clone(), u))
             })
             .collect()
     }
+    
+    /// Get tools visible to the agent (visibility includes "model")
+    pub async fn get_model_tools(&self) -> Vec<Tool> {
+        self.tools.read().await.iter()
+            .filter(|t| Self::tool_ui_meta(t).is_model_visible())
+            .cloned()
+            .collect()
+    }
+    
+    /// Get tools callable by views from this server (visibility includes "app")
+    pub async fn get_app_tools(&self) -> Vec<Tool> {
+        self.tools.read().await.iter()
+            .filter(|t| Self::tool_ui_meta(t).is_app_visible())
+            .cloned()
+            .collect()
+    }
 }
 
 /// Connection manager event types
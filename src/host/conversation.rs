@@ -0,0 +1,372 @@
+//! Host Conversation
+//!
+//! The chat transcript views post into with `ui/message`. Messages from views
+//! are rate limited per server and, when the host asks for it, confirmed by
+//! the user before they are appended.
+
+use crate::host::{now_millis, Prompt, PromptQueue, SlidingWindow, UiSession};
+use crate::protocol::{error_codes, UiMessageRequest};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Messages a server's views may post per [`DEFAULT_RATE_WINDOW`]
+pub const DEFAULT_RATE_LIMIT: usize = 5;
+
+/// Window over which [`DEFAULT_RATE_LIMIT`] applies
+pub const DEFAULT_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Environment variable that turns on confirmation of view messages
+pub const CONFIRM_MESSAGES_ENV: &str = "MCP_CONFIRM_VIEW_MESSAGES";
+
+/// Whether the environment asks for view messages to be confirmed
+pub fn confirm_messages_from_env() -> bool {
+    std::env::var_os(CONFIRM_MESSAGES_ENV).is_some_and(|value| value != "0")
+}
+
+/// Who a message is from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageRole {
+    User,
+    Assistant,
+}
+
+impl MessageRole {
+    fn parse(role: &str) -> Option<Self> {
+        match role {
+            "user" => Some(MessageRole::User),
+            "assistant" => Some(MessageRole::Assistant),
+            _ => None,
+        }
+    }
+}
+
+/// View a message was posted by
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageSource {
+    pub session_id: String,
+    pub server_id: String,
+    /// Name the view gave during `ui/initialize`
+    pub app_name: String,
+}
+
+/// A message in the conversation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationMessage {
+    pub id: String,
+    pub role: MessageRole,
+    /// Content blocks
+    pub content: Vec<Value>,
+    /// View that posted the message (`None` for the host and agent)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<MessageSource>,
+    /// Milliseconds since the Unix epoch
+    pub created_at: u64,
+}
+
+impl ConversationMessage {
+    /// Text of the message's text blocks, joined by newlines
+    pub fn text(&self) -> String {
+        blocks_text(&self.content)
+    }
+}
+
+/// Why a view's message was not posted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageError {
+    /// Views may only post `user` or `assistant` messages
+    InvalidRole(String),
+    /// Content must be a content block or a list of them
+    InvalidContent,
+    /// The server's views posted too many messages recently
+    RateLimited { retry_after: Duration },
+    /// The user declined the message
+    Declined,
+}
+
+impl MessageError {
+    /// JSON-RPC error code reported to the view
+    pub fn code(&self) -> i32 {
+        match self {
+            MessageError::InvalidRole(_) | MessageError::InvalidContent => error_codes::INVALID_PARAMS,
+            MessageError::RateLimited { .. } => error_codes::SERVER_ERROR,
+            MessageError::Declined => error_codes::REQUEST_DENIED,
+        }
+    }
+}
+
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageError::InvalidRole(role) => write!(f, "Invalid message format: role {} is not allowed", role),
+            MessageError::InvalidContent => write!(f, "Invalid message format: content must be content blocks"),
+            MessageError::RateLimited { retry_after } => {
+                write!(f, "Message sending denied: rate limited, retry in {}s", retry_after.as_secs().max(1))
+            }
+            MessageError::Declined => write!(f, "Message sending denied by the user"),
+        }
+    }
+}
+
+impl std::error::Error for MessageError {}
+
+/// A view message waiting for the user's confirmation
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageRequest {
+    /// Prompt ID passed back to [`Conversation::respond`]
+    pub id: String,
+    pub source: MessageSource,
+    pub role: MessageRole,
+    pub content: Vec<Value>,
+}
+
+impl MessageRequest {
+    /// Text of the message's text blocks, joined by newlines
+    pub fn text(&self) -> String {
+        blocks_text(&self.content)
+    }
+}
+
+impl Prompt for MessageRequest {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+fn blocks_text(content: &[Value]) -> String {
+    content
+        .iter()
+        .filter_map(|block| block.get("text").and_then(Value::as_str))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Content as a list of content blocks, each an object with a `type`
+fn content_blocks(content: Value) -> Option<Vec<Value>> {
+    let blocks = match content {
+        Value::Array(blocks) => blocks,
+        block => vec![block],
+    };
+    let valid = !blocks.is_empty() && blocks.iter().all(|b| b.get("type").is_some_and(Value::is_string));
+    valid.then_some(blocks)
+}
+
+/// The host's conversation
+///
+/// Cloning yields another handle to the same conversation.
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    messages: Arc<watch::Sender<Vec<ConversationMessage>>>,
+    /// Messages a server's views may post per window
+    rate_limit: usize,
+    rate_window: Duration,
+    /// Recent posts by each server's views
+    recent: Arc<Mutex<HashMap<String, SlidingWindow>>>,
+    /// Whether view messages wait for the user's confirmation
+    confirm: bool,
+    /// Prompts shown to the user
+    prompts: PromptQueue<MessageRequest, bool>,
+}
+
+impl Conversation {
+    pub fn new() -> Self {
+        Self {
+            messages: Arc::new(watch::channel(Vec::new()).0),
+            rate_limit: DEFAULT_RATE_LIMIT,
+            rate_window: DEFAULT_RATE_WINDOW,
+            recent: Arc::new(Mutex::new(HashMap::new())),
+            confirm: false,
+            prompts: PromptQueue::new(),
+        }
+    }
+
+    /// Builder method: Allow each server's views `limit` messages per `window`
+    pub fn with_rate_limit(mut self, limit: usize, window: Duration) -> Self {
+        self.rate_limit = limit;
+        self.rate_window = window;
+        self
+    }
+
+    /// Builder method: Ask the user before appending messages from views
+    pub fn with_confirmation(mut self, confirm: bool) -> Self {
+        self.confirm = confirm;
+        self
+    }
+
+    /// Messages, oldest first
+    pub fn messages(&self) -> Vec<ConversationMessage> {
+        self.messages.borrow().clone()
+    }
+
+    /// Watch the transcript
+    pub fn subscribe_messages(&self) -> watch::Receiver<Vec<ConversationMessage>> {
+        self.messages.subscribe()
+    }
+
+    /// Messages waiting for the user's confirmation
+    pub fn prompts(&self) -> Vec<MessageRequest> {
+        self.prompts.prompts()
+    }
+
+    /// Watch messages waiting for the user's confirmation
+    pub fn subscribe_prompts(&self) -> watch::Receiver<Vec<MessageRequest>> {
+        self.prompts.subscribe()
+    }
+
+    /// Append a message from the host or agent
+    pub fn append(&self, role: MessageRole, content: Vec<Value>) -> ConversationMessage {
+        self.push(role, content, None)
+    }
+
+    /// Append a message a view sent with `ui/message`
+    ///
+    /// The role and content are checked, the server's rate limit applied, and
+    /// the user asked first if confirmation is on. Dropping the future
+    /// withdraws the prompt.
+    pub async fn post_from_view(
+        &self,
+        session: &UiSession,
+        request: UiMessageRequest,
+    ) -> Result<ConversationMessage, MessageError> {
+        let role = MessageRole::parse(&request.role).ok_or_else(|| MessageError::InvalidRole(request.role.clone()))?;
+        let content = content_blocks(request.content).ok_or(MessageError::InvalidContent)?;
+        self.check_rate(&session.server_id)?;
+
+        let source = MessageSource {
+            session_id: session.id.clone(),
+            server_id: session.server_id.clone(),
+            app_name: session.app_info.as_ref().map(|a| a.name.clone()).unwrap_or_else(|| session.resource_uri.clone()),
+        };
+
+        if self.confirm {
+            let prompt = MessageRequest {
+                id: uuid::Uuid::new_v4().to_string(),
+                source: source.clone(),
+                role,
+                content: content.clone(),
+            };
+            if !self.prompts.ask(prompt).await {
+                return Err(MessageError::Declined);
+            }
+        }
+
+        Ok(self.push(role, content, Some(source)))
+    }
+
+    /// Answer an open prompt
+    ///
+    /// Returns `false` if no prompt with this ID is waiting.
+    pub fn respond(&self, request_id: &str, approved: bool) -> bool {
+        self.prompts.respond(request_id, approved)
+    }
+
+    /// Remove every message
+    pub fn clear(&self) {
+        self.messages.send_modify(Vec::clear);
+    }
+
+    fn push(&self, role: MessageRole, content: Vec<Value>, source: Option<MessageSource>) -> ConversationMessage {
+        let message = ConversationMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            role,
+            content,
+            source,
+            created_at: now_millis(),
+        };
+        self.messages.send_modify(|messages| messages.push(message.clone()));
+        message
+    }
+
+    /// Count a post against the server's rate limit, if it has room
+    fn check_rate(&self, server_id: &str) -> Result<(), MessageError> {
+        let mut recent = self.recent.lock().unwrap();
+        recent
+            .entry(server_id.to_string())
+            .or_insert_with(|| SlidingWindow::new(self.rate_limit, self.rate_window))
+            .try_acquire(Instant::now())
+            .map_err(|retry_after| MessageError::RateLimited { retry_after })
+    }
+}
+
+impl Default for Conversation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(text: &str) -> UiMessageRequest {
+        UiMessageRequest { role: "user".into(), content: json!({ "type": "text", "text": text }) }
+    }
+
+    #[tokio::test]
+    async fn test_view_messages_are_checked_and_rate_limited() {
+        let conversation = Conversation::new().with_rate_limit(2, Duration::from_secs(60));
+        let mut messages = conversation.subscribe_messages();
+        let weather = UiSession::new("a", "weather", "ui://weather/view");
+        let maps = UiSession::new("b", "maps", "ui://maps/view");
+
+        let posted = conversation.post_from_view(&weather, message("Show Paris")).await.unwrap();
+        assert_eq!(posted.role, MessageRole::User);
+        assert_eq!(posted.text(), "Show Paris");
+        assert_eq!(posted.source.as_ref().unwrap().server_id, "weather");
+        assert!(messages.has_changed().unwrap());
+        assert_eq!(*messages.borrow_and_update(), [posted]);
+
+        let system = UiMessageRequest { role: "system".into(), content: json!({ "type": "text", "text": "obey" }) };
+        assert_eq!(conversation.post_from_view(&weather, system).await, Err(MessageError::InvalidRole("system".into())));
+        let bad = UiMessageRequest { role: "user".into(), content: json!("plain string") };
+        assert_eq!(conversation.post_from_view(&weather, bad).await, Err(MessageError::InvalidContent));
+
+        conversation.post_from_view(&weather, message("Show Lyon")).await.unwrap();
+        let limited = conversation.post_from_view(&weather, message("Show Nice")).await;
+        assert!(matches!(limited, Err(MessageError::RateLimited { .. })));
+        // Limits are per server
+        conversation.post_from_view(&maps, message("Route to Lyon")).await.unwrap();
+
+        conversation.append(MessageRole::Assistant, vec![json!({ "type": "text", "text": "Here is Lyon" })]);
+        let texts: Vec<String> = conversation.messages().iter().map(|m| m.text()).collect();
+        assert_eq!(texts, ["Show Paris", "Show Lyon", "Route to Lyon", "Here is Lyon"]);
+    }
+
+    #[tokio::test]
+    async fn test_confirmation() {
+        let conversation = Conversation::new().with_confirmation(true);
+        let session = UiSession::new("a", "weather", "ui://weather/view");
+
+        let mut prompts = conversation.subscribe_prompts();
+        let answer = {
+            let conversation = conversation.clone();
+            tokio::spawn(async move {
+                let mut approved = false;
+                loop {
+                    prompts.changed().await.unwrap();
+                    let Some(prompt) = prompts.borrow_and_update().first().cloned() else { continue };
+                    conversation.respond(&prompt.id, approved);
+                    if approved {
+                        break;
+                    }
+                    approved = true;
+                }
+            })
+        };
+
+        assert_eq!(conversation.post_from_view(&session, message("first")).await, Err(MessageError::Declined));
+        let posted = conversation.post_from_view(&session, message("second")).await.unwrap();
+        answer.await.unwrap();
+
+        assert_eq!(conversation.messages(), vec![posted]);
+        assert!(conversation.prompts().is_empty());
+    }
+}
@@ -0,0 +1,343 @@
+//! Link Opening
+//!
+//! Handles `ui/open-link` for views: the URL must use an allowed scheme and
+//! pass the owning server's allow and deny patterns, and the user confirms
+//! each link before it is handed to the system handler.
+
+use crate::host::{Prompt, PromptQueue};
+use crate::protocol::error_codes;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// URL schemes a view may ask the host to open
+pub const ALLOWED_LINK_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Why a link was not opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkError {
+    /// The URL could not be parsed
+    InvalidUrl(String),
+    /// The scheme is not one of [`ALLOWED_LINK_SCHEMES`]
+    DisallowedScheme(String),
+    /// The URL matched a deny pattern
+    Denied { pattern: String },
+    /// The server has an allowlist and the URL is not on it
+    NotAllowed,
+    /// The user declined to open the link
+    Declined,
+    /// The system handler failed
+    OpenFailed(String),
+}
+
+impl LinkError {
+    /// JSON-RPC error code reported to the view
+    pub fn code(&self) -> i32 {
+        match self {
+            LinkError::InvalidUrl(_) | LinkError::DisallowedScheme(_) => error_codes::INVALID_PARAMS,
+            LinkError::Denied { .. } | LinkError::NotAllowed | LinkError::Declined => error_codes::REQUEST_DENIED,
+            LinkError::OpenFailed(_) => error_codes::INTERNAL_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::InvalidUrl(reason) => write!(f, "invalid URL: {}", reason),
+            LinkError::DisallowedScheme(scheme) => write!(f, "scheme '{}' is not allowed", scheme),
+            LinkError::Denied { pattern } => write!(f, "blocked by host policy ({})", pattern),
+            LinkError::NotAllowed => write!(f, "not on the server's link allowlist"),
+            LinkError::Declined => write!(f, "the user declined to open the link"),
+            LinkError::OpenFailed(e) => write!(f, "failed to open link: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LinkError {}
+
+/// Check that a URL is well formed and uses an allowed scheme
+///
+/// Returns the URL with surrounding whitespace removed.
+pub fn validate_link(url: &str) -> Result<&str, LinkError> {
+    let url = url.trim();
+    if let Some(c) = url.chars().find(|c| c.is_control() || c.is_whitespace()) {
+        return Err(LinkError::InvalidUrl(format!("forbidden character {:?}", c)));
+    }
+
+    let (scheme, rest) = url
+        .split_once(':')
+        .ok_or_else(|| LinkError::InvalidUrl("missing scheme".to_string()))?;
+    let scheme = scheme.to_ascii_lowercase();
+    if !ALLOWED_LINK_SCHEMES.contains(&scheme.as_str()) {
+        return Err(LinkError::DisallowedScheme(scheme));
+    }
+
+    let valid = match scheme.as_str() {
+        "mailto" => !rest.is_empty(),
+        _ => rest
+            .strip_prefix("//")
+            .map(|r| r.split(['/', '?', '#']).next().unwrap_or_default())
+            .is_some_and(|authority| !authority.is_empty() && !authority.contains('@')),
+    };
+    if !valid {
+        return Err(LinkError::InvalidUrl(format!("missing {} target", scheme)));
+    }
+    Ok(url)
+}
+
+/// Match `text` against a pattern where `*` stands for any run of characters
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let (pattern, text) = (pattern.as_bytes(), text.as_bytes());
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p].eq_ignore_ascii_case(&text[t]) {
+            p += 1;
+            t += 1;
+        } else if let Some((bp, bt)) = backtrack {
+            p = bp + 1;
+            t = bt + 1;
+            backtrack = Some((bp, bt + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Which links a server's views may open
+///
+/// Patterns match the whole URL, with `*` as a wildcard, e.g.
+/// `https://*.example.com/*`. Deny patterns win over allow patterns; an empty
+/// allowlist allows any URL that is not denied.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkPolicy {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl LinkPolicy {
+    /// Builder method: Add an allow pattern
+    pub fn allow(mut self, pattern: impl Into<String>) -> Self {
+        self.allow.push(pattern.into());
+        self
+    }
+
+    /// Builder method: Add a deny pattern
+    pub fn deny(mut self, pattern: impl Into<String>) -> Self {
+        self.deny.push(pattern.into());
+        self
+    }
+
+    /// Validate a URL and check it against the patterns
+    pub fn check<'a>(&self, url: &'a str) -> Result<&'a str, LinkError> {
+        let url = validate_link(url)?;
+        if let Some(pattern) = self.deny.iter().find(|p| glob_matches(p, url)) {
+            return Err(LinkError::Denied { pattern: pattern.clone() });
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|p| glob_matches(p, url)) {
+            return Err(LinkError::NotAllowed);
+        }
+        Ok(url)
+    }
+}
+
+/// Hands approved links to something that can open them
+pub trait LinkOpener: Send + Sync {
+    fn open(&self, url: &str) -> Result<(), String>;
+}
+
+/// Opens links with the platform's default handler
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemLinkOpener;
+
+impl LinkOpener for SystemLinkOpener {
+    fn open(&self, url: &str) -> Result<(), String> {
+        #[cfg(target_os = "macos")]
+        let mut command = std::process::Command::new("open");
+        #[cfg(target_os = "windows")]
+        let mut command = {
+            let mut command = std::process::Command::new("rundll32");
+            command.arg("url.dll,FileProtocolHandler");
+            command
+        };
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        let mut command = std::process::Command::new("xdg-open");
+
+        command.arg(url).spawn().map(|_| ()).map_err(|e| e.to_string())
+    }
+}
+
+/// A link waiting for the user's confirmation
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkRequest {
+    /// Prompt ID passed back to [`LinkBroker::respond`]
+    pub id: String,
+    pub server_id: String,
+    /// Name the view gave during `ui/initialize`
+    pub app_name: String,
+    pub url: String,
+}
+
+impl Prompt for LinkRequest {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Checks, confirms and opens links requested by views
+///
+/// Cloning yields another handle to the same broker.
+#[derive(Clone)]
+pub struct LinkBroker {
+    opener: Arc<dyn LinkOpener>,
+    /// Prompts shown to the user
+    prompts: PromptQueue<LinkRequest, bool>,
+}
+
+impl fmt::Debug for LinkBroker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinkBroker")
+            .field("prompts", &self.prompts)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for LinkBroker {
+    fn default() -> Self {
+        Self::new(SystemLinkOpener)
+    }
+}
+
+impl LinkBroker {
+    pub fn new(opener: impl LinkOpener + 'static) -> Self {
+        Self {
+            opener: Arc::new(opener),
+            prompts: PromptQueue::new(),
+        }
+    }
+
+    /// Prompts waiting for the user
+    pub fn prompts(&self) -> Vec<LinkRequest> {
+        self.prompts.prompts()
+    }
+
+    /// Watch prompts waiting for the user
+    pub fn subscribe_prompts(&self) -> watch::Receiver<Vec<LinkRequest>> {
+        self.prompts.subscribe()
+    }
+
+    /// Check a link against policy, ask the user, then open it
+    ///
+    /// Dropping the future withdraws the prompt.
+    pub async fn open(
+        &self,
+        policy: &LinkPolicy,
+        server_id: &str,
+        app_name: &str,
+        url: &str,
+    ) -> Result<(), LinkError> {
+        let url = policy.check(url)?;
+
+        let request = LinkRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            server_id: server_id.to_string(),
+            app_name: app_name.to_string(),
+            url: url.to_string(),
+        };
+        if !self.prompts.ask(request).await {
+            return Err(LinkError::Declined);
+        }
+
+        self.opener.open(url).map_err(LinkError::OpenFailed)
+    }
+
+    /// Answer an open prompt
+    ///
+    /// Returns `false` if no prompt with this ID is waiting.
+    pub fn respond(&self, request_id: &str, approved: bool) -> bool {
+        self.prompts.respond(request_id, approved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records links instead of opening them
+    #[derive(Default, Clone)]
+    struct RecordingOpener(Arc<Mutex<Vec<String>>>);
+
+    impl LinkOpener for RecordingOpener {
+        fn open(&self, url: &str) -> Result<(), String> {
+            self.0.lock().unwrap().push(url.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_link_policy() {
+        assert_eq!(validate_link(" https://example.com/a "), Ok("https://example.com/a"));
+        assert!(validate_link("mailto:someone@example.com").is_ok());
+        assert_eq!(validate_link("javascript:alert(1)"), Err(LinkError::DisallowedScheme("javascript".into())));
+        assert_eq!(validate_link("file:///etc/passwd"), Err(LinkError::DisallowedScheme("file".into())));
+        assert!(matches!(validate_link("https:example.com"), Err(LinkError::InvalidUrl(_))));
+        assert!(matches!(validate_link("https://user@evil.com"), Err(LinkError::InvalidUrl(_))));
+        assert!(matches!(validate_link("https://exa mple.com"), Err(LinkError::InvalidUrl(_))));
+        assert!(matches!(validate_link("example.com"), Err(LinkError::InvalidUrl(_))));
+
+        let policy = LinkPolicy::default()
+            .allow("https://*.example.com/*")
+            .allow("mailto:*")
+            .deny("https://admin.example.com/*");
+        assert!(policy.check("https://docs.example.com/guide").is_ok());
+        assert!(policy.check("mailto:help@example.com").is_ok());
+        assert_eq!(policy.check("https://evil.com/"), Err(LinkError::NotAllowed));
+        assert!(matches!(policy.check("https://admin.example.com/users"), Err(LinkError::Denied { .. })));
+
+        // An empty allowlist allows anything not denied
+        let open = LinkPolicy::default().deny("*://*.tracker.net/*");
+        assert!(open.check("http://anything.org/").is_ok());
+        assert!(open.check("https://ads.tracker.net/x").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_links_open_after_confirmation() {
+        let opener = RecordingOpener::default();
+        let broker = LinkBroker::new(opener.clone());
+
+        // Blocked by policy before the user is asked
+        let error = broker.open(&LinkPolicy::default(), "s", "app", "ftp://example.com").await.unwrap_err();
+        assert_eq!(error.code(), error_codes::INVALID_PARAMS);
+        assert!(broker.prompts().is_empty());
+
+        for approved in [false, true] {
+            let responder = broker.clone();
+            tokio::spawn(async move {
+                let mut prompts = responder.subscribe_prompts();
+                let request = loop {
+                    if let Some(request) = prompts.borrow_and_update().first().cloned() {
+                        break request;
+                    }
+                    prompts.changed().await.unwrap();
+                };
+                assert_eq!(request.app_name, "Weather");
+                assert!(responder.respond(&request.id, approved));
+            });
+
+            let result = broker.open(&LinkPolicy::default(), "s", "Weather", "https://example.com").await;
+            if approved {
+                assert!(result.is_ok());
+            } else {
+                assert_eq!(result.unwrap_err().code(), error_codes::REQUEST_DENIED);
+            }
+        }
+
+        assert_eq!(*opener.0.lock().unwrap(), vec!["https://example.com".to_string()]);
+    }
+}
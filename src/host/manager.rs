@@ -4,7 +4,7 @@
 //! for the host to interact with them.
 
 use crate::protocol::*;
-use crate::host::{McpServerConnection, ConnectionState, ConnectionEvent, HostState};
+use crate::host::{McpServerConnection, ConnectionState, ConnectionEvent, HostState, ViewToolError, ViewToolSet, ViewTools};
 use crate::host::transport::{McpTransport, StdioTransport};
 use rmcp::model::{CallToolResult, Content, ListToolsResult, ListResourcesResult, ReadResourceResult, Resource, ResourceContents, Tool, Meta};
 use serde_json::json;
@@ -24,6 +24,8 @@ pub struct ConnectionManager {
     event_rx: Arc<RwLock<mpsc::UnboundedReceiver<ConnectionEvent>>>,
     /// Host state for capabilities
     pub host_state: HostState,
+    /// Tools exposed by live views
+    view_tools: ViewTools,
 }
 
 impl ConnectionManager {
@@ -36,6 +38,7 @@ impl ConnectionManager {
             event_tx,
             event_rx: Arc::new(RwLock::new(event_rx)),
             host_state,
+            view_tools: ViewTools::new(),
         }
     }
     
@@ -75,7 +78,7 @@ impl ConnectionManager {
         
         // Perform capability negotiation
         let host_caps = self.host_state.to_capabilities();
-        connection.negotiate_capabilities(&host_caps, None);
+        connection.negotiate_capabilities(&host_caps, &self.host_state.supported_display_modes, None);
         
         // Fetch tools and resources BEFORE moving transport to background task
         let tools_request = JsonRpcRequest::new("tools/list", None);
@@ -181,7 +184,11 @@ impl ConnectionManager {
             .map_err(|e| ConnectionError::Transport(e.to_string()))?;
         
         connection.set_capabilities(&init_response.result.unwrap());
-        connection.negotiate_capabilities(&self.host_state.to_capabilities(), None);
+        connection.negotiate_capabilities(
+            &self.host_state.to_capabilities(),
+            &self.host_state.supported_display_modes,
+            None,
+        );
 
         // Fetch tools
         let tools_response = client_transport.send_request(JsonRpcRequest::new("tools/list", None)).await
@@ -312,6 +319,31 @@ impl ConnectionManager {
         all_tools
     }
     
+    /// Get all tools visible to the agent from all connections
+    ///
+    /// Tools whose `_meta.ui.visibility` does not include `"model"` are
+    /// app-only and are excluded from the list exposed to the agent runtime.
+    pub async fn get_model_tools(&self) -> Vec<(String, Tool)> {
+        let mut model_tools = Vec::new();
+        let connections = self.connections.read().await;
+        
+        for (conn_id, conn) in connections.iter() {
+            for tool in conn.get_model_tools().await {
+                model_tools.push((conn_id.clone(), tool));
+            }
+        }
+        
+        model_tools
+    }
+    
+    /// Get tools that views from a specific connection may call
+    pub async fn get_app_tools(&self, connection_id: &str) -> Result<Vec<Tool>, ConnectionError> {
+        let connection = self.get_connection(connection_id).await
+            .ok_or_else(|| ConnectionError::NotFound(connection_id.to_string()))?;
+        
+        Ok(connection.get_app_tools().await)
+    }
+    
     /// Get all UI resources from all connections
     pub async fn get_all_ui_resources(&self) -> Vec<(String, UiResource)> {
         let mut all_resources = Vec::new();
@@ -374,6 +406,49 @@ impl ConnectionManager {
         })
     }
     
+    /// Registry of tools exposed by live views, shared with the view sessions
+    pub fn view_tools(&self) -> &ViewTools {
+        &self.view_tools
+    }
+    
+    /// Get the tools each live view exposes
+    pub fn get_view_tools(&self) -> Vec<ViewToolSet> {
+        self.view_tools.views()
+    }
+    
+    /// Call a tool exposed by a live view
+    pub async fn call_view_tool(
+        &self,
+        session_id: &str,
+        tool_name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<CallToolResult, ViewToolError> {
+        self.view_tools.call(session_id, tool_name, arguments).await
+    }
+    
+    /// Read a resource from a specific connection on behalf of a view
+    pub async fn read_resource(
+        &self,
+        connection_id: &str,
+        uri: &str,
+    ) -> Result<ReadResourceResult, ConnectionError> {
+        if connection_id == "embedded" {
+            let connection = self.get_connection(connection_id).await
+                .ok_or_else(|| ConnectionError::NotFound(connection_id.to_string()))?;
+            if !connection.is_ready() {
+                return Err(ConnectionError::NotReady(connection_id.to_string()));
+            }
+            let server = crate::server::EmbeddedServer::new();
+            return server.read_resource(uri).await.map_err(ConnectionError::ResourceNotFound);
+        }
+
+        // External connections only serve their UI resources in this demo
+        let content = self.read_ui_resource(connection_id, uri).await?;
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::text(content.text.unwrap_or_default(), uri)],
+        })
+    }
+    
     /// Read a UI resource from a specific connection
     pub async fn read_ui_resource(
         &self,
@@ -524,4 +599,14 @@ impl std::fmt::Display for ConnectionError {
     }
 }
 
+impl ConnectionError {
+    /// JSON-RPC error code for the failure
+    pub fn code(&self) -> i32 {
+        match self {
+            ConnectionError::ResourceNotFound(_) | ConnectionError::ToolNotFound(_) => error_codes::INVALID_PARAMS,
+            _ => error_codes::INTERNAL_ERROR,
+        }
+    }
+}
+
 impl std::error::Error for ConnectionError {}
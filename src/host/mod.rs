@@ -3,22 +3,57 @@
 //! This module manages connections to external MCP servers and handles
 //! the host-side responsibilities of the MCP Apps specification.
 
+pub mod audit;
 pub mod connection;
+pub mod conversation;
+pub mod links;
 pub mod manager;
+pub mod model_context;
+pub mod origins;
+pub mod partial_input;
+pub mod permissions;
+pub mod prompts;
+pub mod rate_limit;
+pub mod theme;
 pub mod transport;
+pub mod view_log;
+pub mod view_tools;
 
+pub use audit::*;
 pub use connection::*;
+pub use conversation::*;
+pub use links::*;
 pub use manager::*;
+pub use model_context::*;
+pub use origins::*;
+pub use partial_input::*;
+pub use permissions::*;
+pub use prompts::*;
+pub use rate_limit::*;
+pub use theme::*;
+pub use view_log::*;
+pub use view_tools::*;
 
 use crate::protocol::{
     DisplayMode, McpUiAppCapabilities, UiHostCapabilities, ServerToolsCapability,
     ServerResourcesCapability, SandboxCapability, UiPermissions, HostContext, ToolInfo,
     ContainerDimensions, Platform, DeviceCapabilities, SafeAreaInsets, ApprovedCsp,
+    NegotiatedCapabilities, AppInfo,
 };
 use crate::protocol::messages::Message;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
 
+/// Current time in milliseconds since the Unix epoch, as host records store it
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
 
 /// Host state for MCP Apps
 #[derive(Debug, Clone, PartialEq)]
@@ -31,6 +66,8 @@ pub struct HostState {
     pub supported_display_modes: Vec<DisplayMode>,
     /// Current theme
     pub theme: String,
+    /// Font loading CSS (`@font-face` or `@import`) delivered with the theme
+    pub fonts_css: Option<String>,
     /// Platform type
     pub platform: Platform,
     /// Device capabilities
@@ -41,6 +78,18 @@ pub struct HostState {
     pub locale: String,
     /// User timezone
     pub time_zone: String,
+    /// Domains the host approves for view CSPs
+    ///
+    /// `None` allows any syntactically valid origin the server declares.
+    pub approved_csp: Option<ApprovedCsp>,
+    /// Sandbox permissions the host can grant, subject to user consent
+    pub grantable_permissions: UiPermissions,
+    /// Links each server's views may open, by server ID
+    pub link_policies: HashMap<String, LinkPolicy>,
+    /// Origins views may claim through `_meta.ui.domain`, besides proxy subdomains
+    pub approved_app_domains: Vec<String>,
+    /// Install the legacy `window.mcp` bridge API in views alongside the App SDK
+    pub legacy_bridge: bool,
 }
 
 impl Default for HostState {
@@ -48,8 +97,9 @@ impl Default for HostState {
         Self {
             name: "mcp-apps-host".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
-            supported_display_modes: vec![DisplayMode::Inline],
+            supported_display_modes: vec![DisplayMode::Inline, DisplayMode::Fullscreen, DisplayMode::Pip],
             theme: "light".to_string(),
+            fonts_css: None,
             platform: Platform::Desktop,
             device_capabilities: DeviceCapabilities {
                 touch: Some(false),
@@ -63,6 +113,11 @@ impl Default for HostState {
             },
             locale: "en-US".to_string(),
             time_zone: "UTC".to_string(),
+            approved_csp: None,
+            grantable_permissions: UiPermissions::all(),
+            link_policies: HashMap::new(),
+            approved_app_domains: Vec::new(),
+            legacy_bridge: false,
         }
     }
 }
@@ -77,13 +132,8 @@ impl HostState {
             server_resources: Some(ServerResourcesCapability { list_changed: Some(true) }),
             logging: Some(crate::protocol::capabilities::Empty {}),
             sandbox: Some(SandboxCapability {
-                permissions: Some(UiPermissions {
-                    camera: None,
-                    microphone: None,
-                    geolocation: None,
-                    clipboard_write: None,
-                }),
-                csp: Some(crate::protocol::ApprovedCsp::default()),
+                permissions: Some(self.grantable_permissions.clone()),
+                csp: self.approved_csp.clone(),
             }),
         }
     }
@@ -103,7 +153,7 @@ impl HostState {
         HostContext {
             tool_info: None,
             theme: Some(self.theme.clone()),
-            styles: None,
+            styles: Some(theme_styles(&self.theme, self.fonts_css.clone())),
             display_mode: Some(DisplayMode::Inline),
             available_display_modes: Some(self.supported_display_modes.clone()),
             container_dimensions: Some(self.container_dimensions.clone()),
@@ -128,6 +178,12 @@ impl HostState {
         self
     }
     
+    /// Builder method: Set the font loading CSS sent with the theme
+    pub fn with_fonts_css(mut self, css: impl Into<String>) -> Self {
+        self.fonts_css = Some(css.into());
+        self
+    }
+    
     /// Builder method: Set platform
     pub fn with_platform(mut self, platform: Platform) -> Self {
         self.platform = platform;
@@ -145,6 +201,84 @@ impl HostState {
         self.time_zone = tz.into();
         self
     }
+    
+    /// Builder method: Restrict view CSPs to the given approved domains
+    pub fn with_approved_csp(mut self, csp: ApprovedCsp) -> Self {
+        self.approved_csp = Some(csp);
+        self
+    }
+    
+    /// Builder method: Set the link policy for a server's views
+    pub fn with_link_policy(mut self, server_id: impl Into<String>, policy: LinkPolicy) -> Self {
+        self.link_policies.insert(server_id.into(), policy);
+        self
+    }
+    
+    /// Link policy for a server's views (any valid link if none is set)
+    pub fn link_policy(&self, server_id: &str) -> LinkPolicy {
+        self.link_policies.get(server_id).cloned().unwrap_or_default()
+    }
+    
+    /// Builder method: Set the origins views may claim as their `domain`
+    pub fn with_approved_app_domains(mut self, domains: Vec<String>) -> Self {
+        self.approved_app_domains = domains;
+        self
+    }
+    
+    /// Builder method: Set the sandbox permissions the host can grant
+    pub fn with_grantable_permissions(mut self, permissions: UiPermissions) -> Self {
+        self.grantable_permissions = permissions;
+        self
+    }
+    
+    /// Builder method: Serve views the legacy `window.mcp` bridge API
+    pub fn with_legacy_bridge(mut self, enabled: bool) -> Self {
+        self.legacy_bridge = enabled;
+        self
+    }
+}
+
+/// Shared host state that notifies subscribers when it changes
+///
+/// Clones are handles to the same state, so the UI can update the theme or
+/// container size in one place and every live view is told about it.
+#[derive(Debug, Clone)]
+pub struct ObservableHostState {
+    tx: Arc<watch::Sender<HostState>>,
+}
+
+impl ObservableHostState {
+    pub fn new(state: HostState) -> Self {
+        let (tx, _rx) = watch::channel(state);
+        Self { tx: Arc::new(tx) }
+    }
+
+    /// Snapshot of the current state
+    pub fn get(&self) -> HostState {
+        self.tx.borrow().clone()
+    }
+
+    /// Modify the state in place, waking subscribers if anything changed
+    ///
+    /// Returns whether the state changed.
+    pub fn update(&self, f: impl FnOnce(&mut HostState)) -> bool {
+        self.tx.send_if_modified(|state| {
+            let before = state.clone();
+            f(state);
+            *state != before
+        })
+    }
+
+    /// Subscribe to state changes
+    pub fn subscribe(&self) -> watch::Receiver<HostState> {
+        self.tx.subscribe()
+    }
+}
+
+impl Default for ObservableHostState {
+    fn default() -> Self {
+        Self::new(HostState::default())
+    }
 }
 
 /// Information about an active UI session
@@ -158,10 +292,18 @@ pub struct UiSession {
     pub resource_uri: String,
     /// Tool that triggered this session (if any)
     pub tool_info: Option<ToolInfo>,
+    /// How the view is currently displayed
+    pub display_mode: DisplayMode,
     /// Current state
     pub state: UiSessionState,
+    /// App name and version received during initialization
+    pub app_info: Option<AppInfo>,
     /// App capabilities received during initialization
     pub app_capabilities: Option<McpUiAppCapabilities>,
+    /// Capabilities negotiated during ui/initialize
+    pub negotiated_capabilities: Option<NegotiatedCapabilities>,
+    /// Content size last reported by the view, as `(width, height)`
+    pub content_size: Option<(u32, u32)>,
     /// Session metadata
     pub metadata: HashMap<String, Value>,
 }
@@ -171,6 +313,8 @@ pub struct UiSession {
 pub enum UiSessionState {
     /// Initializing (waiting for ui/initialize)
     Initializing,
+    /// ui/initialize answered, waiting for ui/notifications/initialized
+    Handshaking,
     /// Initialized and ready
     Ready,
     /// Loading tool data
@@ -183,6 +327,13 @@ pub enum UiSessionState {
     Error(String),
 }
 
+impl UiSessionState {
+    /// Check if the view has completed the handshake and may receive notifications
+    pub fn is_initialized(&self) -> bool {
+        matches!(self, UiSessionState::Ready | UiSessionState::Loading | UiSessionState::Active)
+    }
+}
+
 impl UiSession {
     pub fn new(id: impl Into<String>, server_id: impl Into<String>, resource_uri: impl Into<String>) -> Self {
         Self {
@@ -190,8 +341,12 @@ impl UiSession {
             server_id: server_id.into(),
             resource_uri: resource_uri.into(),
             tool_info: None,
+            display_mode: DisplayMode::Inline,
             state: UiSessionState::Initializing,
+            app_info: None,
             app_capabilities: None,
+            negotiated_capabilities: None,
+            content_size: None,
             metadata: HashMap::new(),
         }
     }
@@ -217,7 +372,7 @@ pub enum UiSessionEvent {
     ToolCancelled { session_id: String, reason: Option<String> },
     /// Display mode changed
     DisplayModeChanged { session_id: String, mode: DisplayMode },
-    /// Size changed notification
+    /// The inline container was resized to fit the view's content
     SizeChanged { session_id: String, width: u32, height: u32 },
     /// Session error
     Error { session_id: String, error: String },
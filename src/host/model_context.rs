@@ -0,0 +1,290 @@
+//! Model Context
+//!
+//! Views tell the model about their state with `ui/update-model-context`.
+//! Each update replaces the previous one from the same view. The
+//! [`ModelContextStore`] keeps the latest update per view session, within
+//! size limits, for the agent to include on its next turn.
+
+use crate::host::{now_millis, UiSession};
+use crate::protocol::{error_codes, UpdateModelContextRequest};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Largest update a single view may store, in serialized bytes
+pub const DEFAULT_MAX_ENTRY_BYTES: usize = 16 * 1024;
+
+/// Largest combined context across all views, in serialized bytes
+pub const DEFAULT_MAX_TOTAL_BYTES: usize = 64 * 1024;
+
+/// The latest context a view provided
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelContextEntry {
+    pub session_id: String,
+    pub server_id: String,
+    pub resource_uri: String,
+    /// Name the view gave during initialization
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+    /// Serialized size of the update in bytes
+    pub size: usize,
+    /// Milliseconds since the Unix epoch
+    pub updated_at: u64,
+}
+
+impl ModelContextEntry {
+    /// Content blocks presenting this entry to the model
+    ///
+    /// A text block names the view, followed by its content blocks and its
+    /// structured content as JSON.
+    pub fn to_content_blocks(&self) -> Vec<Value> {
+        let source = match &self.app_name {
+            Some(name) => format!("{} ({} on {})", name, self.resource_uri, self.server_id),
+            None => format!("{} on {}", self.resource_uri, self.server_id),
+        };
+        let mut blocks = vec![json!({ "type": "text", "text": format!("Context from app view {}:", source) })];
+        blocks.extend(self.content.iter().flatten().cloned());
+        if let Some(structured) = &self.structured_content {
+            blocks.push(json!({ "type": "text", "text": structured.to_string() }));
+        }
+        blocks
+    }
+}
+
+/// Why a context update was not stored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelContextError {
+    /// The update is larger than a single view may store
+    TooLarge { size: usize, limit: usize },
+}
+
+impl ModelContextError {
+    /// JSON-RPC error code reported to the view
+    pub fn code(&self) -> i32 {
+        match self {
+            ModelContextError::TooLarge { .. } => error_codes::SERVER_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for ModelContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelContextError::TooLarge { size, limit } => {
+                write!(f, "context update of {} bytes exceeds the limit of {} bytes", size, limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModelContextError {}
+
+/// A change to the stored context
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelContextEvent {
+    /// A view's context was replaced
+    Updated(ModelContextEntry),
+    /// A view's context was cleared, evicted or its session closed
+    Removed { session_id: String },
+}
+
+/// Latest model context from each view session
+///
+/// Cloning yields another handle to the same store.
+#[derive(Debug, Clone)]
+pub struct ModelContextStore {
+    max_entry_bytes: usize,
+    max_total_bytes: usize,
+    /// Entries, least recently updated first
+    entries: Arc<Mutex<Vec<ModelContextEntry>>>,
+    events: broadcast::Sender<ModelContextEvent>,
+}
+
+impl ModelContextStore {
+    pub fn new() -> Self {
+        Self {
+            max_entry_bytes: DEFAULT_MAX_ENTRY_BYTES,
+            max_total_bytes: DEFAULT_MAX_TOTAL_BYTES,
+            entries: Arc::new(Mutex::new(Vec::new())),
+            events: broadcast::channel(32).0,
+        }
+    }
+
+    /// Builder method: Set the per-view and combined size limits in bytes
+    pub fn with_limits(mut self, max_entry_bytes: usize, max_total_bytes: usize) -> Self {
+        self.max_entry_bytes = max_entry_bytes;
+        self.max_total_bytes = max_total_bytes.max(max_entry_bytes);
+        self
+    }
+
+    /// Subscribe to context changes
+    pub fn subscribe(&self) -> broadcast::Receiver<ModelContextEvent> {
+        self.events.subscribe()
+    }
+
+    /// Stored entries, least recently updated first
+    pub fn entries(&self) -> Vec<ModelContextEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    /// Stored context for one view session
+    pub fn get(&self, session_id: &str) -> Option<ModelContextEntry> {
+        self.entries.lock().unwrap().iter().find(|e| e.session_id == session_id).cloned()
+    }
+
+    /// Combined context for the agent's next turn, as content blocks
+    pub fn combined(&self) -> Vec<Value> {
+        self.entries.lock().unwrap().iter().flat_map(|e| e.to_content_blocks()).collect()
+    }
+
+    /// Replace a view's context with its latest update
+    ///
+    /// An update with neither content nor structured content clears the
+    /// view's context. Identical updates are ignored. When the combined size
+    /// would exceed the limit, the least recently updated views are evicted.
+    /// Returns whether the stored context changed.
+    pub fn update(&self, session: &UiSession, request: UpdateModelContextRequest) -> Result<bool, ModelContextError> {
+        if request.content.is_none() && request.structured_content.is_none() {
+            return Ok(self.remove(&session.id));
+        }
+
+        let size = serde_json::to_vec(&request).map(|json| json.len()).unwrap_or(usize::MAX);
+        if size > self.max_entry_bytes {
+            return Err(ModelContextError::TooLarge { size, limit: self.max_entry_bytes });
+        }
+
+        let entry = ModelContextEntry {
+            session_id: session.id.clone(),
+            server_id: session.server_id.clone(),
+            resource_uri: session.resource_uri.clone(),
+            app_name: session.app_info.as_ref().map(|a| a.name.clone()),
+            content: request.content,
+            structured_content: request.structured_content,
+            size,
+            updated_at: now_millis(),
+        };
+
+        let evicted = {
+            let mut entries = self.entries.lock().unwrap();
+            if let Some(at) = entries.iter().position(|e| e.session_id == entry.session_id) {
+                let previous = &entries[at];
+                if previous.content == entry.content && previous.structured_content == entry.structured_content {
+                    return Ok(false);
+                }
+                entries.remove(at);
+            }
+
+            let mut evicted = Vec::new();
+            let mut total: usize = entries.iter().map(|e| e.size).sum::<usize>() + entry.size;
+            while total > self.max_total_bytes && !entries.is_empty() {
+                let oldest = entries.remove(0);
+                total -= oldest.size;
+                evicted.push(oldest.session_id);
+            }
+            entries.push(entry.clone());
+            evicted
+        };
+
+        for session_id in evicted {
+            log::info!("Evicted model context of view {} to stay within the size limit", session_id);
+            let _ = self.events.send(ModelContextEvent::Removed { session_id });
+        }
+        let _ = self.events.send(ModelContextEvent::Updated(entry));
+        Ok(true)
+    }
+
+    /// Drop a view's context
+    ///
+    /// Returns whether there was any.
+    pub fn remove(&self, session_id: &str) -> bool {
+        let removed = {
+            let mut entries = self.entries.lock().unwrap();
+            let before = entries.len();
+            entries.retain(|e| e.session_id != session_id);
+            entries.len() != before
+        };
+        if removed {
+            let _ = self.events.send(ModelContextEvent::Removed { session_id: session_id.to_string() });
+        }
+        removed
+    }
+}
+
+impl Default for ModelContextStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_update(text: &str) -> UpdateModelContextRequest {
+        UpdateModelContextRequest {
+            content: Some(vec![json!({ "type": "text", "text": text })]),
+            structured_content: None,
+        }
+    }
+
+    #[test]
+    fn test_latest_update_per_session() {
+        let store = ModelContextStore::new();
+        let mut events = store.subscribe();
+        let a = UiSession::new("a", "weather", "ui://weather/view");
+        let b = UiSession::new("b", "maps", "ui://maps/view");
+
+        assert_eq!(store.update(&a, text_update("Paris, 18°C")), Ok(true));
+        assert_eq!(store.update(&b, text_update("Route: 12 km")), Ok(true));
+        assert_eq!(store.update(&a, text_update("Lyon, 21°C")), Ok(true));
+        // Identical updates are deduplicated
+        assert_eq!(store.update(&a, text_update("Lyon, 21°C")), Ok(false));
+
+        let entries = store.entries();
+        assert_eq!(entries.iter().map(|e| e.session_id.as_str()).collect::<Vec<_>>(), ["b", "a"]);
+        assert_eq!(store.get("a").unwrap().content, Some(vec![json!({ "type": "text", "text": "Lyon, 21°C" })]));
+
+        let combined = store.combined();
+        assert_eq!(combined.len(), 4);
+        assert_eq!(combined[0]["text"], json!("Context from app view ui://maps/view on maps:"));
+        assert_eq!(combined[3]["text"], json!("Lyon, 21°C"));
+
+        for _ in 0..3 {
+            assert!(matches!(events.try_recv(), Ok(ModelContextEvent::Updated(_))));
+        }
+        assert!(events.try_recv().is_err());
+
+        // An empty update clears the view's context
+        let empty = UpdateModelContextRequest { content: None, structured_content: None };
+        assert_eq!(store.update(&a, empty), Ok(true));
+        assert_eq!(events.try_recv(), Ok(ModelContextEvent::Removed { session_id: "a".into() }));
+        assert!(store.get("a").is_none());
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let store = ModelContextStore::new().with_limits(100, 200);
+        let big = UpdateModelContextRequest { content: None, structured_content: Some(json!({ "rows": "x".repeat(200) })) };
+        let session = |id: &str| UiSession::new(id, "s", format!("ui://s/{}", id));
+
+        let err = store.update(&session("a"), big).unwrap_err();
+        assert!(matches!(err, ModelContextError::TooLarge { limit: 100, .. }));
+        assert!(store.entries().is_empty());
+
+        // Older views are evicted to keep the combined size under the limit
+        for id in ["a", "b", "c", "d"] {
+            store.update(&session(id), text_update(&"y".repeat(40))).unwrap();
+        }
+        let kept: Vec<String> = store.entries().into_iter().map(|e| e.session_id).collect();
+        assert!(!kept.contains(&"a".to_string()));
+        assert_eq!(kept.last().map(String::as_str), Some("d"));
+        assert!(store.entries().iter().map(|e| e.size).sum::<usize>() <= 200);
+    }
+}
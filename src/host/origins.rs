@@ -0,0 +1,282 @@
+//! Dedicated View Origins
+//!
+//! Views are served through the sandbox proxy from an origin of their own so
+//! that storage, OAuth callbacks and CORS allowlists stay stable across loads.
+//! The origin is derived from a hash of the server and resource by default,
+//! or taken from `_meta.ui.domain` when the declared domain passes policy.
+//! [`AppOrigins`] keeps two live views from sharing a declared origin.
+
+use crate::host::UiSession;
+use crate::protocol::{approved_covers, CspOrigin, CspOriginError};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Bytes of the server/resource hash used as the origin's subdomain label
+const ORIGIN_HASH_BYTES: usize = 16;
+
+/// Bytes of the server hash naming the `localhost` subdomains a server may declare
+const SERVER_HASH_BYTES: usize = 8;
+
+/// Why a declared `domain` was not used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppDomainError {
+    /// Not a valid origin
+    Invalid(CspOriginError),
+    /// Only `http` and `https` origins can serve a view
+    MissingScheme,
+    /// Wildcards cannot name a single origin
+    Wildcard,
+    /// Neither approved by the host nor routed to its sandbox proxy
+    NotApproved,
+    /// A proxy subdomain outside the declaring server's own namespace
+    OtherServer,
+    /// Already serving another live view
+    InUse,
+}
+
+impl fmt::Display for AppDomainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppDomainError::Invalid(e) => write!(f, "invalid domain: {}", e),
+            AppDomainError::MissingScheme => write!(f, "domain must be an http or https origin"),
+            AppDomainError::Wildcard => write!(f, "domain must not be a wildcard"),
+            AppDomainError::NotApproved => write!(f, "domain is not approved by the host"),
+            AppDomainError::OtherServer => write!(f, "domain belongs to another server"),
+            AppDomainError::InUse => write!(f, "domain is in use by another view"),
+        }
+    }
+}
+
+impl std::error::Error for AppDomainError {}
+
+/// Origin a view is served from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppOrigin {
+    /// Derived from the server and resource
+    Hashed(String),
+    /// The resource's declared `domain`
+    Declared(String),
+}
+
+impl AppOrigin {
+    pub fn as_str(&self) -> &str {
+        match self {
+            AppOrigin::Hashed(origin) | AppOrigin::Declared(origin) => origin,
+        }
+    }
+}
+
+impl fmt::Display for AppOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Subdomain label identifying a server's resource
+pub fn app_origin_label(server_id: &str, resource_uri: &str) -> String {
+    let digest = Sha256::new()
+        .chain_update(server_id.as_bytes())
+        .chain_update([0])
+        .chain_update(resource_uri.as_bytes())
+        .finalize();
+    digest[..ORIGIN_HASH_BYTES].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Subdomain label of `localhost` under which a server may declare view domains
+pub fn app_server_label(server_id: &str) -> String {
+    let digest = Sha256::digest(server_id.as_bytes());
+    digest[..SERVER_HASH_BYTES].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hash-based origin for a view under the sandbox proxy
+///
+/// The label becomes a subdomain of the proxy host; a proxy on a loopback
+/// address uses `localhost`, whose subdomains also resolve to loopback.
+pub fn hashed_app_origin(proxy_origin: &str, server_id: &str, resource_uri: &str) -> Result<String, CspOriginError> {
+    let proxy = CspOrigin::parse(proxy_origin)?;
+    let is_ip = proxy.host.chars().all(|c| c.is_ascii_digit() || c == '.');
+    let host = if is_ip || proxy.host == "localhost" { "localhost" } else { proxy.host.as_str() };
+    let origin = CspOrigin {
+        scheme: Some(proxy.scheme.unwrap_or_else(|| "http".to_string())),
+        host: format!("{}.{}", app_origin_label(server_id, resource_uri), host),
+        port: proxy.port,
+    };
+    Ok(origin.to_string())
+}
+
+/// Check a declared `domain` against host policy
+///
+/// The domain is accepted if the host approves it, or if it reaches the proxy
+/// as a subdomain of the declaring server's `<label>.localhost` namespace (see
+/// [`app_server_label`]) on the proxy's scheme and port.
+pub fn validate_app_domain(
+    domain: &str,
+    proxy_origin: &str,
+    server_id: &str,
+    approved: &[String],
+) -> Result<String, AppDomainError> {
+    let origin = CspOrigin::parse(domain).map_err(AppDomainError::Invalid)?;
+    if !matches!(origin.scheme.as_deref(), Some("http" | "https")) {
+        return Err(AppDomainError::MissingScheme);
+    }
+    if origin.is_wildcard() {
+        return Err(AppDomainError::Wildcard);
+    }
+
+    let routed_to_proxy = CspOrigin::parse(proxy_origin).is_ok_and(|proxy| {
+        origin.host.ends_with(".localhost")
+            && origin.scheme == Some(proxy.scheme.unwrap_or_else(|| "http".to_string()))
+            && origin.port == proxy.port
+    });
+    if approved_covers(approved, &origin) {
+        return Ok(origin.to_string());
+    }
+    if !routed_to_proxy {
+        return Err(AppDomainError::NotApproved);
+    }
+    let namespace = format!("{}.localhost", app_server_label(server_id));
+    if origin.host != namespace && !origin.host.ends_with(&format!(".{}", namespace)) {
+        return Err(AppDomainError::OtherServer);
+    }
+    Ok(origin.to_string())
+}
+
+/// Origins held by live views
+///
+/// Cloning yields another handle to the same registry.
+#[derive(Debug, Clone, Default)]
+pub struct AppOrigins {
+    /// Session holding each origin
+    held: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl AppOrigins {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Origin a view should be served from, held for the session until released
+    ///
+    /// Uses the declared `domain` if it passes [`validate_app_domain`] and no
+    /// other session holds it, otherwise the hash-based origin. Returns `None`
+    /// if the proxy origin is unusable.
+    pub fn resolve(
+        &self,
+        session: &UiSession,
+        proxy_origin: &str,
+        declared: Option<&str>,
+        approved: &[String],
+    ) -> Option<AppOrigin> {
+        let mut held = self.held.lock().unwrap();
+        if let Some(domain) = declared {
+            let origin = validate_app_domain(domain, proxy_origin, &session.server_id, approved).and_then(|origin| {
+                match held.get(&origin) {
+                    Some(holder) if *holder != session.id => Err(AppDomainError::InUse),
+                    _ => Ok(origin),
+                }
+            });
+            match origin {
+                Ok(origin) => {
+                    held.insert(origin.clone(), session.id.clone());
+                    return Some(AppOrigin::Declared(origin));
+                }
+                Err(e) => log::warn!("Ignoring domain {} declared by {}: {}", domain, session.resource_uri, e),
+            }
+        }
+
+        match hashed_app_origin(proxy_origin, &session.server_id, &session.resource_uri) {
+            Ok(origin) => {
+                held.insert(origin.clone(), session.id.clone());
+                Some(AppOrigin::Hashed(origin))
+            }
+            Err(e) => {
+                log::warn!("Sandbox proxy origin {} is unusable: {}", proxy_origin, e);
+                None
+            }
+        }
+    }
+
+    /// Release the origins a session holds
+    pub fn release(&self, session_id: &str) {
+        self.held.lock().unwrap().retain(|_, holder| holder != session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROXY: &str = "http://127.0.0.1:8765";
+
+    #[test]
+    fn test_hashed_origins_are_deterministic() {
+        let a = hashed_app_origin(PROXY, "weather", "ui://weather/view").unwrap();
+        assert_eq!(a, hashed_app_origin(PROXY, "weather", "ui://weather/view").unwrap());
+        assert_ne!(a, hashed_app_origin(PROXY, "weather", "ui://weather/other").unwrap());
+        assert_ne!(a, hashed_app_origin(PROXY, "maps", "ui://weather/view").unwrap());
+
+        let label = app_origin_label("weather", "ui://weather/view");
+        assert_eq!(label.len(), ORIGIN_HASH_BYTES * 2);
+        assert_eq!(a, format!("http://{}.localhost:8765", label));
+        assert!(CspOrigin::parse(&a).is_ok());
+
+        let remote = hashed_app_origin("https://sandbox.example.net", "weather", "ui://weather/view").unwrap();
+        assert_eq!(remote, format!("https://{}.sandbox.example.net", label));
+    }
+
+    #[test]
+    fn test_declared_domains_are_validated() {
+        let approved = vec!["https://*.apps.example.com".to_string()];
+        let own = format!("http://charts.{}.localhost:8765", app_server_label("weather"));
+
+        assert_eq!(validate_app_domain(&own, PROXY, "weather", &[]), Ok(own.clone()));
+        assert_eq!(
+            validate_app_domain("https://weather.apps.example.com", PROXY, "weather", &approved),
+            Ok("https://weather.apps.example.com".to_string())
+        );
+        assert_eq!(validate_app_domain(&own, PROXY, "maps", &[]), Err(AppDomainError::OtherServer));
+        assert_eq!(
+            validate_app_domain("http://weather.localhost:8765", PROXY, "weather", &[]),
+            Err(AppDomainError::OtherServer)
+        );
+        assert_eq!(
+            validate_app_domain(&own.replace("8765", "9000"), PROXY, "weather", &[]),
+            Err(AppDomainError::NotApproved)
+        );
+        assert_eq!(validate_app_domain("https://evil.com", PROXY, "weather", &approved), Err(AppDomainError::NotApproved));
+        assert_eq!(validate_app_domain("weather.localhost", PROXY, "weather", &[]), Err(AppDomainError::MissingScheme));
+        assert_eq!(
+            validate_app_domain("https://*.apps.example.com", PROXY, "weather", &approved),
+            Err(AppDomainError::Wildcard)
+        );
+        assert!(matches!(
+            validate_app_domain("https://a.com/path", PROXY, "weather", &approved),
+            Err(AppDomainError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_origins_are_not_shared_between_servers() {
+        let origins = AppOrigins::new();
+        let approved = vec!["https://*.apps.example.com".to_string()];
+        let a = UiSession::new("a1", "server-a", "ui://a/view");
+        let b = UiSession::new("b1", "server-b", "ui://b/view");
+        let hashed_b = AppOrigin::Hashed(hashed_app_origin(PROXY, "server-b", "ui://b/view").unwrap());
+
+        // Server B cannot claim A's namespace or A's hash-based origin
+        let a_domain = format!("http://app.{}.localhost:8765", app_server_label("server-a"));
+        assert_eq!(origins.resolve(&a, PROXY, Some(&a_domain), &[]), Some(AppOrigin::Declared(a_domain.clone())));
+        assert_eq!(origins.resolve(&b, PROXY, Some(&a_domain), &[]), Some(hashed_b.clone()));
+        let a_hashed = hashed_app_origin(PROXY, "server-a", "ui://a/view").unwrap();
+        assert_eq!(origins.resolve(&b, PROXY, Some(&a_hashed), &[]), Some(hashed_b.clone()));
+
+        // An approved domain serves one live view at a time
+        let shared = "https://charts.apps.example.com";
+        assert_eq!(origins.resolve(&a, PROXY, Some(shared), &approved), Some(AppOrigin::Declared(shared.to_string())));
+        assert_eq!(origins.resolve(&b, PROXY, Some(shared), &approved), Some(hashed_b));
+        origins.release("a1");
+        assert_eq!(origins.resolve(&b, PROXY, Some(shared), &approved), Some(AppOrigin::Declared(shared.to_string())));
+    }
+}
@@ -0,0 +1,240 @@
+//! Partial Tool Input
+//!
+//! Accumulates tool arguments streamed token by token from the agent and
+//! repairs the incomplete JSON into a valid object so views can render
+//! `ui/notifications/tool-input-partial` previews while the call is built.
+
+use serde_json::Value;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Default minimum interval between partial input notifications
+pub const DEFAULT_PARTIAL_INPUT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Container frame tracked while scanning partial JSON
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frame {
+    Object { expecting_key: bool },
+    Array,
+}
+
+impl Frame {
+    fn closer(&self) -> char {
+        match self {
+            Frame::Object { .. } => '}',
+            Frame::Array => ']',
+        }
+    }
+}
+
+fn closers(stack: &[Frame]) -> String {
+    stack.iter().rev().map(Frame::closer).collect()
+}
+
+/// Best-effort repair of a truncated JSON document
+///
+/// Open strings are closed, open containers are closed in order, and any
+/// trailing incomplete token (a dangling key, `:` or `,`, or a half-written
+/// literal) is dropped. Returns `None` if nothing valid can be recovered.
+pub fn repair_partial_json(input: &str) -> Option<Value> {
+    if let Ok(value) = serde_json::from_str(input) {
+        return Some(value);
+    }
+
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut in_string = false;
+    let mut string_is_key = false;
+    let mut escape = false;
+    // Where the escape sequence last started in a string, at its backslash
+    let mut last_escape: Option<usize> = None;
+    // Last prefix known to end on a complete value, with the closers it needs
+    let mut checkpoint: Option<(usize, String)> = None;
+
+    for (i, c) in input.char_indices() {
+        if in_string {
+            if escape {
+                escape = false;
+            } else if c == '\\' {
+                escape = true;
+                last_escape = Some(i);
+            } else if c == '"' {
+                in_string = false;
+                if !string_is_key {
+                    checkpoint = Some((i + 1, closers(&stack)));
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                last_escape = None;
+                string_is_key = matches!(stack.last(), Some(Frame::Object { expecting_key: true }));
+            }
+            '{' => {
+                stack.push(Frame::Object { expecting_key: true });
+                checkpoint = Some((i + 1, closers(&stack)));
+            }
+            '[' => {
+                stack.push(Frame::Array);
+                checkpoint = Some((i + 1, closers(&stack)));
+            }
+            '}' | ']' => {
+                stack.pop();
+                checkpoint = Some((i + 1, closers(&stack)));
+            }
+            ':' => {
+                if let Some(Frame::Object { expecting_key }) = stack.last_mut() {
+                    *expecting_key = false;
+                }
+            }
+            ',' => {
+                // Everything before the comma is a complete value
+                checkpoint = Some((i, closers(&stack)));
+                if let Some(Frame::Object { expecting_key }) = stack.last_mut() {
+                    *expecting_key = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // A value string still being written: close it so the text streams in
+    if in_string && !string_is_key {
+        let mut prefix = input.to_string();
+        // Drop a dangling backslash or an unfinished `\uXXXX` escape
+        if let Some(pos) = last_escape {
+            let unfinished_unicode = input[pos + 1..].starts_with('u') && input.len() - pos < 6;
+            if escape || unfinished_unicode {
+                prefix.truncate(pos);
+            }
+        }
+        let candidate = format!("{}\"{}", prefix, closers(&stack));
+        if let Ok(value) = serde_json::from_str(&candidate) {
+            return Some(value);
+        }
+    }
+
+    // A trailing number or literal that is already complete
+    if !in_string {
+        let candidate = format!("{}{}", input.trim_end(), closers(&stack));
+        if let Ok(value) = serde_json::from_str(&candidate) {
+            return Some(value);
+        }
+    }
+
+    let (end, closing) = checkpoint?;
+    let candidate = format!("{}{}", input[..end].trim_end(), closing);
+    serde_json::from_str(&candidate).ok()
+}
+
+/// Accumulator for streamed tool arguments with throttled previews
+#[derive(Debug, Clone)]
+pub struct PartialToolInput {
+    /// Raw argument text received so far
+    buffer: String,
+    /// Minimum time between emitted previews
+    interval: Duration,
+    /// When the last preview was emitted
+    last_emitted_at: Option<Instant>,
+    /// Last preview emitted, to skip unchanged repeats
+    last_emitted: Option<Value>,
+}
+
+impl PartialToolInput {
+    /// Create an accumulator emitting at most one preview per `interval`
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            buffer: String::new(),
+            interval,
+            last_emitted_at: None,
+            last_emitted: None,
+        }
+    }
+
+    /// Minimum time between emitted previews
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Raw argument text received so far
+    pub fn buffer(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Append a chunk and return a repaired preview if one is due
+    ///
+    /// Previews are only returned when they repair to a JSON object, differ
+    /// from the last preview, and the throttle interval has elapsed.
+    pub fn push(&mut self, chunk: &str, now: Instant) -> Option<Value> {
+        self.buffer.push_str(chunk);
+
+        if let Some(last) = self.last_emitted_at {
+            if now.duration_since(last) < self.interval {
+                return None;
+            }
+        }
+
+        let preview = repair_partial_json(&self.buffer).filter(|v| v.is_object())?;
+        if self.last_emitted.as_ref() == Some(&preview) {
+            return None;
+        }
+
+        self.last_emitted_at = Some(now);
+        self.last_emitted = Some(preview.clone());
+        Some(preview)
+    }
+
+    /// Parse the complete argument text
+    pub fn finish(&self) -> Result<Value, serde_json::Error> {
+        serde_json::from_str(&self.buffer)
+    }
+}
+
+impl Default for PartialToolInput {
+    fn default() -> Self {
+        Self::new(DEFAULT_PARTIAL_INPUT_INTERVAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_repair_partial_json() {
+        assert_eq!(repair_partial_json(r#"{"location": "San Fr"#), Some(json!({ "location": "San Fr" })));
+        assert_eq!(repair_partial_json(r#"{"a": 1, "b"#), Some(json!({ "a": 1 })));
+        assert_eq!(repair_partial_json(r#"{"a": 1, "b":"#), Some(json!({ "a": 1 })));
+        assert_eq!(repair_partial_json(r#"{"a": 12"#), Some(json!({ "a": 12 })));
+        assert_eq!(repair_partial_json(r#"{"a": tr"#), Some(json!({})));
+        assert_eq!(repair_partial_json(r#"{"items": [1, 2, {"x": "y"#), Some(json!({ "items": [1, 2, { "x": "y" }] })));
+        assert_eq!(repair_partial_json(r#"{"text": "line\"#), Some(json!({ "text": "line" })));
+        assert_eq!(repair_partial_json(r#"{"text": "caf\u00"#), Some(json!({ "text": "caf" })));
+        // An escaped backslash followed by `u` is plain text
+        assert_eq!(repair_partial_json(r#"{"path": "C:\\us"#), Some(json!({ "path": "C:\\us" })));
+        assert_eq!(repair_partial_json(r#"{"a": {"b": [true, false]}, "c": "d"}"#), Some(json!({ "a": { "b": [true, false] }, "c": "d" })));
+        assert_eq!(repair_partial_json(""), None);
+    }
+
+    #[test]
+    fn test_partial_input_throttling() {
+        let start = Instant::now();
+        let mut input = PartialToolInput::new(Duration::from_millis(100));
+
+        assert_eq!(input.push(r#"{"loc"#, start), Some(json!({})));
+        // Within the interval nothing is emitted
+        assert_eq!(input.push(r#"ation": "Par"#, start + Duration::from_millis(50)), None);
+        assert_eq!(
+            input.push(r#"is"#, start + Duration::from_millis(150)),
+            Some(json!({ "location": "Paris" }))
+        );
+        // Unchanged previews are skipped
+        assert_eq!(input.push("\"", start + Duration::from_millis(300)), None);
+
+        input.push("}", start + Duration::from_millis(400));
+        assert_eq!(input.finish().unwrap(), json!({ "location": "Paris" }));
+    }
+}
@@ -0,0 +1,346 @@
+//! Sandbox Permission Consent
+//!
+//! Views may request camera, microphone, geolocation and clipboard access
+//! through `_meta.ui.permissions`. The [`PermissionBroker`] asks the user once
+//! per server and resource, remembers "always" and "deny" decisions on disk,
+//! and hands back the set of permissions the view is actually granted.
+
+use crate::host::{now_millis, Prompt, PromptQueue};
+use crate::protocol::{UiPermissions, UiResourcePermissions};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Environment variable overriding [`DEFAULT_PERMISSIONS_PATH`]
+pub const PERMISSIONS_ENV: &str = "MCP_PERMISSIONS_FILE";
+
+/// Where the host keeps remembered permission decisions by default
+pub const DEFAULT_PERMISSIONS_PATH: &str = "config/mcp-permissions.json";
+
+/// The user's answer to a permission prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionDecision {
+    /// Grant for this load only; the user is asked again next time
+    AllowOnce,
+    /// Grant now and for future loads of the same resource
+    Always,
+    /// Grant nothing, now and for future loads of the same resource
+    Deny,
+}
+
+/// A remembered decision for one server and resource
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionGrant {
+    pub server_id: String,
+    pub resource_uri: String,
+    /// `Always` or `Deny`
+    pub decision: PermissionDecision,
+    /// Permissions covered by the decision
+    pub permissions: UiPermissions,
+    /// Milliseconds since the Unix epoch
+    pub decided_at: u64,
+}
+
+impl PermissionGrant {
+    fn matches(&self, server_id: &str, resource_uri: &str) -> bool {
+        self.server_id == server_id && self.resource_uri == resource_uri
+    }
+}
+
+/// A prompt waiting for the user
+#[derive(Debug, Clone, PartialEq)]
+pub struct PermissionRequest {
+    /// Prompt ID passed back to [`PermissionBroker::respond`]
+    pub id: String,
+    pub server_id: String,
+    pub resource_uri: String,
+    /// Permissions the resource asks for that the host can grant
+    pub requested: UiPermissions,
+}
+
+impl Prompt for PermissionRequest {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Asks for, remembers and revokes sandbox permission consent
+///
+/// Cloning yields another handle to the same broker.
+#[derive(Debug, Clone)]
+pub struct PermissionBroker {
+    /// File decisions are persisted to (`None` keeps them in memory)
+    path: Option<PathBuf>,
+    /// Remembered decisions
+    grants: Arc<watch::Sender<Vec<PermissionGrant>>>,
+    /// Prompts shown to the user
+    prompts: PromptQueue<PermissionRequest, PermissionDecision>,
+}
+
+impl PermissionBroker {
+    /// Broker whose decisions last only for this process
+    pub fn in_memory() -> Self {
+        Self::with_grants(None, Vec::new())
+    }
+
+    /// Broker persisting decisions to `path`, loading any already there
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let grants = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).map_err(io::Error::other)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self::with_grants(Some(path), grants))
+    }
+
+    /// Open the broker at `$MCP_PERMISSIONS_FILE`, or [`DEFAULT_PERMISSIONS_PATH`] if unset
+    pub fn open_default() -> io::Result<Self> {
+        let path = std::env::var_os(PERMISSIONS_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_PERMISSIONS_PATH));
+        Self::open(path)
+    }
+
+    fn with_grants(path: Option<PathBuf>, grants: Vec<PermissionGrant>) -> Self {
+        Self {
+            path,
+            grants: Arc::new(watch::channel(grants).0),
+            prompts: PromptQueue::new(),
+        }
+    }
+
+    /// Remembered decisions
+    pub fn grants(&self) -> Vec<PermissionGrant> {
+        self.grants.borrow().clone()
+    }
+
+    /// Watch remembered decisions
+    pub fn subscribe_grants(&self) -> watch::Receiver<Vec<PermissionGrant>> {
+        self.grants.subscribe()
+    }
+
+    /// Prompts waiting for the user
+    pub fn prompts(&self) -> Vec<PermissionRequest> {
+        self.prompts.prompts()
+    }
+
+    /// Watch prompts waiting for the user
+    pub fn subscribe_prompts(&self) -> watch::Receiver<Vec<PermissionRequest>> {
+        self.prompts.subscribe()
+    }
+
+    /// Permissions a remembered decision grants, if it answers this request
+    ///
+    /// An "always" decision only answers requests it fully covers; a resource
+    /// asking for more than was approved is prompted again.
+    pub fn remembered(
+        &self,
+        server_id: &str,
+        resource_uri: &str,
+        requested: &UiPermissions,
+    ) -> Option<UiPermissions> {
+        let grants = self.grants.borrow();
+        let grant = grants.iter().find(|g| g.matches(server_id, resource_uri))?;
+        match grant.decision {
+            PermissionDecision::Deny => Some(UiPermissions::default()),
+            PermissionDecision::Always if grant.permissions.covers(requested) => Some(requested.clone()),
+            _ => None,
+        }
+    }
+
+    /// Permissions granted to a view, prompting the user if needed
+    ///
+    /// Only the requested permissions in `grantable` are asked for. Resolves
+    /// once the prompt is answered; dropping the future withdraws the prompt.
+    pub async fn request(
+        &self,
+        server_id: &str,
+        resource_uri: &str,
+        requested: &UiResourcePermissions,
+        grantable: &UiPermissions,
+    ) -> UiPermissions {
+        let requested = grantable.grant(requested);
+        if !requested.has_any() {
+            return UiPermissions::default();
+        }
+        if let Some(granted) = self.remembered(server_id, resource_uri, &requested) {
+            return granted;
+        }
+
+        let request = PermissionRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            server_id: server_id.to_string(),
+            resource_uri: resource_uri.to_string(),
+            requested,
+        };
+        let decision = self.prompts.ask(request.clone()).await;
+        self.decide(&request, decision)
+    }
+
+    /// Answer an open prompt
+    ///
+    /// Returns `false` if no prompt with this ID is waiting.
+    pub fn respond(&self, request_id: &str, decision: PermissionDecision) -> bool {
+        self.prompts.respond(request_id, decision)
+    }
+
+    /// Apply a decision, remembering it unless it was for this load only
+    fn decide(&self, request: &PermissionRequest, decision: PermissionDecision) -> UiPermissions {
+        let requested = request.requested.clone();
+        let granted = match decision {
+            PermissionDecision::Deny => UiPermissions::default(),
+            PermissionDecision::AllowOnce | PermissionDecision::Always => requested.clone(),
+        };
+
+        if decision != PermissionDecision::AllowOnce {
+            let grant = PermissionGrant {
+                server_id: request.server_id.clone(),
+                resource_uri: request.resource_uri.clone(),
+                decision,
+                permissions: requested,
+                decided_at: now_millis(),
+            };
+            self.grants.send_modify(|grants| {
+                grants.retain(|g| !g.matches(&grant.server_id, &grant.resource_uri));
+                grants.push(grant);
+            });
+            self.persist();
+        }
+
+        granted
+    }
+
+    /// Forget the decision for a server and resource
+    ///
+    /// The next load of the resource prompts again. Returns `false` if there
+    /// was nothing to revoke.
+    pub fn revoke(&self, server_id: &str, resource_uri: &str) -> bool {
+        let revoked = self.grants.send_if_modified(|grants| {
+            let before = grants.len();
+            grants.retain(|g| !g.matches(server_id, resource_uri));
+            grants.len() != before
+        });
+        if revoked {
+            self.persist();
+        }
+        revoked
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        let result = (|| -> io::Result<()> {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            let json = serde_json::to_string_pretty(&*self.grants.borrow()).map_err(io::Error::other)?;
+            fs::write(path, json)
+        })();
+        if let Err(e) = result {
+            log::warn!("Failed to save permission decisions to {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::Empty;
+
+    fn camera() -> UiResourcePermissions {
+        UiResourcePermissions { camera: Some(Empty {}), ..Default::default() }
+    }
+
+    /// Permissions the host asks the user for, given what a resource requested
+    fn asked(requested: UiResourcePermissions) -> UiPermissions {
+        UiPermissions::from(&requested)
+    }
+
+    /// Answer the next prompt the broker raises
+    fn answer_next(broker: &PermissionBroker, decision: PermissionDecision) {
+        let broker = broker.clone();
+        tokio::spawn(async move {
+            let mut prompts = broker.subscribe_prompts();
+            let request = loop {
+                if let Some(request) = prompts.borrow_and_update().first().cloned() {
+                    break request;
+                }
+                prompts.changed().await.unwrap();
+            };
+            assert!(broker.respond(&request.id, decision));
+        });
+    }
+
+    #[tokio::test]
+    async fn test_consent_decisions() {
+        let broker = PermissionBroker::in_memory();
+
+        // Nothing requested, nothing asked
+        assert!(!broker.request("s", "ui://a", &UiResourcePermissions::default(), &UiPermissions::all()).await.has_any());
+
+        // Allow once grants without remembering
+        answer_next(&broker, PermissionDecision::AllowOnce);
+        assert!(broker.request("s", "ui://a", &camera(), &UiPermissions::all()).await.camera.is_some());
+        assert!(broker.grants().is_empty());
+        assert!(broker.remembered("s", "ui://a", &asked(camera())).is_none());
+
+        // Permissions the host cannot grant are neither asked for nor granted
+        let grantable = UiPermissions { camera: Some(Empty {}), ..Default::default() };
+        let both = UiResourcePermissions { microphone: Some(Empty {}), ..camera() };
+        let mut prompts = broker.subscribe_prompts();
+        let answering = broker.clone();
+        let prompted = tokio::spawn(async move {
+            let request = prompts.wait_for(|p| !p.is_empty()).await.unwrap()[0].clone();
+            answering.respond(&request.id, PermissionDecision::AllowOnce);
+            request.requested
+        });
+        let granted = broker.request("s", "ui://a", &both, &grantable).await;
+        assert_eq!(granted.granted(), vec!["camera"]);
+        assert_eq!(prompted.await.unwrap().granted(), vec!["camera"]);
+        let microphone = UiResourcePermissions { microphone: Some(Empty {}), ..Default::default() };
+        assert!(!broker.request("s", "ui://a", &microphone, &grantable).await.has_any());
+
+        // Always is remembered for requests it covers
+        answer_next(&broker, PermissionDecision::Always);
+        assert!(broker.request("s", "ui://a", &camera(), &UiPermissions::all()).await.camera.is_some());
+        assert!(broker.request("s", "ui://a", &camera(), &UiPermissions::all()).await.camera.is_some());
+        let more = UiResourcePermissions { microphone: Some(Empty {}), ..camera() };
+        assert!(broker.remembered("s", "ui://a", &asked(more)).is_none());
+
+        // Deny is remembered per server and resource
+        answer_next(&broker, PermissionDecision::Deny);
+        assert!(!broker.request("s", "ui://b", &camera(), &UiPermissions::all()).await.has_any());
+        assert_eq!(broker.remembered("s", "ui://b", &asked(camera())), Some(UiPermissions::default()));
+        assert!(broker.remembered("other", "ui://b", &asked(camera())).is_none());
+
+        assert!(broker.revoke("s", "ui://b"));
+        assert!(!broker.revoke("s", "ui://b"));
+        assert_eq!(broker.grants().len(), 1);
+        assert!(broker.prompts().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_decisions_persist() {
+        let path = std::env::temp_dir()
+            .join(format!("mcp-permissions-test-{}", uuid::Uuid::new_v4()))
+            .join("permissions.json");
+
+        let broker = PermissionBroker::open(&path).unwrap();
+        answer_next(&broker, PermissionDecision::Always);
+        broker.request("s", "ui://a", &camera(), &UiPermissions::all()).await;
+
+        let reopened = PermissionBroker::open(&path).unwrap();
+        assert_eq!(reopened.grants(), broker.grants());
+        assert!(reopened.remembered("s", "ui://a", &asked(camera())).unwrap().camera.is_some());
+
+        reopened.revoke("s", "ui://a");
+        assert!(PermissionBroker::open(&path).unwrap().grants().is_empty());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
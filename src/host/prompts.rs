@@ -0,0 +1,160 @@
+//! Pending Prompts
+//!
+//! Permission requests, links and view messages can wait on the user. Each
+//! broker queues its prompts in a [`PromptQueue`], which the UI watches to show
+//! the oldest one and answers through [`PromptQueue::respond`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{oneshot, watch};
+
+/// A prompt shown to the user
+pub trait Prompt: Clone {
+    /// ID the answer is given for
+    fn id(&self) -> &str;
+}
+
+/// Prompts of type `T` waiting for an answer of type `A`
+///
+/// Cloning yields another handle to the same queue.
+pub struct PromptQueue<T, A> {
+    /// Prompts shown to the user, oldest first
+    prompts: Arc<watch::Sender<Vec<T>>>,
+    /// Waiters for each open prompt
+    pending: Arc<Mutex<HashMap<String, oneshot::Sender<A>>>>,
+}
+
+impl<T, A> Clone for PromptQueue<T, A> {
+    fn clone(&self) -> Self {
+        Self { prompts: self.prompts.clone(), pending: self.pending.clone() }
+    }
+}
+
+impl<T: fmt::Debug, A> fmt::Debug for PromptQueue<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PromptQueue").field("prompts", &*self.prompts.borrow()).finish_non_exhaustive()
+    }
+}
+
+impl<T: Prompt, A> PromptQueue<T, A> {
+    pub fn new() -> Self {
+        Self {
+            prompts: Arc::new(watch::channel(Vec::new()).0),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Prompts waiting for the user, oldest first
+    pub fn prompts(&self) -> Vec<T> {
+        self.prompts.borrow().clone()
+    }
+
+    /// Watch prompts waiting for the user
+    pub fn subscribe(&self) -> watch::Receiver<Vec<T>> {
+        self.prompts.subscribe()
+    }
+
+    /// Show a prompt and wait for its answer
+    ///
+    /// Dropping the returned future withdraws the prompt.
+    pub async fn ask(&self, prompt: T) -> A {
+        let (tx, rx) = oneshot::channel();
+        let open = OpenPrompt { queue: self, id: prompt.id().to_string() };
+        self.pending.lock().unwrap().insert(open.id.clone(), tx);
+        self.prompts.send_modify(|prompts| prompts.push(prompt));
+
+        rx.await.expect("open prompts keep their waiter until answered")
+    }
+
+    /// Answer an open prompt
+    ///
+    /// Returns `false` if no prompt with this ID is waiting.
+    pub fn respond(&self, id: &str, answer: A) -> bool {
+        let waiter = self.pending.lock().unwrap().remove(id);
+        self.close(id);
+        waiter.is_some_and(|tx| tx.send(answer).is_ok())
+    }
+
+    fn close(&self, id: &str) {
+        self.prompts.send_if_modified(|prompts| {
+            let before = prompts.len();
+            prompts.retain(|p| p.id() != id);
+            prompts.len() != before
+        });
+    }
+}
+
+/// Removes a prompt from its queue once it is answered or abandoned
+struct OpenPrompt<'a, T: Prompt, A> {
+    queue: &'a PromptQueue<T, A>,
+    id: String,
+}
+
+impl<T: Prompt, A> Drop for OpenPrompt<'_, T, A> {
+    fn drop(&mut self) {
+        self.queue.pending.lock().unwrap().remove(&self.id);
+        self.queue.close(&self.id);
+    }
+}
+
+impl<T: Prompt, A> Default for PromptQueue<T, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Question(String);
+
+    impl Prompt for Question {
+        fn id(&self) -> &str {
+            &self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prompts_are_answered_in_any_order() {
+        let queue = PromptQueue::<Question, bool>::new();
+        let first = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.ask(Question("a".into())).await }
+        });
+        let second = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.ask(Question("b".into())).await }
+        });
+
+        let mut prompts = queue.subscribe();
+        prompts.wait_for(|p| p.len() == 2).await.unwrap();
+        assert_eq!(queue.prompts(), [Question("a".into()), Question("b".into())]);
+
+        assert!(queue.respond("b", true));
+        assert!(!queue.respond("b", false));
+        assert!(second.await.unwrap());
+        assert_eq!(queue.prompts(), [Question("a".into())]);
+
+        assert!(queue.respond("a", false));
+        assert!(!first.await.unwrap());
+        assert!(queue.prompts().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_prompts_are_withdrawn() {
+        let queue = PromptQueue::<Question, bool>::new();
+        let asking = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.ask(Question("a".into())).await }
+        });
+        queue.subscribe().wait_for(|p| !p.is_empty()).await.unwrap();
+
+        asking.abort();
+        assert!(asking.await.unwrap_err().is_cancelled());
+        assert!(queue.prompts().is_empty());
+        assert!(!queue.respond("a", true));
+    }
+}
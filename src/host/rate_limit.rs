@@ -0,0 +1,56 @@
+//! Rate Limiting
+//!
+//! Sliding-window limits on how often a view or server may do something.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// At most `limit` events in any `window`
+#[derive(Debug, Clone)]
+pub struct SlidingWindow {
+    limit: usize,
+    window: Duration,
+    /// Times of the events still in the window, oldest first
+    hits: VecDeque<Instant>,
+}
+
+impl SlidingWindow {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self { limit, window, hits: VecDeque::new() }
+    }
+
+    /// Count an event at `now` if the window has room
+    ///
+    /// Returns how long until there is room otherwise.
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        while self.hits.front().is_some_and(|at| now.duration_since(*at) >= self.window) {
+            self.hits.pop_front();
+        }
+        if self.hits.len() >= self.limit {
+            let retry_after = self.hits.front().map(|at| self.window.saturating_sub(now.duration_since(*at)));
+            return Err(retry_after.unwrap_or(self.window));
+        }
+        self.hits.push_back(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sliding_window() {
+        let start = Instant::now();
+        let mut window = SlidingWindow::new(2, Duration::from_secs(10));
+        assert_eq!(window.try_acquire(start), Ok(()));
+        assert_eq!(window.try_acquire(start + Duration::from_secs(4)), Ok(()));
+        assert_eq!(window.try_acquire(start + Duration::from_secs(6)), Err(Duration::from_secs(4)));
+        // The first event leaves the window
+        assert_eq!(window.try_acquire(start + Duration::from_secs(10)), Ok(()));
+        assert!(window.try_acquire(start + Duration::from_secs(11)).is_err());
+
+        let mut closed = SlidingWindow::new(0, Duration::from_secs(10));
+        assert_eq!(closed.try_acquire(start), Err(Duration::from_secs(10)));
+    }
+}
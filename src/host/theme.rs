@@ -0,0 +1,256 @@
+//! Host Theme
+//!
+//! Defines the standard MCP Apps style variables for the light and dark
+//! themes, delivered to views through `HostContext.styles`, and the matching
+//! Tailwind class tokens for natively rendered Rhai views.
+
+use crate::protocol::{UiCssConfig, UiStyleConfig};
+use serde_json::{Map, Value};
+
+/// Variables shared by both themes: typography, radii, borders and shadows
+const SHARED_VARIABLES: &[(&str, &str)] = &[
+    ("--font-sans", "-apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Oxygen, Ubuntu, sans-serif"),
+    ("--font-mono", "ui-monospace, SFMono-Regular, Menlo, Consolas, 'Liberation Mono', monospace"),
+    ("--font-weight-normal", "400"),
+    ("--font-weight-medium", "500"),
+    ("--font-weight-semibold", "600"),
+    ("--font-weight-bold", "700"),
+    ("--font-text-xs-size", "0.75rem"),
+    ("--font-text-xs-line-height", "1rem"),
+    ("--font-text-sm-size", "0.875rem"),
+    ("--font-text-sm-line-height", "1.25rem"),
+    ("--font-text-md-size", "1rem"),
+    ("--font-text-md-line-height", "1.5rem"),
+    ("--font-text-lg-size", "1.125rem"),
+    ("--font-text-lg-line-height", "1.75rem"),
+    ("--font-heading-xs-size", "0.875rem"),
+    ("--font-heading-xs-line-height", "1.25rem"),
+    ("--font-heading-sm-size", "1rem"),
+    ("--font-heading-sm-line-height", "1.5rem"),
+    ("--font-heading-md-size", "1.25rem"),
+    ("--font-heading-md-line-height", "1.75rem"),
+    ("--font-heading-lg-size", "1.5rem"),
+    ("--font-heading-lg-line-height", "2rem"),
+    ("--font-heading-xl-size", "1.875rem"),
+    ("--font-heading-xl-line-height", "2.25rem"),
+    ("--font-heading-2xl-size", "2.25rem"),
+    ("--font-heading-2xl-line-height", "2.5rem"),
+    ("--font-heading-3xl-size", "3rem"),
+    ("--font-heading-3xl-line-height", "1"),
+    ("--border-radius-xs", "0.125rem"),
+    ("--border-radius-sm", "0.25rem"),
+    ("--border-radius-md", "0.5rem"),
+    ("--border-radius-lg", "0.75rem"),
+    ("--border-radius-xl", "1rem"),
+    ("--border-radius-full", "9999px"),
+    ("--border-width-regular", "1px"),
+];
+
+const LIGHT_VARIABLES: &[(&str, &str)] = &[
+    ("--color-background-primary", "#ffffff"),
+    ("--color-background-secondary", "#f9fafb"),
+    ("--color-background-tertiary", "#f3f4f6"),
+    ("--color-background-inverse", "#111827"),
+    ("--color-background-ghost", "transparent"),
+    ("--color-background-info", "#eff6ff"),
+    ("--color-background-danger", "#fef2f2"),
+    ("--color-background-success", "#f0fdf4"),
+    ("--color-background-warning", "#fffbeb"),
+    ("--color-background-disabled", "#f3f4f6"),
+    ("--color-text-primary", "#1f2937"),
+    ("--color-text-secondary", "#4b5563"),
+    ("--color-text-tertiary", "#9ca3af"),
+    ("--color-text-inverse", "#f9fafb"),
+    ("--color-text-ghost", "#6b7280"),
+    ("--color-text-info", "#1d4ed8"),
+    ("--color-text-danger", "#b91c1c"),
+    ("--color-text-success", "#15803d"),
+    ("--color-text-warning", "#b45309"),
+    ("--color-text-disabled", "#9ca3af"),
+    ("--color-border-primary", "#d1d5db"),
+    ("--color-border-secondary", "#e5e7eb"),
+    ("--color-border-tertiary", "#f3f4f6"),
+    ("--color-border-inverse", "#374151"),
+    ("--color-border-ghost", "transparent"),
+    ("--color-border-info", "#93c5fd"),
+    ("--color-border-danger", "#fca5a5"),
+    ("--color-border-success", "#86efac"),
+    ("--color-border-warning", "#fcd34d"),
+    ("--color-border-disabled", "#e5e7eb"),
+    ("--color-ring-primary", "#4f46e5"),
+    ("--color-ring-secondary", "#9ca3af"),
+    ("--color-ring-inverse", "#ffffff"),
+    ("--color-ring-info", "#3b82f6"),
+    ("--color-ring-danger", "#ef4444"),
+    ("--color-ring-success", "#22c55e"),
+    ("--color-ring-warning", "#f59e0b"),
+    ("--shadow-hairline", "0 0 0 1px rgba(0, 0, 0, 0.05)"),
+    ("--shadow-sm", "0 1px 2px rgba(0, 0, 0, 0.05)"),
+    ("--shadow-md", "0 4px 6px rgba(0, 0, 0, 0.1)"),
+    ("--shadow-lg", "0 10px 15px rgba(0, 0, 0, 0.1)"),
+];
+
+const DARK_VARIABLES: &[(&str, &str)] = &[
+    ("--color-background-primary", "#111827"),
+    ("--color-background-secondary", "#1f2937"),
+    ("--color-background-tertiary", "#374151"),
+    ("--color-background-inverse", "#f9fafb"),
+    ("--color-background-ghost", "transparent"),
+    ("--color-background-info", "#172554"),
+    ("--color-background-danger", "#450a0a"),
+    ("--color-background-success", "#052e16"),
+    ("--color-background-warning", "#451a03"),
+    ("--color-background-disabled", "#1f2937"),
+    ("--color-text-primary", "#f3f4f6"),
+    ("--color-text-secondary", "#d1d5db"),
+    ("--color-text-tertiary", "#9ca3af"),
+    ("--color-text-inverse", "#111827"),
+    ("--color-text-ghost", "#9ca3af"),
+    ("--color-text-info", "#93c5fd"),
+    ("--color-text-danger", "#fca5a5"),
+    ("--color-text-success", "#86efac"),
+    ("--color-text-warning", "#fcd34d"),
+    ("--color-text-disabled", "#6b7280"),
+    ("--color-border-primary", "#4b5563"),
+    ("--color-border-secondary", "#374151"),
+    ("--color-border-tertiary", "#1f2937"),
+    ("--color-border-inverse", "#e5e7eb"),
+    ("--color-border-ghost", "transparent"),
+    ("--color-border-info", "#1e40af"),
+    ("--color-border-danger", "#991b1b"),
+    ("--color-border-success", "#166534"),
+    ("--color-border-warning", "#92400e"),
+    ("--color-border-disabled", "#374151"),
+    ("--color-ring-primary", "#818cf8"),
+    ("--color-ring-secondary", "#6b7280"),
+    ("--color-ring-inverse", "#111827"),
+    ("--color-ring-info", "#60a5fa"),
+    ("--color-ring-danger", "#f87171"),
+    ("--color-ring-success", "#4ade80"),
+    ("--color-ring-warning", "#fbbf24"),
+    ("--shadow-hairline", "0 0 0 1px rgba(255, 255, 255, 0.08)"),
+    ("--shadow-sm", "0 1px 2px rgba(0, 0, 0, 0.4)"),
+    ("--shadow-md", "0 4px 6px rgba(0, 0, 0, 0.4)"),
+    ("--shadow-lg", "0 10px 15px rgba(0, 0, 0, 0.5)"),
+];
+
+/// Tailwind classes matching the style variables, by semantic token
+const LIGHT_TOKENS: &[(&str, &str)] = &[
+    ("bg", "bg-white"),
+    ("surface", "bg-gray-50"),
+    ("text", "text-gray-800"),
+    ("muted", "text-gray-500"),
+    ("border", "border-gray-200"),
+    ("accent", "bg-indigo-600 text-white"),
+    ("accent_text", "text-indigo-600"),
+    ("ring", "ring-indigo-600"),
+    ("info", "bg-blue-50 text-blue-700"),
+    ("danger", "bg-red-50 text-red-700"),
+    ("success", "bg-green-50 text-green-700"),
+    ("warning", "bg-amber-50 text-amber-700"),
+];
+
+const DARK_TOKENS: &[(&str, &str)] = &[
+    ("bg", "bg-gray-900"),
+    ("surface", "bg-gray-800"),
+    ("text", "text-gray-100"),
+    ("muted", "text-gray-400"),
+    ("border", "border-gray-700"),
+    ("accent", "bg-indigo-400 text-gray-900"),
+    ("accent_text", "text-indigo-400"),
+    ("ring", "ring-indigo-400"),
+    ("info", "bg-blue-950 text-blue-300"),
+    ("danger", "bg-red-950 text-red-300"),
+    ("success", "bg-green-950 text-green-300"),
+    ("warning", "bg-amber-950 text-amber-300"),
+];
+
+fn is_dark(theme: &str) -> bool {
+    theme.eq_ignore_ascii_case("dark")
+}
+
+/// Standard style variables for a theme (`"light"` or `"dark"`)
+pub fn theme_variables(theme: &str) -> Map<String, Value> {
+    let colors = if is_dark(theme) { DARK_VARIABLES } else { LIGHT_VARIABLES };
+    SHARED_VARIABLES
+        .iter()
+        .chain(colors)
+        .map(|(name, value)| (name.to_string(), Value::String(value.to_string())))
+        .collect()
+}
+
+/// Styles for `HostContext.styles`
+pub fn theme_styles(theme: &str, fonts: Option<String>) -> UiStyleConfig {
+    UiStyleConfig {
+        variables: Some(Value::Object(theme_variables(theme))),
+        css: fonts.map(|fonts| UiCssConfig { fonts: Some(fonts) }),
+    }
+}
+
+/// Tailwind class tokens for a theme
+pub fn theme_tokens(theme: &str) -> &'static [(&'static str, &'static str)] {
+    if is_dark(theme) { DARK_TOKENS } else { LIGHT_TOKENS }
+}
+
+/// Render styles as CSS for a view's `<head>`
+///
+/// Variables become a `:root` rule; names that are not custom properties and
+/// values that could break out of the declaration are skipped.
+pub fn styles_css(styles: &UiStyleConfig) -> String {
+    let valid_name = |name: &str| {
+        name.len() > 2
+            && name.starts_with("--")
+            && name[2..].chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    };
+    let valid_value = |value: &str| !value.chars().any(|c| matches!(c, ';' | '{' | '}' | '<' | '>') || c.is_control());
+
+    let mut css = String::new();
+    if let Some(fonts) = styles.css.as_ref().and_then(|c| c.fonts.as_ref()) {
+        css.push_str(&fonts.replace("</", "<\\/"));
+        css.push('\n');
+    }
+    if let Some(Value::Object(variables)) = &styles.variables {
+        css.push_str(":root {\n");
+        for (name, value) in variables {
+            if let Some(value) = value.as_str().filter(|v| valid_name(name) && valid_value(v)) {
+                css.push_str(&format!("    {}: {};\n", name, value));
+            }
+        }
+        css.push_str("}\n");
+    }
+    css
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_themes_define_the_same_variables() {
+        let light = theme_variables("light");
+        let dark = theme_variables("dark");
+        assert_eq!(light.len(), dark.len());
+        assert!(light.keys().all(|k| dark.contains_key(k)));
+        assert_ne!(light["--color-background-primary"], dark["--color-background-primary"]);
+        assert_eq!(light["--font-sans"], dark["--font-sans"]);
+
+        let light_tokens: Vec<&str> = theme_tokens("light").iter().map(|(k, _)| *k).collect();
+        let dark_tokens: Vec<&str> = theme_tokens("dark").iter().map(|(k, _)| *k).collect();
+        assert_eq!(light_tokens, dark_tokens);
+    }
+
+    #[test]
+    fn test_styles_css() {
+        let mut styles = theme_styles("dark", Some("@import url('https://fonts.example.com/inter.css');".into()));
+        if let Some(Value::Object(variables)) = styles.variables.as_mut() {
+            variables.insert("--evil".into(), "red; } body { display: none".into());
+            variables.insert("color".into(), "red".into());
+        }
+
+        let css = styles_css(&styles);
+        assert!(css.starts_with("@import"));
+        assert!(css.contains("--color-background-primary: #111827;"));
+        assert!(!css.contains("--evil"));
+        assert!(!css.contains("color: red"));
+    }
+}
@@ -0,0 +1,299 @@
+//! View Logs
+//!
+//! Views log with `notifications/message`. Each message is written to the
+//! host log under the target `mcp_app::<server>::<resource>`, with the session
+//! ID as a structured field, and kept in a per-session buffer for the UI.
+//! Sessions are rate limited and long messages truncated, so a chatty view
+//! cannot flood either.
+
+use crate::host::{now_millis, SlidingWindow, UiSession};
+use crate::protocol::{LoggingLevel, LoggingMessageNotification};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// Longest message kept, in bytes
+pub const DEFAULT_MAX_LOG_MESSAGE_BYTES: usize = 4 * 1024;
+
+/// Messages kept per session
+pub const DEFAULT_LOG_BUFFER_ENTRIES: usize = 500;
+
+/// Messages a view may log per [`DEFAULT_LOG_RATE_WINDOW`]
+pub const DEFAULT_LOG_RATE_LIMIT: usize = 50;
+
+/// Window over which [`DEFAULT_LOG_RATE_LIMIT`] applies
+pub const DEFAULT_LOG_RATE_WINDOW: Duration = Duration::from_secs(10);
+
+impl From<LoggingLevel> for log::Level {
+    fn from(level: LoggingLevel) -> Self {
+        match level {
+            LoggingLevel::Debug => log::Level::Debug,
+            LoggingLevel::Info | LoggingLevel::Notice => log::Level::Info,
+            LoggingLevel::Warning => log::Level::Warn,
+            LoggingLevel::Error | LoggingLevel::Critical | LoggingLevel::Alert | LoggingLevel::Emergency => {
+                log::Level::Error
+            }
+        }
+    }
+}
+
+/// Host log target for a view's messages
+///
+/// `ui://weather/forecast` on server `weather` logs to
+/// `mcp_app::weather::weather/forecast`.
+pub fn view_log_target(server_id: &str, resource_uri: &str) -> String {
+    let resource = resource_uri.strip_prefix("ui://").unwrap_or(resource_uri);
+    format!("mcp_app::{}::{}", server_id, resource)
+}
+
+/// A message a view logged
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewLogEntry {
+    pub level: LoggingLevel,
+    /// Name of the logger within the view
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logger: Option<String>,
+    pub message: String,
+    /// Whether the message was cut to the size limit
+    pub truncated: bool,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// A change to the view logs
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViewLogEvent {
+    /// A message was added to a session's buffer
+    Logged { session_id: String, entry: ViewLogEntry },
+    /// A session's buffer was dropped
+    Removed { session_id: String },
+}
+
+/// Buffer and rate limit of one session
+#[derive(Debug)]
+struct SessionLog {
+    entries: VecDeque<ViewLogEntry>,
+    rate: SlidingWindow,
+    /// Messages dropped by the rate limit since the last one let through
+    dropped: usize,
+}
+
+/// Log messages from each view session
+///
+/// Cloning yields another handle to the same logs.
+#[derive(Debug, Clone)]
+pub struct ViewLogs {
+    max_message_bytes: usize,
+    max_entries: usize,
+    rate_limit: usize,
+    rate_window: Duration,
+    sessions: Arc<Mutex<HashMap<String, SessionLog>>>,
+    events: broadcast::Sender<ViewLogEvent>,
+}
+
+impl ViewLogs {
+    pub fn new() -> Self {
+        Self {
+            max_message_bytes: DEFAULT_MAX_LOG_MESSAGE_BYTES,
+            max_entries: DEFAULT_LOG_BUFFER_ENTRIES,
+            rate_limit: DEFAULT_LOG_RATE_LIMIT,
+            rate_window: DEFAULT_LOG_RATE_WINDOW,
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            events: broadcast::channel(256).0,
+        }
+    }
+
+    /// Builder method: Set the longest message in bytes and the messages kept per session
+    pub fn with_limits(mut self, max_message_bytes: usize, max_entries: usize) -> Self {
+        self.max_message_bytes = max_message_bytes;
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Builder method: Allow each view `limit` messages per `window`
+    pub fn with_rate_limit(mut self, limit: usize, window: Duration) -> Self {
+        self.rate_limit = limit;
+        self.rate_window = window;
+        self
+    }
+
+    /// Subscribe to logged messages
+    pub fn subscribe(&self) -> broadcast::Receiver<ViewLogEvent> {
+        self.events.subscribe()
+    }
+
+    /// Buffered messages of a session, oldest first
+    pub fn entries(&self, session_id: &str) -> Vec<ViewLogEntry> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .map(|log| log.entries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Log a message from a view
+    ///
+    /// Messages over the rate limit are dropped; the next message let through
+    /// is preceded by a warning saying how many. Returns whether the message
+    /// was logged.
+    pub fn log(&self, session: &UiSession, message: LoggingMessageNotification) -> bool {
+        let target = view_log_target(&session.server_id, &session.resource_uri);
+        let (text, truncated) = self.message_text(message.data);
+        let entry = ViewLogEntry {
+            level: message.level,
+            logger: message.logger,
+            message: text,
+            truncated,
+            timestamp: now_millis(),
+        };
+
+        let mut logged = Vec::new();
+        {
+            let mut sessions = self.sessions.lock().unwrap();
+            let log = sessions.entry(session.id.clone()).or_insert_with(|| SessionLog {
+                entries: VecDeque::new(),
+                rate: SlidingWindow::new(self.rate_limit, self.rate_window),
+                dropped: 0,
+            });
+            if log.rate.try_acquire(Instant::now()).is_err() {
+                log.dropped += 1;
+                return false;
+            }
+            if log.dropped > 0 {
+                logged.push(ViewLogEntry {
+                    level: LoggingLevel::Warning,
+                    logger: None,
+                    message: format!("{} messages dropped by the rate limit", std::mem::take(&mut log.dropped)),
+                    truncated: false,
+                    timestamp: entry.timestamp,
+                });
+            }
+            logged.push(entry);
+            for entry in &logged {
+                if log.entries.len() >= self.max_entries {
+                    log.entries.pop_front();
+                }
+                log.entries.push_back(entry.clone());
+            }
+        }
+
+        for entry in logged {
+            let logger = entry.logger.as_deref().unwrap_or_default();
+            log::log!(
+                target: &target,
+                entry.level.into(),
+                session_id = session.id.as_str(),
+                logger = logger;
+                "{}",
+                entry.message
+            );
+            let _ = self.events.send(ViewLogEvent::Logged { session_id: session.id.clone(), entry });
+        }
+        true
+    }
+
+    /// Drop a session's buffer
+    pub fn remove(&self, session_id: &str) {
+        if self.sessions.lock().unwrap().remove(session_id).is_some() {
+            let _ = self.events.send(ViewLogEvent::Removed { session_id: session_id.to_string() });
+        }
+    }
+
+    /// Message text, cut to the size limit on a character boundary
+    fn message_text(&self, data: Value) -> (String, bool) {
+        let mut text = match data {
+            Value::String(text) => text,
+            other => other.to_string(),
+        };
+        if text.len() <= self.max_message_bytes {
+            return (text, false);
+        }
+        let mut end = self.max_message_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push('…');
+        (text, true)
+    }
+}
+
+impl Default for ViewLogs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(level: LoggingLevel, data: Value) -> LoggingMessageNotification {
+        LoggingMessageNotification { level, logger: Some("chart".into()), data }
+    }
+
+    #[test]
+    fn test_levels_and_targets() {
+        assert_eq!(log::Level::from(LoggingLevel::Notice), log::Level::Info);
+        assert_eq!(log::Level::from(LoggingLevel::Warning), log::Level::Warn);
+        assert_eq!(log::Level::from(LoggingLevel::Emergency), log::Level::Error);
+        assert_eq!(view_log_target("weather", "ui://weather/forecast"), "mcp_app::weather::weather/forecast");
+    }
+
+    #[test]
+    fn test_messages_are_buffered_per_session() {
+        let logs = ViewLogs::new().with_limits(12, 3);
+        let mut events = logs.subscribe();
+        let a = UiSession::new("a", "weather", "ui://weather/view");
+        let b = UiSession::new("b", "maps", "ui://maps/view");
+
+        assert!(logs.log(&a, message(LoggingLevel::Info, json!("loaded"))));
+        assert!(logs.log(&b, message(LoggingLevel::Error, json!({ "code": 1 }))));
+        assert!(logs.log(&a, message(LoggingLevel::Debug, json!("aéééééé"))));
+
+        let entries = logs.entries("a");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].message, "loaded");
+        // Cut at a character boundary within 12 bytes
+        assert_eq!(entries[1].message, "aééééé…");
+        assert!(entries[1].truncated);
+        assert_eq!(logs.entries("b")[0].message, r#"{"code":1}"#);
+        assert!(matches!(events.try_recv(), Ok(ViewLogEvent::Logged { session_id, .. }) if session_id == "a"));
+
+        // Only the latest messages are kept
+        for n in 0..3 {
+            logs.log(&a, message(LoggingLevel::Info, json!(n.to_string())));
+        }
+        let kept: Vec<String> = logs.entries("a").into_iter().map(|e| e.message).collect();
+        assert_eq!(kept, ["0", "1", "2"]);
+
+        logs.remove("a");
+        assert!(logs.entries("a").is_empty());
+        assert_eq!(logs.entries("b").len(), 1);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let logs = ViewLogs::new().with_rate_limit(2, Duration::from_millis(50));
+        let session = UiSession::new("a", "weather", "ui://weather/view");
+
+        assert!(logs.log(&session, message(LoggingLevel::Info, json!("1"))));
+        assert!(logs.log(&session, message(LoggingLevel::Info, json!("2"))));
+        assert!(!logs.log(&session, message(LoggingLevel::Info, json!("3"))));
+        assert!(!logs.log(&session, message(LoggingLevel::Info, json!("4"))));
+        assert_eq!(logs.entries("a").len(), 2);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(logs.log(&session, message(LoggingLevel::Info, json!("5"))));
+        let entries = logs.entries("a");
+        assert_eq!(entries[2].level, LoggingLevel::Warning);
+        assert_eq!(entries[2].message, "2 messages dropped by the rate limit");
+        assert_eq!(entries[3].message, "5");
+    }
+}
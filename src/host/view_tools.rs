@@ -0,0 +1,290 @@
+//! View Tools
+//!
+//! Views that declare the `tools` app capability expose MCP-style tools of
+//! their own, such as a chart reporting its current selection. The host lists
+//! them with `tools/list` once the view is initialized, lists them again on
+//! `notifications/tools/list_changed`, and lets the agent call them with
+//! `tools/call` while the view is live.
+
+use crate::host::UiSession;
+use crate::protocol::{error_codes, JsonRpcError};
+use rmcp::model::{CallToolResult, ListToolsResult, Tool};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Time a view gets to answer `tools/list` or `tools/call`
+pub const DEFAULT_VIEW_TOOL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sends requests to a view
+#[async_trait::async_trait]
+pub trait ViewToolTransport: Send + Sync {
+    /// Send a request and wait for the view's result
+    async fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, JsonRpcError>;
+}
+
+/// Tools a live view exposes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewToolSet {
+    pub session_id: String,
+    pub server_id: String,
+    pub resource_uri: String,
+    /// Name the view gave during initialization
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    pub tools: Vec<Tool>,
+}
+
+/// A change to the tools views expose
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViewToolsEvent {
+    /// A view's tools were listed
+    Updated(ViewToolSet),
+    /// A view is gone, and its tools with it
+    Removed { session_id: String },
+}
+
+/// Why a view tool could not be listed or called
+#[derive(Debug, Clone)]
+pub enum ViewToolError {
+    /// No live view exposes tools under this session ID
+    ViewNotFound(String),
+    /// The view does not list a tool with this name
+    ToolNotFound { session_id: String, name: String },
+    /// The view answered with an error or not at all
+    Request(JsonRpcError),
+    /// The view's answer is not a valid result
+    InvalidResult(String),
+}
+
+impl ViewToolError {
+    /// JSON-RPC error code for the failure
+    pub fn code(&self) -> i32 {
+        match self {
+            ViewToolError::ViewNotFound(_) | ViewToolError::ToolNotFound { .. } => error_codes::INVALID_PARAMS,
+            ViewToolError::Request(e) => e.code,
+            ViewToolError::InvalidResult(_) => error_codes::INTERNAL_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for ViewToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViewToolError::ViewNotFound(id) => write!(f, "No view with tools: {}", id),
+            ViewToolError::ToolNotFound { session_id, name } => write!(f, "View {} has no tool {}", session_id, name),
+            ViewToolError::Request(e) => write!(f, "View request failed: {}", e.message),
+            ViewToolError::InvalidResult(e) => write!(f, "Invalid result from view: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ViewToolError {}
+
+/// A view whose tools are registered
+struct RegisteredView {
+    tools: ViewToolSet,
+    transport: Arc<dyn ViewToolTransport>,
+}
+
+/// Tools exposed by each live view
+///
+/// Cloning yields another handle to the same registry.
+#[derive(Clone)]
+pub struct ViewTools {
+    views: Arc<Mutex<HashMap<String, RegisteredView>>>,
+    events: broadcast::Sender<ViewToolsEvent>,
+}
+
+impl fmt::Debug for ViewTools {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ViewTools").field("views", &self.views().len()).finish_non_exhaustive()
+    }
+}
+
+impl ViewTools {
+    pub fn new() -> Self {
+        Self {
+            views: Arc::new(Mutex::new(HashMap::new())),
+            events: broadcast::channel(32).0,
+        }
+    }
+
+    /// Subscribe to tool list changes
+    pub fn subscribe(&self) -> broadcast::Receiver<ViewToolsEvent> {
+        self.events.subscribe()
+    }
+
+    /// Tools of every registered view
+    pub fn views(&self) -> Vec<ViewToolSet> {
+        self.views.lock().unwrap().values().map(|v| v.tools.clone()).collect()
+    }
+
+    /// Register an initialized view and list its tools
+    ///
+    /// A view whose tools cannot be listed is not registered.
+    pub async fn register(
+        &self,
+        session: &UiSession,
+        transport: Arc<dyn ViewToolTransport>,
+    ) -> Result<Vec<Tool>, ViewToolError> {
+        let tools = ViewToolSet {
+            session_id: session.id.clone(),
+            server_id: session.server_id.clone(),
+            resource_uri: session.resource_uri.clone(),
+            app_name: session.app_info.as_ref().map(|a| a.name.clone()),
+            tools: Vec::new(),
+        };
+        self.views.lock().unwrap().insert(session.id.clone(), RegisteredView { tools, transport });
+        let listed = self.refresh(&session.id).await;
+        if listed.is_err() {
+            self.views.lock().unwrap().remove(&session.id);
+        }
+        listed
+    }
+
+    /// List a view's tools again
+    pub async fn refresh(&self, session_id: &str) -> Result<Vec<Tool>, ViewToolError> {
+        let transport = self.transport(session_id)?;
+        let result = transport.request("tools/list", json!({}), DEFAULT_VIEW_TOOL_TIMEOUT).await.map_err(ViewToolError::Request)?;
+        let tools = serde_json::from_value::<ListToolsResult>(result)
+            .map_err(|e| ViewToolError::InvalidResult(e.to_string()))?
+            .tools;
+
+        // The view may have gone away while it was answering
+        let updated = {
+            let mut views = self.views.lock().unwrap();
+            let view = views.get_mut(session_id).ok_or_else(|| ViewToolError::ViewNotFound(session_id.to_string()))?;
+            (view.tools.tools != tools).then(|| {
+                view.tools.tools = tools.clone();
+                view.tools.clone()
+            })
+        };
+        if let Some(updated) = updated {
+            log::info!("View {} exposes {} tools", session_id, updated.tools.len());
+            let _ = self.events.send(ViewToolsEvent::Updated(updated));
+        }
+        Ok(tools)
+    }
+
+    /// Call a tool a view exposes
+    pub async fn call(&self, session_id: &str, name: &str, arguments: Value) -> Result<CallToolResult, ViewToolError> {
+        let transport = {
+            let views = self.views.lock().unwrap();
+            let view = views.get(session_id).ok_or_else(|| ViewToolError::ViewNotFound(session_id.to_string()))?;
+            if !view.tools.tools.iter().any(|t| t.name == name) {
+                return Err(ViewToolError::ToolNotFound { session_id: session_id.to_string(), name: name.to_string() });
+            }
+            view.transport.clone()
+        };
+
+        log::info!("Calling tool {} on view {}", name, session_id);
+        let params = json!({ "name": name, "arguments": arguments });
+        let result = transport.request("tools/call", params, DEFAULT_VIEW_TOOL_TIMEOUT).await.map_err(ViewToolError::Request)?;
+        serde_json::from_value(result).map_err(|e| ViewToolError::InvalidResult(e.to_string()))
+    }
+
+    /// Forget a view's tools
+    pub fn remove(&self, session_id: &str) {
+        if self.views.lock().unwrap().remove(session_id).is_some() {
+            let _ = self.events.send(ViewToolsEvent::Removed { session_id: session_id.to_string() });
+        }
+    }
+
+    fn transport(&self, session_id: &str) -> Result<Arc<dyn ViewToolTransport>, ViewToolError> {
+        self.views
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .map(|v| v.transport.clone())
+            .ok_or_else(|| ViewToolError::ViewNotFound(session_id.to_string()))
+    }
+}
+
+impl Default for ViewTools {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A chart view with a `get_selection` tool, and `get_range` once enabled
+    struct ChartView {
+        range: Mutex<bool>,
+    }
+
+    #[async_trait::async_trait]
+    impl ViewToolTransport for ChartView {
+        async fn request(&self, method: &str, params: Value, _timeout: Duration) -> Result<Value, JsonRpcError> {
+            let tool = |name: &str| json!({ "name": name, "inputSchema": { "type": "object" } });
+            match method {
+                "tools/list" if *self.range.lock().unwrap() => Ok(json!({ "tools": [tool("get_selection"), tool("get_range")] })),
+                "tools/list" => Ok(json!({ "tools": [tool("get_selection")] })),
+                "tools/call" => Ok(json!({
+                    "content": [{ "type": "text", "text": "2 points" }],
+                    "structuredContent": { "tool": params["name"], "points": [3, 5] },
+                })),
+                _ => Err(JsonRpcError::new(error_codes::METHOD_NOT_FOUND, "Method not found")),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_view_tools_are_listed_and_called() {
+        let registry = ViewTools::new();
+        let mut events = registry.subscribe();
+        let view = Arc::new(ChartView { range: Mutex::new(false) });
+        let session = UiSession::new("v1", "charts", "ui://charts/line");
+
+        let tools = registry.register(&session, view.clone()).await.unwrap();
+        assert_eq!(tools.len(), 1);
+        assert!(matches!(events.try_recv(), Ok(ViewToolsEvent::Updated(set)) if set.tools.len() == 1));
+
+        let result = registry.call("v1", "get_selection", json!({})).await.unwrap();
+        assert_eq!(result.structured_content, Some(json!({ "tool": "get_selection", "points": [3, 5] })));
+        assert!(matches!(
+            registry.call("v1", "get_range", json!({})).await,
+            Err(ViewToolError::ToolNotFound { .. })
+        ));
+
+        // Following list_changed
+        *view.range.lock().unwrap() = true;
+        assert_eq!(registry.refresh("v1").await.unwrap().len(), 2);
+        assert!(registry.call("v1", "get_range", json!({})).await.is_ok());
+        // An unchanged list is not announced again
+        registry.refresh("v1").await.unwrap();
+        assert!(matches!(events.try_recv(), Ok(ViewToolsEvent::Updated(set)) if set.tools.len() == 2));
+        assert!(events.try_recv().is_err());
+
+        registry.remove("v1");
+        assert!(registry.views().is_empty());
+        assert!(matches!(registry.call("v1", "get_selection", json!({})).await, Err(ViewToolError::ViewNotFound(_))));
+    }
+
+    /// A view that never answers `tools/list`
+    struct SilentView;
+
+    #[async_trait::async_trait]
+    impl ViewToolTransport for SilentView {
+        async fn request(&self, _method: &str, _params: Value, _timeout: Duration) -> Result<Value, JsonRpcError> {
+            Err(JsonRpcError::new(error_codes::INTERNAL_ERROR, "Request timed out"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unlisted_view_is_not_registered() {
+        let registry = ViewTools::new();
+        let session = UiSession::new("v1", "charts", "ui://charts/line");
+
+        assert!(matches!(registry.register(&session, Arc::new(SilentView)).await, Err(ViewToolError::Request(_))));
+        assert!(registry.views().is_empty());
+    }
+}
@@ -0,0 +1,10 @@
+//! MCP Apps Host
+//!
+//! Host, protocol and UI modules, built as a library so the desktop
+//! application, the standalone binaries and the benchmarks link one copy of
+//! them. The embedded demo server stays private to the crate.
+
+pub mod host;
+pub mod protocol;
+mod server;
+pub mod ui;
@@ -7,14 +7,15 @@
 
 use dioxus::prelude::*;
 
-mod host;
-mod protocol;
-mod server;
-mod ui;
+use dioxus_desktop_barebones::{host, protocol, ui};
 
-use host::{ConnectionManager, ConnectionState, HostState};
+use host::{
+    AppOrigins, ConnectionManager, Conversation, HostState, LinkBroker, MessageRole, ModelContextStore, ObservableHostState,
+    PermissionBroker,
+    PermissionDecision, UiSessionEvent, ViewLogEntry, ViewLogEvent, ViewLogs,
+};
 use protocol::*;
-use ui::{UiContent, UiContentRenderer, UiMessageEvent};
+use ui::{BridgeManager, ToolCaller, UiContent, UiContentRenderer, UiMessageEvent, ViewSession};
 
 fn main() {
     dioxus::launch(App);
@@ -67,12 +68,59 @@ struct AppState {
     pub error_message: Signal<Option<String>>,
     /// Current display mode for the UI
     pub display_mode: Signal<DisplayMode>,
+    /// Bridges and lifecycle sessions for rendered views
+    pub bridge_manager: BridgeManager,
+    /// Host-side session for the currently rendered view
+    pub view_session: Signal<Option<ViewSession>>,
+    /// Theme, container size and other host state shared with views
+    pub host_state: ObservableHostState,
+    /// Current host theme, kept in step with `host_state`
+    pub theme: Signal<String>,
+    /// Confirms and opens links requested by views
+    pub link_broker: LinkBroker,
+    /// Chat transcript views post into with `ui/message`
+    pub conversation: Conversation,
+    /// Whether the chat transcript panel is open
+    pub show_chat: Signal<bool>,
+    /// Log messages from each view, also written to the host log
+    pub view_logs: ViewLogs,
+    /// Whether the current view's log panel is open
+    pub show_view_logs: Signal<bool>,
+    /// User consent for view sandbox permissions
+    pub permission_broker: PermissionBroker,
+    /// Sandbox permissions granted to the current view
+    pub granted_permissions: Signal<Option<UiPermissions>>,
+    /// Whether the permission settings panel is open
+    pub show_permission_settings: Signal<bool>,
+    /// Origin of the sandbox proxy, once one is available
+    pub sandbox_proxy_origin: Signal<Option<String>>,
+    /// Dedicated origin the current view is served from
+    pub app_origin: Signal<Option<String>>,
+    /// Origins held by live views, released with their bridges
+    pub app_origins: AppOrigins,
 }
 
 impl AppState {
     pub fn new() -> Self {
-        let host_state = HostState::default();
-        let connection_manager = ConnectionManager::new(host_state);
+        let host_state = HostState::default().with_legacy_bridge(ui::legacy_bridge_from_env());
+        ui::set_script_limits(&ui::ScriptLimits::from_env());
+        let connection_manager = ConnectionManager::new(host_state.clone());
+        let link_broker = LinkBroker::default();
+        let conversation = Conversation::new().with_confirmation(host::confirm_messages_from_env());
+        let view_logs = ViewLogs::new();
+        let app_origins = AppOrigins::new();
+        let mut bridge_manager = BridgeManager::new()
+            .with_link_broker(link_broker.clone())
+            .with_model_context(ModelContextStore::new())
+            .with_conversation(conversation.clone())
+            .with_view_logs(view_logs.clone())
+            .with_view_tools(connection_manager.view_tools().clone())
+            .with_connection_manager(connection_manager.clone())
+            .with_app_origins(app_origins.clone());
+        match host::AuditLog::open_default() {
+            Ok(audit) => bridge_manager = bridge_manager.with_audit_log(audit),
+            Err(e) => log::warn!("Audit log unavailable, view activity will not be recorded: {}", e),
+        }
         
         Self {
             connection_manager: Signal::new(connection_manager),
@@ -81,6 +129,25 @@ impl AppState {
             ui_content: Signal::new(UiContent::Loading),
             error_message: Signal::new(None),
             display_mode: Signal::new(DisplayMode::Inline),
+            bridge_manager,
+            link_broker,
+            conversation,
+            show_chat: Signal::new(false),
+            view_logs,
+            show_view_logs: Signal::new(false),
+            view_session: Signal::new(None),
+            theme: Signal::new(host_state.theme.clone()),
+            host_state: ObservableHostState::new(host_state),
+            permission_broker: PermissionBroker::open_default().unwrap_or_else(|e| {
+                log::warn!("Permission decisions will not be remembered: {}", e);
+                PermissionBroker::in_memory()
+            }),
+            granted_permissions: Signal::new(None),
+            show_permission_settings: Signal::new(false),
+            // Web builds use the separately deployed proxy; desktop starts its own
+            sandbox_proxy_origin: Signal::new(cfg!(feature = "web").then(|| ui::sandbox_proxy_origin().to_string())),
+            app_origin: Signal::new(None),
+            app_origins,
         }
     }
 
@@ -88,47 +155,143 @@ impl AppState {
     pub async fn load_tool(&mut self, conn_id: String, tool_name: String, resource_uri: String) {
         println!("State: Loading tool {}...", tool_name);
         
+        // 0. Let the current view persist its state before it is unmounted
+        let previous = self.view_session.read().clone();
+        if let Some(previous) = previous {
+            if let Err(e) = previous.teardown(Some("Switching views"), ui::DEFAULT_TEARDOWN_TIMEOUT).await {
+                log::warn!("{}", e);
+            }
+            self.bridge_manager.remove_bridge(previous.session_id()).await;
+            self.view_session.set(None);
+        }
+        
         // 1. Set Loading State
         self.ui_content.set(UiContent::Loading);
+        self.granted_permissions.set(None);
+        self.app_origin.set(None);
         
         // 2. Create and set session
+        let session_id = uuid::Uuid::new_v4().to_string();
         let session = ui::UiSessionState::new(
-            uuid::Uuid::new_v4().to_string(),
+            session_id.clone(),
             conn_id.clone(),
             resource_uri.clone(),
         );
         self.active_session.set(Some(session));
 
-        // 3. Execute Tool Call
+        // 3. Create the host-side lifecycle session for the view
         let manager = self.connection_manager.read().clone();
-        // For demonstration, we'll hardcode some arguments or use defaults
-        // In a real app, this would come from a form or input
-        let args = serde_json::json!({ "location": "San Francisco" });
+        let connection = manager.get_connection(&conn_id).await;
+        let mut host_session = host::UiSession::new(session_id, conn_id.clone(), resource_uri.clone());
+        let tool = match &connection {
+            Some(conn) => conn.find_tool(&tool_name).await,
+            None => None,
+        };
+        if let Some(tool) = tool {
+            host_session = host_session.with_tool_info(ToolInfo {
+                id: None,
+                tool: serde_json::to_value(&tool).unwrap_or_default(),
+            });
+        }
+        let view_session = self.bridge_manager.create_session(
+            host_session,
+            self.host_state.get(),
+            connection.and_then(|c| c.parsed_capabilities),
+        ).await;
+        self.view_session.set(Some(view_session.clone()));
+        self.display_mode.set(DisplayMode::Inline);
         
-        println!("State: Calling tool on manager...");
-        let result = match manager.call_tool(&conn_id, &tool_name, args).await {
-            Ok(res) => res,
-            Err(e) => {
-                println!("State: Tool call failed: {}", e);
-                self.ui_content.set(UiContent::Error(format!("Tool call failed: {}", e)));
-                return;
+        // Follow display mode changes granted to the view
+        let mut events = view_session.subscribe();
+        let mut display_mode = self.display_mode;
+        let mut active_session = self.active_session;
+        spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(UiSessionEvent::DisplayModeChanged { mode, .. }) => {
+                        display_mode.set(mode);
+                        let session = active_session.read().clone();
+                        if let Some(mut session) = session {
+                            session.display_mode = mode;
+                            active_session.set(Some(session));
+                        }
+                    }
+                    Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
             }
-        };
+        });
 
-        // 4. Fetch UI Resource
+        // 4. Fetch UI Resource and render the view before the tool runs
         println!("State: Reading resource...");
         match manager.read_ui_resource(&conn_id, &resource_uri).await {
             Ok(resource_content) => {
                 println!("State: Resource loaded.");
-                let tool_result_json = serde_json::to_string(&result).unwrap_or_default();
-                let content = UiContent::from_resource_content(resource_content, Some(tool_result_json));
+                let content = UiContent::from_resource_content(resource_content);
+                if let Some((source, metadata)) = content.view_source() {
+                    // Ask before the view gets any sandbox permission
+                    let requested = metadata
+                        .and_then(|m| m.ui.as_ref())
+                        .and_then(|u| u.permissions.clone())
+                        .unwrap_or_default();
+                    let grantable = self.host_state.get().grantable_permissions;
+                    let granted = self.permission_broker.request(&conn_id, &resource_uri, &requested, &grantable).await;
+                    view_session.set_granted_permissions(granted).await;
+                    self.granted_permissions.set(Some(view_session.granted_permissions().await));
+                    // Every view load is audited, whichever renderer shows it
+                    view_session.record_view_loaded(source, metadata).await;
+                }
+                if let UiContent::Html { metadata, .. } = &content {
+                    // Serve the view from its own origin when a sandbox proxy is running
+                    let proxy_origin = self.sandbox_proxy_origin.read().clone();
+                    let declared = metadata.as_ref()
+                        .and_then(|m| m.ui.as_ref())
+                        .and_then(|u| u.domain.as_deref());
+                    let approved = self.host_state.get().approved_app_domains;
+                    let session = view_session.session().await;
+                    let app_origin = proxy_origin.and_then(|proxy| {
+                        self.app_origins.resolve(&session, &proxy, declared, &approved)
+                    });
+                    self.app_origin.set(app_origin.map(|o| o.to_string()));
+                }
                 self.ui_content.set(content);
             }
             Err(e) => {
                 println!("State: Resource load failed: {}", e);
                 self.ui_content.set(UiContent::Error(format!("Failed to load UI resource: {}", e)));
+                return;
             }
         }
+
+        // 5. Deliver tool input (held back until the view is initialized)
+        // For demonstration, we'll hardcode some arguments or use defaults
+        // In a real app, this would come from a form or input; an agent streaming
+        // its arguments feeds them through BridgeManager::push_tool_input_chunk
+        let args = serde_json::json!({ "location": "San Francisco" });
+        if let Err(e) = view_session.send_tool_input(args.clone()).await {
+            log::error!("Failed to send tool input: {}", e);
+        }
+
+        // 6. Execute Tool Call and deliver the result
+        println!("State: Calling tool on manager...");
+        let result = match manager.call_tool(&conn_id, &tool_name, args).await {
+            Ok(res) => serde_json::to_value(&res).unwrap_or_default(),
+            Err(e) => {
+                println!("State: Tool call failed: {}", e);
+                if self.ui_content.read().is_rhai() {
+                    self.ui_content.set(UiContent::Error(format!("Tool call failed: {}", e)));
+                }
+                serde_json::json!({
+                    "content": [{ "type": "text", "text": format!("Tool call failed: {}", e) }],
+                    "isError": true,
+                })
+            }
+        };
+
+        self.ui_content.with_mut(|content| content.set_tool_result(&result));
+        if let Err(e) = view_session.send_tool_result(result).await {
+            log::error!("Failed to send tool result: {}", e);
+        }
     }
 }
 
@@ -181,6 +344,16 @@ fn ToolItem(props: ToolItemProps) -> Element {
 fn Sidebar() -> Element {
     let app_state = use_context::<AppState>();
     let mut tools = use_signal(Vec::new);
+    let theme = app_state.theme;
+    let host_state = app_state.host_state.clone();
+    let mut show_permission_settings = app_state.show_permission_settings;
+    let mut show_chat = app_state.show_chat;
+    let mut show_view_logs = app_state.show_view_logs;
+    
+    let toggle_theme = move |_| {
+        let next = if theme.read().as_str() == "dark" { "light" } else { "dark" };
+        host_state.update(|state| state.theme = next.to_string());
+    };
     
     // Refresh tools list periodically
     use_effect(move || {
@@ -230,8 +403,41 @@ fn Sidebar() -> Element {
             }
             
             // Footer
-            div { class: "p-4 border-t border-gray-100 text-xs text-center text-gray-400",
-                "MCP Apps Host v0.1.0"
+            div { class: "p-4 border-t border-gray-100 text-xs text-gray-400 flex items-center justify-between",
+                span { "MCP Apps Host v0.1.0" }
+                button {
+                    class: "px-2 py-1 rounded hover:bg-gray-100 text-gray-500",
+                    title: "Permission settings",
+                    onclick: move |_| {
+                        let open = *show_permission_settings.read();
+                        show_permission_settings.set(!open);
+                    },
+                    "🔐"
+                }
+                button {
+                    class: "px-2 py-1 rounded hover:bg-gray-100 text-gray-500",
+                    title: "Conversation",
+                    onclick: move |_| {
+                        let open = *show_chat.read();
+                        show_chat.set(!open);
+                    },
+                    "💬"
+                }
+                button {
+                    class: "px-2 py-1 rounded hover:bg-gray-100 text-gray-500",
+                    title: "View logs",
+                    onclick: move |_| {
+                        let open = *show_view_logs.read();
+                        show_view_logs.set(!open);
+                    },
+                    "📜"
+                }
+                button {
+                    class: "px-2 py-1 rounded hover:bg-gray-100 text-gray-500",
+                    title: "Toggle theme",
+                    onclick: toggle_theme,
+                    if theme.read().as_str() == "dark" { "☀️" } else { "🌙" }
+                }
             }
         }
     }
@@ -243,6 +449,47 @@ fn McpHost() -> Element {
     // Initialize application state
     let app_state = use_context_provider(|| AppState::new());
     
+    // Forward host state changes to every live view
+    use_hook({
+        let app_state = app_state.clone();
+        move || {
+            let mut changes = app_state.host_state.subscribe();
+            let bridge_manager = app_state.bridge_manager.clone();
+            let mut theme = app_state.theme;
+            spawn(async move {
+                while changes.changed().await.is_ok() {
+                    let state = changes.borrow_and_update().clone();
+                    if *theme.peek() != state.theme {
+                        theme.set(state.theme.clone());
+                    }
+                    bridge_manager.broadcast_host_state(&state).await;
+                }
+            });
+        }
+    });
+    
+    // Desktop builds serve views from dedicated origins through an in-process sandbox proxy
+    #[cfg(not(target_arch = "wasm32"))]
+    use_hook({
+        let mut proxy_origin = app_state.sandbox_proxy_origin;
+        move || {
+            if proxy_origin.peek().is_some() {
+                return;
+            }
+            spawn(async move {
+                match ui::sandbox_proxy::server::SandboxProxyServer::bind(ui::DEFAULT_SANDBOX_PROXY_ADDR).await {
+                    Ok(server) => {
+                        proxy_origin.set(Some(server.origin()));
+                        // Keep serving for the life of the app
+                        std::future::pending::<()>().await;
+                        drop(server);
+                    }
+                    Err(e) => log::warn!("Sandbox proxy unavailable, views will use opaque origins: {}", e),
+                }
+            });
+        }
+    });
+    
     // Auto-connect to embedded server on mount
     let mut conn_signal = app_state.selected_connection;
     let mut err_signal = app_state.error_message;
@@ -277,6 +524,304 @@ fn McpHost() -> Element {
             
             // Main Content
             MainContent {}
+            
+            if *app_state.show_chat.read() {
+                ChatPanel {}
+            }
+            
+            PermissionPrompt {}
+            LinkPrompt {}
+            MessagePrompt {}
+            if *app_state.show_permission_settings.read() {
+                PermissionSettings {}
+            }
+        }
+    }
+}
+
+/// Human-readable names for requested sandbox permissions
+fn permission_labels(permissions: &UiPermissions) -> String {
+    let labels: Vec<&str> = permissions
+        .granted()
+        .into_iter()
+        .map(|p| match p {
+            "camera" => "📷 Camera",
+            "microphone" => "🎤 Microphone",
+            "geolocation" => "📍 Geolocation",
+            "clipboard-write" => "📋 Clipboard",
+            other => other,
+        })
+        .collect();
+    if labels.is_empty() { "None".to_string() } else { labels.join(", ") }
+}
+
+/// Follow a watch channel into a signal
+fn use_watch<T: Clone + Default + 'static>(subscribe: impl FnOnce() -> tokio::sync::watch::Receiver<T>) -> Signal<T> {
+    let mut value = use_signal(T::default);
+    use_hook(move || {
+        let mut changes = subscribe();
+        spawn(async move {
+            loop {
+                value.set(changes.borrow_and_update().clone());
+                if changes.changed().await.is_err() {
+                    break;
+                }
+            }
+        });
+    });
+    value
+}
+
+/// How a prompt button is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PromptButtonStyle {
+    Dismiss,
+    Secondary,
+    Primary,
+}
+
+/// Modal shell shared by the host's prompts
+#[component]
+fn PromptDialog(title: String, actions: Element, children: Element) -> Element {
+    rsx! {
+        div { class: "fixed inset-0 z-50 flex items-center justify-center bg-black/30",
+            div { class: "bg-white rounded-lg shadow-xl p-6 w-96 space-y-4",
+                div { class: "text-lg font-semibold text-gray-800", "{title}" }
+                {children}
+                div { class: "flex justify-end gap-2", {actions} }
+            }
+        }
+    }
+}
+
+/// Button answering a prompt
+#[component]
+fn PromptButton(label: String, style: PromptButtonStyle, onclick: EventHandler<MouseEvent>) -> Element {
+    let class = match style {
+        PromptButtonStyle::Dismiss => "px-3 py-1 rounded bg-gray-100 hover:bg-gray-200 text-gray-700 text-sm",
+        PromptButtonStyle::Secondary => "px-3 py-1 rounded bg-indigo-50 hover:bg-indigo-100 text-indigo-700 text-sm",
+        PromptButtonStyle::Primary => "px-3 py-1 rounded bg-indigo-600 hover:bg-indigo-700 text-white text-sm",
+    };
+    rsx! {
+        button { class, onclick: move |evt| onclick.call(evt), "{label}" }
+    }
+}
+
+/// Consent dialog for the oldest pending permission request
+#[component]
+fn PermissionPrompt() -> Element {
+    let app_state = use_context::<AppState>();
+    let broker = app_state.permission_broker.clone();
+    let prompts = use_watch({
+        let broker = broker.clone();
+        move || broker.subscribe_prompts()
+    });
+    
+    let Some(request) = prompts.read().first().cloned() else {
+        return rsx! {};
+    };
+    let requested = permission_labels(&request.requested);
+    let respond = move |decision: PermissionDecision| {
+        let broker = broker.clone();
+        let id = request.id.clone();
+        move |_| {
+            broker.respond(&id, decision);
+        }
+    };
+    
+    rsx! {
+        PromptDialog {
+            title: "Permission request",
+            actions: rsx! {
+                PromptButton { label: "Deny", style: PromptButtonStyle::Dismiss, onclick: respond(PermissionDecision::Deny) }
+                PromptButton { label: "Allow once", style: PromptButtonStyle::Secondary, onclick: respond(PermissionDecision::AllowOnce) }
+                PromptButton { label: "Always allow", style: PromptButtonStyle::Primary, onclick: respond(PermissionDecision::Always) }
+            },
+            div { class: "text-sm text-gray-600",
+                "{request.resource_uri} from server {request.server_id} wants:"
+            }
+            div { class: "text-sm font-medium text-gray-800", "{requested}" }
+        }
+    }
+}
+
+/// Confirmation dialog for the oldest pending link request
+#[component]
+fn LinkPrompt() -> Element {
+    let app_state = use_context::<AppState>();
+    let broker = app_state.link_broker.clone();
+    let prompts = use_watch({
+        let broker = broker.clone();
+        move || broker.subscribe_prompts()
+    });
+    
+    let Some(request) = prompts.read().first().cloned() else {
+        return rsx! {};
+    };
+    let respond = move |approved: bool| {
+        let broker = broker.clone();
+        let id = request.id.clone();
+        move |_| {
+            broker.respond(&id, approved);
+        }
+    };
+    
+    rsx! {
+        PromptDialog {
+            title: "Open link?",
+            actions: rsx! {
+                PromptButton { label: "Cancel", style: PromptButtonStyle::Dismiss, onclick: respond(false) }
+                PromptButton { label: "Open", style: PromptButtonStyle::Primary, onclick: respond(true) }
+            },
+            div { class: "text-sm text-gray-600",
+                "{request.app_name} (server {request.server_id}) wants to open:"
+            }
+            div { class: "text-sm font-mono break-all text-gray-800 bg-gray-50 p-2 rounded", "{request.url}" }
+        }
+    }
+}
+
+/// Confirmation dialog for the oldest pending view message
+#[component]
+fn MessagePrompt() -> Element {
+    let app_state = use_context::<AppState>();
+    let conversation = app_state.conversation.clone();
+    let prompts = use_watch({
+        let conversation = conversation.clone();
+        move || conversation.subscribe_prompts()
+    });
+    
+    let Some(request) = prompts.read().first().cloned() else {
+        return rsx! {};
+    };
+    let text = request.text();
+    let respond = move |approved: bool| {
+        let conversation = conversation.clone();
+        let id = request.id.clone();
+        move |_| {
+            conversation.respond(&id, approved);
+        }
+    };
+    
+    rsx! {
+        PromptDialog {
+            title: "Post message?",
+            actions: rsx! {
+                PromptButton { label: "Decline", style: PromptButtonStyle::Dismiss, onclick: respond(false) }
+                PromptButton { label: "Post", style: PromptButtonStyle::Primary, onclick: respond(true) }
+            },
+            div { class: "text-sm text-gray-600",
+                "{request.source.app_name} (server {request.source.server_id}) wants to post to the conversation:"
+            }
+            div { class: "text-sm whitespace-pre-wrap text-gray-800 bg-gray-50 p-2 rounded max-h-48 overflow-y-auto", "{text}" }
+        }
+    }
+}
+
+/// Chat transcript of the host conversation
+#[component]
+fn ChatPanel() -> Element {
+    let app_state = use_context::<AppState>();
+    let conversation = app_state.conversation.clone();
+    let mut show = app_state.show_chat;
+    let messages = use_watch({
+        let conversation = conversation.clone();
+        move || conversation.subscribe_messages()
+    });
+    
+    rsx! {
+        div { class: "w-80 bg-white border-l border-gray-200 flex flex-col",
+            div { class: "p-4 border-b border-gray-100 flex items-center justify-between",
+                div { class: "text-sm font-semibold text-gray-800", "Conversation" }
+                div { class: "flex gap-1",
+                    button {
+                        class: "px-2 py-1 rounded hover:bg-gray-100 text-gray-500 text-xs",
+                        onclick: move |_| conversation.clear(),
+                        "Clear"
+                    }
+                    button {
+                        class: "p-1 rounded hover:bg-gray-100 text-gray-500",
+                        onclick: move |_| show.set(false),
+                        "✕"
+                    }
+                }
+            }
+            div { class: "flex-1 overflow-y-auto p-4 space-y-3",
+                if messages.read().is_empty() {
+                    div { class: "text-sm text-gray-400 text-center py-8", "No messages yet" }
+                }
+                for message in messages.read().iter().cloned() {
+                    div {
+                        key: "{message.id}",
+                        class: if message.role == MessageRole::User {
+                            "ml-6 p-2 rounded-lg bg-indigo-50 text-sm text-gray-800"
+                        } else {
+                            "mr-6 p-2 rounded-lg bg-gray-100 text-sm text-gray-800"
+                        },
+                        if let Some(source) = &message.source {
+                            div { class: "text-xs text-gray-400 mb-1", "via {source.app_name}" }
+                        }
+                        div { class: "whitespace-pre-wrap", "{message.text()}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Remembered permission decisions, with revocation
+#[component]
+fn PermissionSettings() -> Element {
+    let app_state = use_context::<AppState>();
+    let broker = app_state.permission_broker.clone();
+    let mut show = app_state.show_permission_settings;
+    let grants = use_watch({
+        let broker = broker.clone();
+        move || broker.subscribe_grants()
+    });
+    
+    rsx! {
+        div { class: "fixed inset-0 z-40 flex items-center justify-center bg-black/30",
+            div { class: "bg-white rounded-lg shadow-xl p-6 w-[32rem] space-y-4",
+                div { class: "flex items-center justify-between",
+                    div { class: "text-lg font-semibold text-gray-800", "Sandbox permissions" }
+                    button {
+                        class: "p-1 rounded hover:bg-gray-100 text-gray-500",
+                        onclick: move |_| show.set(false),
+                        "✕"
+                    }
+                }
+                if grants.read().is_empty() {
+                    div { class: "text-sm text-gray-400", "No remembered decisions" }
+                }
+                for grant in grants.read().iter().cloned() {
+                    div {
+                        key: "{grant.server_id}-{grant.resource_uri}",
+                        class: "flex items-center justify-between text-sm border-b border-gray-100 py-2",
+                        div {
+                            div { class: "font-medium text-gray-700", "{grant.resource_uri}" }
+                            div { class: "text-xs text-gray-400",
+                                "{grant.server_id} · "
+                                if grant.decision == PermissionDecision::Deny {
+                                    "Denied"
+                                } else {
+                                    "Allowed: {permission_labels(&grant.permissions)}"
+                                }
+                            }
+                        }
+                        button {
+                            class: "px-2 py-1 rounded bg-gray-100 hover:bg-red-50 text-red-600 text-xs",
+                            onclick: {
+                                let broker = broker.clone();
+                                move |_| {
+                                    broker.revoke(&grant.server_id, &grant.resource_uri);
+                                }
+                            },
+                            "Revoke"
+                        }
+                    }
+                }
+            }
         }
     }
 }
@@ -287,52 +832,92 @@ fn MainContent() -> Element {
     let mut app_state = use_context::<AppState>();
     let ui_content = app_state.ui_content.read().clone();
     let mut display_mode = app_state.display_mode;
-    let mut active_session = app_state.active_session;
+    let active_session = app_state.active_session;
+    let view_session_signal = app_state.view_session;
+    let view_session = view_session_signal.read().clone();
+    let host_state = app_state.host_state.clone();
     
-    // Get host state and create host context
-    let host_state = use_memo(move || {
-        app_state.connection_manager.read().host_state.clone()
+    // Initial host context injected with the view; later changes are
+    // delivered through ui/notifications/host-context-changed. Tracking the
+    // theme re-themes natively rendered content.
+    let theme = app_state.theme;
+    let host_context = use_memo({
+        let host_state = host_state.clone();
+        move || {
+            theme.read();
+            host_state.get().to_host_context()
+        }
     });
     
-    let host_context = use_memo(move || {
-        host_state.read().to_host_context()
-    });
+    // Switch display mode and tell the current view
+    let mut set_display_mode = move |mode: DisplayMode| {
+        display_mode.set(mode);
+        if let Some(view) = view_session_signal.read().clone() {
+            spawn(async move {
+                if let Err(e) = view.set_display_mode(mode).await {
+                    log::warn!("Failed to update display mode for view {}: {}", view.session_id(), e);
+                }
+            });
+        }
+    };
+    
+    let approved_csp = host_state.get().approved_csp;
+    let legacy_bridge = host_state.get().legacy_bridge;
+    
+    // Track the content area size as the container dimensions
+    let on_resize = move |event: Event<ResizeData>| {
+        if let Ok(size) = event.get_content_box_size() {
+            host_state.update(|state| {
+                state.container_dimensions.max_width = Some(size.width as u32);
+                state.container_dimensions.max_height = Some(size.height as u32);
+            });
+        }
+    };
     
     // Handle UI messages
     let handle_message = move |event: UiMessageEvent| {
         match event {
             UiMessageEvent::RequestDisplayMode { mode } => {
                 log::info!("UI requested display mode: {:?}", mode);
-                display_mode.set(mode.clone());
-                // Update session display mode if active
-                let session = active_session.read().as_ref().cloned();
-                if let Some(mut session) = session {
-                    session.display_mode = mode;
-                    active_session.set(Some(session));
+                // The view session decides which mode is granted
+                if let Some(view) = view_session_signal.read().clone() {
+                    spawn(async move {
+                        if let Err(e) = view.request_display_mode(mode).await {
+                            log::warn!("Display mode request failed for view {}: {}", view.session_id(), e);
+                        }
+                    });
                 }
             }
-            UiMessageEvent::ToolCall { name, arguments } => {
-                log::info!("UI requested tool call: {} with args {:?}", name, arguments);
-                // Tool calls from UI would be handled here
-                // This requires routing back to the connection manager
+            UiMessageEvent::ToolCall { name, .. } => {
+                // tools/call is checked, audited and forwarded to the server by the view session
+                log::info!("UI requested tool call: {}", name);
             }
-            UiMessageEvent::UpdateModelContext { content, structured_content } => {
+            UiMessageEvent::UpdateModelContext { .. } => {
+                // ui/update-model-context is stored by the view session in the bridge manager's context store
                 log::info!("UI updated model context");
-                // Handle context updates from UI
             }
             UiMessageEvent::Log { level, message } => {
                 log::info!("[UI:{}] {}", level, message);
             }
+            UiMessageEvent::Message { role, .. } => {
+                // ui/message is checked and posted to app_state.conversation by the view session
+                log::info!("UI posted a {} message", role);
+            }
             UiMessageEvent::OpenLink { url } => {
+                // ui/open-link is checked, confirmed and opened by the view session
                 log::info!("UI requested to open link: {}", url);
-                // In a full implementation, this would open the link
-                // with user confirmation based on capability negotiation
             }
             UiMessageEvent::SizeChanged { width, height } => {
-                log::info!("UI size changed: {}x{}", width, height);
+                // The view session fits the inline container to the content
+                log::debug!("View container resized to {}x{}", width, height);
             }
-            _ => {
-                log::info!("UI Message: {:?}", event);
+            UiMessageEvent::JsonRpc(message) => {
+                log::info!("Unhandled UI message: {}", message);
+                if let Some(view) = view_session_signal.read().clone() {
+                    if let Err(e) = view.reject_unhandled(&message) {
+                        log::warn!("Failed to answer view {}: {}", view.session_id(), e);
+                    }
+                }
             }
         }
     };
@@ -346,6 +931,24 @@ fn MainContent() -> Element {
     
     let is_overlay = matches!(display_mode.read().clone(), DisplayMode::Fullscreen | DisplayMode::Pip);
     
+    // Log panel for the current view, when open
+    let log_session_id = view_session
+        .as_ref()
+        .filter(|_| *app_state.show_view_logs.read())
+        .map(|view| view.session_id().to_string());
+    
+    // Rhai event handlers may call the tools the view's server lets views call
+    let tool_caller = use_memo(move || {
+        let view = view_session_signal.read().clone()?;
+        Some(ToolCaller::new(move |name: String, arguments| {
+            let view = view.clone();
+            async move {
+                let result = view.call_server_tool(&name, arguments).await.map_err(|(_, message)| message)?;
+                serde_json::to_value(&result).map_err(|e| e.to_string())
+            }
+        }))
+    });
+    
     // Create a key for the content renderer based on the session ID or resource URI
     // This forces a full re-render when switching tools
     let content_key = active_session.read().as_ref()
@@ -356,12 +959,13 @@ fn MainContent() -> Element {
         div { class: "flex-1 flex flex-col overflow-hidden relative bg-white",
             // Content Area
             div { class: "flex-1 overflow-y-auto p-8 {display_class}",
+                onresize: on_resize,
                 // Close button for expanded/fullscreen modes
                 if is_overlay {
                     div { class: "absolute top-4 right-4 z-10",
                         button {
                             class: "p-2 bg-gray-100 hover:bg-gray-200 rounded-full text-gray-600 transition-colors",
-                            onclick: move |_| display_mode.set(DisplayMode::Inline),
+                            onclick: move |_| set_display_mode(DisplayMode::Inline),
                             "✕"
                         }
                     }
@@ -391,11 +995,77 @@ fn MainContent() -> Element {
                                 content: ui_content,
                                 on_message: Some(EventHandler::new(handle_message)),
                                 host_context: Some(host_context.read().clone()),
+                                session: view_session,
+                                approved_csp: approved_csp.clone(),
+                                granted_permissions: app_state.granted_permissions.read().clone(),
+                                app_origin: app_state.app_origin.read().clone(),
+                                tool_caller: tool_caller.read().clone(),
+                                legacy_bridge,
                             }
                         }
                     }
                 }
             }
+            
+            if let Some(session_id) = log_session_id {
+                ViewLogPanel { key: "{session_id}", session_id }
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Color of a log level in the log panel
+fn log_level_class(level: LoggingLevel) -> &'static str {
+    match log::Level::from(level) {
+        log::Level::Error => "text-red-600",
+        log::Level::Warn => "text-amber-600",
+        log::Level::Info => "text-gray-700",
+        log::Level::Debug | log::Level::Trace => "text-gray-400",
+    }
+}
+
+/// Log messages of one view session
+#[component]
+fn ViewLogPanel(session_id: String) -> Element {
+    let app_state = use_context::<AppState>();
+    let logs = app_state.view_logs.clone();
+    let mut entries = use_signal(Vec::<ViewLogEntry>::new);
+    
+    // Follow the session's buffer
+    use_hook({
+        let session_id = session_id.clone();
+        move || {
+            let mut events = logs.subscribe();
+            entries.set(logs.entries(&session_id));
+            spawn(async move {
+                loop {
+                    match events.recv().await {
+                        Ok(ViewLogEvent::Logged { session_id: id, .. } | ViewLogEvent::Removed { session_id: id })
+                            if id != session_id => {}
+                        Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                            entries.set(logs.entries(&session_id));
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+        }
+    });
+    
+    rsx! {
+        div { class: "h-48 border-t border-gray-200 bg-gray-50 overflow-y-auto p-2 font-mono text-xs",
+            if entries.read().is_empty() {
+                div { class: "text-gray-400", "No log messages from this view" }
+            }
+            for (n, entry) in entries.read().iter().cloned().enumerate() {
+                div { key: "{n}-{entry.timestamp}", class: "flex gap-2 {log_level_class(entry.level)}",
+                    span { class: "uppercase w-16 shrink-0", "{entry.level.as_str()}" }
+                    if let Some(logger) = &entry.logger {
+                        span { class: "text-gray-400 shrink-0", "[{logger}]" }
+                    }
+                    span { class: "whitespace-pre-wrap break-all", "{entry.message}" }
+                }
+            }
+        }
+    }
+}
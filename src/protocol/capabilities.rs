@@ -46,12 +46,7 @@ impl UiHostCapabilities {
             server_resources: Some(ServerResourcesCapability { list_changed: Some(true) }),
             logging: Some(Empty {}),
             sandbox: Some(SandboxCapability {
-                permissions: Some(UiPermissions {
-                    camera: Some(Empty {}),
-                    microphone: Some(Empty {}),
-                    geolocation: Some(Empty {}),
-                    clipboard_write: Some(Empty {}),
-                }),
+                permissions: Some(UiPermissions::all()),
                 csp: Some(ApprovedCsp {
                     connect_domains: Some(vec!["*".to_string()]),
                     resource_domains: Some(vec!["*".to_string()]),
@@ -144,6 +139,16 @@ pub struct UiPermissions {
 }
 
 impl UiPermissions {
+    /// Every permission the spec defines
+    pub fn all() -> Self {
+        Self {
+            camera: Some(Empty {}),
+            microphone: Some(Empty {}),
+            geolocation: Some(Empty {}),
+            clipboard_write: Some(Empty {}),
+        }
+    }
+    
     /// Check if any permissions are granted
     pub fn has_any(&self) -> bool {
         self.camera.is_some() ||
@@ -161,6 +166,42 @@ impl UiPermissions {
         if self.clipboard_write.is_some() { granted.push("clipboard-write"); }
         granted
     }
+
+    /// Permissions in both this set and `other`
+    pub fn intersect(&self, other: &UiPermissions) -> UiPermissions {
+        UiPermissions {
+            camera: self.camera.and(other.camera),
+            microphone: self.microphone.and(other.microphone),
+            geolocation: self.geolocation.and(other.geolocation),
+            clipboard_write: self.clipboard_write.and(other.clipboard_write),
+        }
+    }
+
+    /// Permissions granted for a view: those it requested that the host also grants
+    pub fn grant(&self, requested: &super::resources::UiResourcePermissions) -> UiPermissions {
+        self.intersect(&requested.into())
+    }
+
+    /// Check that every permission in `requested` is in this set
+    pub fn covers(&self, requested: &UiPermissions) -> bool {
+        self.intersect(requested) == *requested
+    }
+
+    /// Permission Policy features for an iframe `allow` attribute
+    pub fn allow_attribute(&self) -> String {
+        self.granted().join("; ")
+    }
+}
+
+impl From<&super::resources::UiResourcePermissions> for UiPermissions {
+    fn from(requested: &super::resources::UiResourcePermissions) -> Self {
+        Self {
+            camera: requested.camera,
+            microphone: requested.microphone,
+            geolocation: requested.geolocation,
+            clipboard_write: requested.clipboard_write,
+        }
+    }
 }
 
 /// Approved CSP domains by the host
@@ -181,26 +222,6 @@ pub struct ApprovedCsp {
     pub base_uri_domains: Option<Vec<String>>,
 }
 
-impl ApprovedCsp {
-    /// Check if a domain is approved for connections
-    pub fn allows_connection(&self, domain: &str) -> bool {
-        if let Some(domains) = &self.connect_domains {
-            domains.iter().any(|d| d == "*" || domain.ends_with(d.trim_start_matches("*.")) || domain == d)
-        } else {
-            false
-        }
-    }
-    
-    /// Check if a domain is approved for resources
-    pub fn allows_resource(&self, domain: &str) -> bool {
-        if let Some(domains) = &self.resource_domains {
-            domains.iter().any(|d| d == "*" || domain.ends_with(d.trim_start_matches("*.")) || domain == d)
-        } else {
-            false
-        }
-    }
-}
-
 /// App (View) capabilities sent during ui/initialize
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -368,16 +389,16 @@ pub struct NegotiatedCapabilities {
 /// Negotiate capabilities between host and server
 pub fn negotiate_capabilities(
     host_caps: &UiHostCapabilities,
+    host_modes: &[DisplayMode],
     server_caps: &ServerCapabilities,
     app_caps: Option<&McpUiAppCapabilities>,
 ) -> NegotiatedCapabilities {
-    // Determine supported display modes
-    let host_modes = vec![DisplayMode::Inline, DisplayMode::Fullscreen];
-    let app_modes = app_caps.and_then(|a| a.available_display_modes.clone());
+    // Display modes are the ones both the host and the app support
+    let app_modes = app_caps.and_then(|a| a.available_display_modes.as_ref());
     
     let display_modes = match app_modes {
-        Some(modes) => host_modes.into_iter().filter(|m| modes.contains(m)).collect(),
-        None => host_modes,
+        Some(modes) => host_modes.iter().copied().filter(|m| modes.contains(m)).collect(),
+        None => host_modes.to_vec(),
     };
     
     NegotiatedCapabilities {
@@ -393,6 +414,7 @@ pub fn negotiate_capabilities(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::UiResourcePermissions;
     
     #[test]
     fn test_ui_host_capabilities_full() {
@@ -425,6 +447,11 @@ mod tests {
         assert_eq!(granted.len(), 2);
         assert!(granted.contains(&"camera"));
         assert!(granted.contains(&"geolocation"));
+
+        let requested = UiResourcePermissions { camera: Some(Empty {}), microphone: Some(Empty {}), ..Default::default() };
+        assert_eq!(perms.grant(&requested).granted(), vec!["camera"]);
+        assert!(perms.covers(&perms.grant(&requested)));
+        assert!(!perms.covers(&UiPermissions::from(&requested)));
     }
     
     #[test]
@@ -450,4 +477,22 @@ mod tests {
         let parsed: DisplayMode = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed, DisplayMode::Fullscreen);
     }
+    
+    #[test]
+    fn test_display_mode_negotiation() {
+        let host_caps = UiHostCapabilities::minimal();
+        let server_caps = ServerCapabilities::default();
+        let host_modes = [DisplayMode::Inline, DisplayMode::Pip];
+        
+        let app_caps = McpUiAppCapabilities {
+            available_display_modes: Some(vec![DisplayMode::Inline, DisplayMode::Fullscreen, DisplayMode::Pip]),
+            ..Default::default()
+        };
+        let negotiated = negotiate_capabilities(&host_caps, &host_modes, &server_caps, Some(&app_caps));
+        assert_eq!(negotiated.display_modes, vec![DisplayMode::Inline, DisplayMode::Pip]);
+        
+        // Without app preferences the host modes apply
+        let negotiated = negotiate_capabilities(&host_caps, &host_modes, &server_caps, None);
+        assert_eq!(negotiated.display_modes, host_modes.to_vec());
+    }
 }
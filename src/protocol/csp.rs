@@ -0,0 +1,456 @@
+//! Content Security Policy
+//!
+//! Parses and validates the origins declared in `_meta.ui.csp`, intersects
+//! them with the domains the host approves, and builds the effective policy
+//! for a view. Anything that is not kept is reported with the reason.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::capabilities::ApprovedCsp;
+use super::resources::McpUiResourceCsp;
+
+/// Schemes a declared origin may use
+const ALLOWED_SCHEMES: &[&str] = &["http", "https", "ws", "wss"];
+
+/// Why an origin string was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "camelCase")]
+pub enum CspOriginError {
+    /// Empty string
+    Empty,
+    /// Bare `*` or a wildcard that covers every host
+    Wildcard,
+    /// Scheme other than http(s)/ws(s), e.g. `data:` or `javascript:`
+    DisallowedScheme(String),
+    /// Quotes, separators or whitespace that could inject extra directives
+    ForbiddenCharacter(char),
+    /// Host is missing or malformed
+    InvalidHost(String),
+    /// Port is not a number in range
+    InvalidPort(String),
+    /// Origins may not carry a path, query or fragment
+    UnexpectedPath(String),
+}
+
+impl fmt::Display for CspOriginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CspOriginError::Empty => write!(f, "empty origin"),
+            CspOriginError::Wildcard => write!(f, "wildcard origins are not allowed"),
+            CspOriginError::DisallowedScheme(s) => write!(f, "scheme '{}' is not allowed", s),
+            CspOriginError::ForbiddenCharacter(c) => write!(f, "forbidden character {:?}", c),
+            CspOriginError::InvalidHost(h) => write!(f, "invalid host '{}'", h),
+            CspOriginError::InvalidPort(p) => write!(f, "invalid port '{}'", p),
+            CspOriginError::UnexpectedPath(p) => write!(f, "unexpected path '{}'", p),
+        }
+    }
+}
+
+impl std::error::Error for CspOriginError {}
+
+/// A validated CSP host source such as `https://*.example.com:8443`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspOrigin {
+    /// Lowercase scheme, if one was given
+    pub scheme: Option<String>,
+    /// Lowercase host; a leading `*.` matches any subdomain
+    pub host: String,
+    /// Explicit port
+    pub port: Option<u16>,
+}
+
+impl CspOrigin {
+    /// Parse and validate an origin
+    pub fn parse(input: &str) -> Result<Self, CspOriginError> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(CspOriginError::Empty);
+        }
+        if let Some(c) = input.chars().find(|c| matches!(c, '\'' | '"' | ';' | ',' | '`') || c.is_whitespace() || c.is_control()) {
+            return Err(CspOriginError::ForbiddenCharacter(c));
+        }
+
+        let (scheme, rest) = match input.split_once("://") {
+            Some((scheme, rest)) => (Some(scheme.to_ascii_lowercase()), rest),
+            None => match input.split_once(':') {
+                // `data:`, `blob:`, `javascript:` and friends
+                Some((scheme, rest)) if !rest.chars().next().is_some_and(|c| c.is_ascii_digit()) => {
+                    return Err(CspOriginError::DisallowedScheme(scheme.to_ascii_lowercase()));
+                }
+                _ => (None, input),
+            },
+        };
+        if let Some(scheme) = &scheme {
+            if !ALLOWED_SCHEMES.contains(&scheme.as_str()) {
+                return Err(CspOriginError::DisallowedScheme(scheme.clone()));
+            }
+        }
+
+        let (authority, path) = match rest.find(['/', '?', '#']) {
+            Some(i) => rest.split_at(i),
+            None => (rest, ""),
+        };
+        if !path.is_empty() && path != "/" {
+            return Err(CspOriginError::UnexpectedPath(path.to_string()));
+        }
+
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse::<u16>()
+                    .ok()
+                    .filter(|p| *p != 0)
+                    .ok_or_else(|| CspOriginError::InvalidPort(port.to_string()))?;
+                (host, Some(port))
+            }
+            None => (authority, None),
+        };
+
+        let host = host.to_ascii_lowercase();
+        if host == "*" {
+            return Err(CspOriginError::Wildcard);
+        }
+        let name = host.strip_prefix("*.").unwrap_or(&host);
+        let valid_label = |label: &str| {
+            !label.is_empty()
+                && label.len() <= 63
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+        };
+        if name.is_empty() || !name.split('.').all(valid_label) {
+            return Err(CspOriginError::InvalidHost(host.clone()));
+        }
+        // `*.com` would cover a whole top-level domain
+        if host.starts_with("*.") && !name.contains('.') {
+            return Err(CspOriginError::Wildcard);
+        }
+
+        Ok(Self { scheme, host, port })
+    }
+
+    /// Whether the host is a `*.` subdomain wildcard
+    pub fn is_wildcard(&self) -> bool {
+        self.host.starts_with("*.")
+    }
+
+    /// Whether this source allows everything `other` allows
+    ///
+    /// A wildcard covers strict subdomains (`*.example.com` covers
+    /// `a.example.com` and `*.a.example.com`, not `example.com`). A source
+    /// without a scheme covers any scheme; one with a scheme only covers the
+    /// same scheme.
+    pub fn covers(&self, other: &CspOrigin) -> bool {
+        let scheme_ok = match (&self.scheme, &other.scheme) {
+            (None, _) => true,
+            (Some(a), Some(b)) => a == b,
+            (Some(_), None) => false,
+        };
+
+        let host_ok = match self.host.strip_prefix("*.") {
+            Some(suffix) => {
+                let other_name = other.host.strip_prefix("*.").unwrap_or(&other.host);
+                other_name.len() > suffix.len()
+                    && other_name.ends_with(suffix)
+                    && other_name[..other_name.len() - suffix.len()].ends_with('.')
+            }
+            None => !other.is_wildcard() && self.host == other.host,
+        };
+
+        scheme_ok && host_ok && self.port == other.port
+    }
+}
+
+impl fmt::Display for CspOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(scheme) = &self.scheme {
+            write!(f, "{}://", scheme)?;
+        }
+        write!(f, "{}", self.host)?;
+        if let Some(port) = self.port {
+            write!(f, ":{}", port)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check a host-approved domain list
+///
+/// `*` approves everything; invalid entries approve nothing.
+pub fn approved_covers(approved: &[String], origin: &CspOrigin) -> bool {
+    approved.iter().any(|entry| {
+        entry.trim() == "*" || CspOrigin::parse(entry).is_ok_and(|a| a.covers(origin))
+    })
+}
+
+impl ApprovedCsp {
+    /// Check a domain list against an origin string
+    fn allows(domains: &Option<Vec<String>>, origin: &str) -> bool {
+        match (domains, CspOrigin::parse(origin)) {
+            (Some(domains), Ok(origin)) => approved_covers(domains, &origin),
+            _ => false,
+        }
+    }
+
+    /// Check if an origin is approved for connections
+    pub fn allows_connection(&self, origin: &str) -> bool {
+        Self::allows(&self.connect_domains, origin)
+    }
+
+    /// Check if an origin is approved for resources
+    pub fn allows_resource(&self, origin: &str) -> bool {
+        Self::allows(&self.resource_domains, origin)
+    }
+}
+
+/// CSP domain lists a view can declare
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CspDirective {
+    /// `connectDomains` → `connect-src`
+    Connect,
+    /// `resourceDomains` → script/style/img/font/media sources
+    Resource,
+    /// `frameDomains` → `frame-src`
+    Frame,
+    /// `baseUriDomains` → `base-uri`
+    BaseUri,
+}
+
+/// Why a declared origin is missing from the effective policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", content = "error", rename_all = "camelCase")]
+pub enum CspDropReason {
+    /// The origin failed validation
+    Invalid(CspOriginError),
+    /// The host does not approve the origin for this directive
+    NotApproved,
+}
+
+/// A declared origin that was left out of the effective policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedCspOrigin {
+    /// Directive the origin was declared for
+    pub directive: CspDirective,
+    /// Origin as declared
+    pub origin: String,
+    /// Why it was dropped
+    #[serde(flatten)]
+    pub reason: CspDropReason,
+}
+
+/// Effective CSP for a view
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CspPolicy {
+    /// Origins for `connect-src`
+    pub connect: Vec<CspOrigin>,
+    /// Origins added to script/style/img/font/media sources
+    pub resource: Vec<CspOrigin>,
+    /// Origins for `frame-src`
+    pub frame: Vec<CspOrigin>,
+    /// Origins for `base-uri`
+    pub base_uri: Vec<CspOrigin>,
+    /// Declared origins that were not kept
+    pub dropped: Vec<DroppedCspOrigin>,
+}
+
+impl CspPolicy {
+    /// Build the policy from declared domains and the host's approved domains
+    ///
+    /// With no host approval only validation applies. With an approval, a
+    /// directive the host leaves unset approves nothing.
+    pub fn build(declared: &McpUiResourceCsp, approved: Option<&ApprovedCsp>) -> Self {
+        let mut policy = Self::default();
+        let directives = [
+            (CspDirective::Connect, &declared.connect_domains, approved.map(|a| &a.connect_domains)),
+            (CspDirective::Resource, &declared.resource_domains, approved.map(|a| &a.resource_domains)),
+            (CspDirective::Frame, &declared.frame_domains, approved.map(|a| &a.frame_domains)),
+            (CspDirective::BaseUri, &declared.base_uri_domains, approved.map(|a| &a.base_uri_domains)),
+        ];
+
+        for (directive, domains, approved) in directives {
+            for raw in domains.iter().flatten() {
+                let reason = match CspOrigin::parse(raw) {
+                    Err(e) => CspDropReason::Invalid(e),
+                    Ok(origin) => {
+                        let is_approved = match approved {
+                            None => true,
+                            Some(list) => list.as_deref().is_some_and(|list| approved_covers(list, &origin)),
+                        };
+                        if is_approved {
+                            let kept = policy.origins_mut(directive);
+                            if !kept.contains(&origin) {
+                                kept.push(origin);
+                            }
+                            continue;
+                        }
+                        CspDropReason::NotApproved
+                    }
+                };
+                policy.dropped.push(DroppedCspOrigin {
+                    directive,
+                    origin: raw.clone(),
+                    reason,
+                });
+            }
+        }
+
+        policy
+    }
+
+    fn origins_mut(&mut self, directive: CspDirective) -> &mut Vec<CspOrigin> {
+        match directive {
+            CspDirective::Connect => &mut self.connect,
+            CspDirective::Resource => &mut self.resource,
+            CspDirective::Frame => &mut self.frame,
+            CspDirective::BaseUri => &mut self.base_uri,
+        }
+    }
+
+    /// Kept origins as a resource CSP declaration
+    pub fn to_resource_csp(&self) -> McpUiResourceCsp {
+        let list = |origins: &[CspOrigin]| {
+            (!origins.is_empty()).then(|| origins.iter().map(|o| o.to_string()).collect())
+        };
+        McpUiResourceCsp {
+            connect_domains: list(&self.connect),
+            resource_domains: list(&self.resource),
+            frame_domains: list(&self.frame),
+            base_uri_domains: list(&self.base_uri),
+        }
+    }
+
+    /// Render the policy as a CSP header value
+    ///
+    /// Defaults follow the spec: nothing may connect or be framed unless
+    /// declared, and the base URI stays `'self'`.
+    pub fn to_header(&self) -> String {
+        let join = |origins: &[CspOrigin]| {
+            origins.iter().map(|o| o.to_string()).collect::<Vec<_>>().join(" ")
+        };
+        let with = |base: &str, origins: &[CspOrigin]| {
+            if origins.is_empty() {
+                base.to_string()
+            } else {
+                format!("{} {}", base, join(origins))
+            }
+        };
+        let or_default = |origins: &[CspOrigin], default: &str| {
+            if origins.is_empty() {
+                default.to_string()
+            } else {
+                join(origins)
+            }
+        };
+
+        [
+            "default-src 'none'".to_string(),
+            with("script-src 'self' 'unsafe-inline'", &self.resource),
+            with("style-src 'self' 'unsafe-inline'", &self.resource),
+            with("img-src 'self' data:", &self.resource),
+            with("font-src 'self'", &self.resource),
+            with("media-src 'self' data:", &self.resource),
+            format!("connect-src {}", or_default(&self.connect, "'none'")),
+            format!("frame-src {}", or_default(&self.frame, "'none'")),
+            format!("base-uri {}", or_default(&self.base_uri, "'self'")),
+            "object-src 'none'".to_string(),
+        ]
+        .join("; ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_parsing() {
+        let origin = CspOrigin::parse("https://API.example.com:8443").unwrap();
+        assert_eq!(origin.scheme.as_deref(), Some("https"));
+        assert_eq!(origin.host, "api.example.com");
+        assert_eq!(origin.port, Some(8443));
+        assert_eq!(origin.to_string(), "https://api.example.com:8443");
+
+        assert!(CspOrigin::parse("*.example.com").unwrap().is_wildcard());
+        assert_eq!(CspOrigin::parse("localhost:3000").unwrap().port, Some(3000));
+        assert!(CspOrigin::parse("https://example.com/").is_ok());
+
+        assert_eq!(CspOrigin::parse(""), Err(CspOriginError::Empty));
+        assert_eq!(CspOrigin::parse("*"), Err(CspOriginError::Wildcard));
+        assert_eq!(CspOrigin::parse("https://*"), Err(CspOriginError::Wildcard));
+        assert_eq!(CspOrigin::parse("*.com"), Err(CspOriginError::Wildcard));
+        assert_eq!(CspOrigin::parse("data:"), Err(CspOriginError::DisallowedScheme("data".into())));
+        assert_eq!(
+            CspOrigin::parse("javascript:alert(1)"),
+            Err(CspOriginError::DisallowedScheme("javascript".into()))
+        );
+        assert_eq!(
+            CspOrigin::parse("https://a.com 'unsafe-eval'"),
+            Err(CspOriginError::ForbiddenCharacter(' '))
+        );
+        assert_eq!(
+            CspOrigin::parse("https://a.com;script-src"),
+            Err(CspOriginError::ForbiddenCharacter(';'))
+        );
+        assert_eq!(CspOrigin::parse("'self'"), Err(CspOriginError::ForbiddenCharacter('\'')));
+        assert!(matches!(CspOrigin::parse("https://a.com/path"), Err(CspOriginError::UnexpectedPath(_))));
+        assert!(matches!(CspOrigin::parse("https://a.com:99999"), Err(CspOriginError::InvalidPort(_))));
+        assert!(matches!(CspOrigin::parse("https://a..com"), Err(CspOriginError::InvalidHost(_))));
+    }
+
+    #[test]
+    fn test_wildcard_subdomain_matching() {
+        let wildcard = CspOrigin::parse("https://*.example.com").unwrap();
+        assert!(wildcard.covers(&CspOrigin::parse("https://api.example.com").unwrap()));
+        assert!(wildcard.covers(&CspOrigin::parse("https://a.b.example.com").unwrap()));
+        assert!(wildcard.covers(&CspOrigin::parse("https://*.api.example.com").unwrap()));
+        assert!(!wildcard.covers(&CspOrigin::parse("https://example.com").unwrap()));
+        assert!(!wildcard.covers(&CspOrigin::parse("https://badexample.com").unwrap()));
+        assert!(!wildcard.covers(&CspOrigin::parse("http://api.example.com").unwrap()));
+
+        let approved = ApprovedCsp {
+            connect_domains: Some(vec!["*.example.com".to_string()]),
+            ..Default::default()
+        };
+        assert!(approved.allows_connection("https://sub.example.com"));
+        assert!(!approved.allows_connection("https://notexample.com"));
+        assert!(!approved.allows_connection("*"));
+    }
+
+    #[test]
+    fn test_policy_intersection_and_report() {
+        let declared = McpUiResourceCsp {
+            connect_domains: Some(vec![
+                "https://api.example.com".to_string(),
+                "https://evil.test".to_string(),
+                "*".to_string(),
+            ]),
+            resource_domains: Some(vec!["https://cdn.example.com".to_string(), "data:".to_string()]),
+            frame_domains: None,
+            base_uri_domains: None,
+        };
+        let approved = ApprovedCsp {
+            connect_domains: Some(vec!["https://*.example.com".to_string()]),
+            resource_domains: Some(vec!["*".to_string()]),
+            frame_domains: None,
+            base_uri_domains: None,
+        };
+
+        let policy = CspPolicy::build(&declared, Some(&approved));
+        assert_eq!(policy.connect, vec![CspOrigin::parse("https://api.example.com").unwrap()]);
+        assert_eq!(policy.resource, vec![CspOrigin::parse("https://cdn.example.com").unwrap()]);
+        assert_eq!(policy.dropped.len(), 3);
+        assert_eq!(policy.dropped[0].origin, "https://evil.test");
+        assert_eq!(policy.dropped[0].reason, CspDropReason::NotApproved);
+        assert_eq!(policy.dropped[1].reason, CspDropReason::Invalid(CspOriginError::Wildcard));
+        assert_eq!(policy.dropped[2].directive, CspDirective::Resource);
+
+        let header = policy.to_header();
+        assert!(header.contains("connect-src https://api.example.com;"));
+        assert!(header.contains("img-src 'self' data: https://cdn.example.com;"));
+        assert!(header.contains("frame-src 'none'"));
+        assert!(header.contains("base-uri 'self'"));
+        assert!(!header.contains("evil"));
+    }
+}
@@ -117,6 +117,38 @@ pub struct HostContext {
     pub safe_area_insets: Option<SafeAreaInsets>,
 }
 
+impl HostContext {
+    /// Compute the fields that differ in `updated`
+    ///
+    /// Returns the partial context for `ui/notifications/host-context-changed`,
+    /// or `None` if nothing changed. Fields no longer present are sent as `null`.
+    pub fn diff(&self, updated: &HostContext) -> Option<Value> {
+        let (Ok(Value::Object(old)), Ok(Value::Object(new))) =
+            (serde_json::to_value(self), serde_json::to_value(updated))
+        else {
+            return None;
+        };
+
+        let mut changes = serde_json::Map::new();
+        for (key, value) in &new {
+            if old.get(key) != Some(value) {
+                changes.insert(key.clone(), value.clone());
+            }
+        }
+        for key in old.keys() {
+            if !new.contains_key(key) {
+                changes.insert(key.clone(), Value::Null);
+            }
+        }
+
+        if changes.is_empty() {
+            None
+        } else {
+            Some(Value::Object(changes))
+        }
+    }
+}
+
 /// Tool info for host context
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -146,6 +178,19 @@ pub struct ContainerDimensions {
     pub max_width: Option<u32>,
 }
 
+impl ContainerDimensions {
+    /// Container size for content of the given size
+    ///
+    /// Fixed dimensions win; otherwise the content size is capped at the
+    /// maximum, if any.
+    pub fn fit(&self, width: u32, height: u32) -> (u32, u32) {
+        let fit = |size: u32, fixed: Option<u32>, max: Option<u32>| {
+            fixed.unwrap_or_else(|| max.map_or(size, |max| size.min(max)))
+        };
+        (fit(width, self.width, self.max_width), fit(height, self.height, self.max_height))
+    }
+}
+
 /// Platform types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -318,3 +363,92 @@ pub struct UiMessageRequest {
     /// Message content
     pub content: Value,
 }
+
+/// Log severity, as in MCP `notifications/message`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggingLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl LoggingLevel {
+    /// Level name as sent on the wire
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoggingLevel::Debug => "debug",
+            LoggingLevel::Info => "info",
+            LoggingLevel::Notice => "notice",
+            LoggingLevel::Warning => "warning",
+            LoggingLevel::Error => "error",
+            LoggingLevel::Critical => "critical",
+            LoggingLevel::Alert => "alert",
+            LoggingLevel::Emergency => "emergency",
+        }
+    }
+}
+
+/// Log message notification params (View → Host)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingMessageNotification {
+    /// Severity
+    pub level: LoggingLevel,
+    /// Name of the logger within the view
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logger: Option<String>,
+    /// Message, or any JSON value to log
+    pub data: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_host_context_diff() {
+        let old = HostContext {
+            theme: Some("light".to_string()),
+            display_mode: Some(DisplayMode::Inline),
+            locale: Some("en-US".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(old.diff(&old.clone()), None);
+
+        let new = HostContext {
+            theme: Some("dark".to_string()),
+            display_mode: Some(DisplayMode::Inline),
+            container_dimensions: Some(ContainerDimensions {
+                height: None,
+                max_height: Some(400),
+                width: None,
+                max_width: Some(500),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            old.diff(&new),
+            Some(json!({
+                "theme": "dark",
+                "containerDimensions": { "maxHeight": 400, "maxWidth": 500 },
+                "locale": null,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_container_dimensions_fit() {
+        let flexible = ContainerDimensions { height: None, max_height: Some(400), width: None, max_width: Some(500) };
+        assert_eq!(flexible.fit(300, 200), (300, 200));
+        assert_eq!(flexible.fit(800, 900), (500, 400));
+
+        let fixed = ContainerDimensions { height: Some(240), ..flexible };
+        assert_eq!(fixed.fit(300, 900), (300, 240));
+    }
+}
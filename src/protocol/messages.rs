@@ -214,6 +214,16 @@ pub fn success_response(id: Value) -> JsonRpcResponse {
     }
 }
 
+/// Build a response carrying a result
+pub fn result_response(id: Value, result: Value) -> JsonRpcResponse {
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id: Some(id),
+        result: Some(result),
+        error: None,
+    }
+}
+
 /// Build an error response
 pub fn error_response(id: Value, code: i32, message: impl Into<String>) -> JsonRpcResponse {
     JsonRpcResponse {
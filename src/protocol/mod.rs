@@ -5,11 +5,13 @@
 //! bidirectional communication between hosts and views.
 
 pub mod capabilities;
+pub mod csp;
 pub mod lifecycle;
 pub mod resources;
 pub mod messages;
 
 pub use capabilities::*;
+pub use csp::*;
 pub use lifecycle::*;
 pub use resources::*;
 pub use messages::*;
@@ -120,4 +122,6 @@ pub mod error_codes {
     pub const INTERNAL_ERROR: i32 = -32603;
     /// Server error (implementation-defined)
     pub const SERVER_ERROR: i32 = -32000;
+    /// The host's policy or the user refused the request
+    pub const REQUEST_DENIED: i32 = -32001;
 }
@@ -5,6 +5,9 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::capabilities::ApprovedCsp;
+use super::csp::CspPolicy;
+
 /// UI Resource type as defined in the spec
 /// 
 /// URI MUST start with `ui://` scheme
@@ -110,7 +113,7 @@ pub struct UiResourceContent {
 }
 
 /// Tool metadata linking to UI resources
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct McpUiToolMeta {
     /// URI of UI resource for rendering tool results
@@ -170,44 +173,70 @@ impl UiResource {
     }
 }
 
+impl McpUiToolMeta {
+    /// Legacy flat key used before `resourceUri` moved under `_meta.ui`
+    pub const LEGACY_RESOURCE_URI_KEY: &'static str = "ui/resourceUri";
+
+    /// Parse UI metadata from a tool's `_meta` map
+    ///
+    /// Reads the nested `ui` object and falls back to the deprecated flat
+    /// `ui/resourceUri` key. Unknown visibility values are ignored rather than
+    /// failing the whole parse.
+    pub fn from_meta(meta: &serde_json::Map<String, Value>) -> Self {
+        let ui = meta.get("ui").and_then(|u| u.as_object());
+
+        let resource_uri = ui
+            .and_then(|u| u.get("resourceUri"))
+            .and_then(|v| v.as_str())
+            .or_else(|| meta.get(Self::LEGACY_RESOURCE_URI_KEY).and_then(|v| v.as_str()))
+            .map(|s| s.to_string());
+
+        let visibility = ui
+            .and_then(|u| u.get("visibility"))
+            .and_then(|v| v.as_array())
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|v| serde_json::from_value::<ToolVisibility>(v.clone()).ok())
+                    .collect()
+            });
+
+        Self { resource_uri, visibility }
+    }
+
+    /// Check if the tool is visible to the given audience
+    ///
+    /// Tools without an explicit visibility list default to `["model", "app"]`.
+    pub fn is_visible_to(&self, audience: ToolVisibility) -> bool {
+        self.visibility
+            .as_ref()
+            .map(|v| v.contains(&audience))
+            .unwrap_or(true)
+    }
+
+    /// Check if the tool should be exposed to the agent
+    pub fn is_model_visible(&self) -> bool {
+        self.is_visible_to(ToolVisibility::Model)
+    }
+
+    /// Check if the tool may be called by views from the same server
+    pub fn is_app_visible(&self) -> bool {
+        self.is_visible_to(ToolVisibility::App)
+    }
+}
+
 impl McpUiResourceCsp {
     /// Build a CSP header string from the configuration
+    ///
+    /// Only validation applies here; use [`Self::effective_policy`] to also
+    /// intersect with the domains the host approves.
     pub fn build_csp_header(&self) -> String {
-        let mut parts = Vec::new();
-        
-        // Default restrictive policy
-        parts.push("default-src 'none'".to_string());
-        parts.push("script-src 'self' 'unsafe-inline'".to_string());
-        parts.push("style-src 'self' 'unsafe-inline'".to_string());
-        parts.push("img-src 'self' data:".to_string());
-        parts.push("media-src 'self' data:".to_string());
-        
-        // Connect-src
-        if let Some(domains) = &self.connect_domains {
-            if domains.is_empty() {
-                parts.push("connect-src 'none'".to_string());
-            } else {
-                parts.push(format!("connect-src {}", domains.join(" ")));
-            }
-        } else {
-            parts.push("connect-src 'none'".to_string());
-        }
-        
-        // Frame-src
-        if let Some(domains) = &self.frame_domains {
-            if domains.is_empty() {
-                parts.push("frame-src 'none'".to_string());
-            } else {
-                parts.push(format!("frame-src {}", domains.join(" ")));
-            }
-        } else {
-            parts.push("frame-src 'none'".to_string());
-        }
-        
-        // Object-src (always block)
-        parts.push("object-src 'none'".to_string());
-        
-        parts.join("; ")
+        CspPolicy::build(self, None).to_header()
+    }
+    
+    /// Effective policy after validation and intersection with the host's approved domains
+    pub fn effective_policy(&self, approved: Option<&ApprovedCsp>) -> CspPolicy {
+        CspPolicy::build(self, approved)
     }
 }
 
@@ -223,6 +252,38 @@ mod tests {
         assert!(!UiResource::is_valid_uri("https://example.com"));
     }
     
+    #[test]
+    fn test_tool_meta_parsing() {
+        let meta = serde_json::json!({
+            "ui": {
+                "resourceUri": "ui://weather/dashboard",
+                "visibility": ["app"]
+            }
+        });
+        let parsed = McpUiToolMeta::from_meta(meta.as_object().unwrap());
+
+        assert_eq!(parsed.resource_uri.as_deref(), Some("ui://weather/dashboard"));
+        assert!(parsed.is_app_visible());
+        assert!(!parsed.is_model_visible());
+    }
+
+    #[test]
+    fn test_tool_meta_legacy_key_and_defaults() {
+        let meta = serde_json::json!({ "ui/resourceUri": "ui://legacy/view" });
+        let parsed = McpUiToolMeta::from_meta(meta.as_object().unwrap());
+
+        assert_eq!(parsed.resource_uri.as_deref(), Some("ui://legacy/view"));
+        assert!(parsed.is_model_visible());
+        assert!(parsed.is_app_visible());
+
+        // Unknown visibility entries are dropped, known ones still apply
+        let meta = serde_json::json!({ "ui": { "visibility": ["model", "bogus"] } });
+        let parsed = McpUiToolMeta::from_meta(meta.as_object().unwrap());
+        assert!(parsed.resource_uri.is_none());
+        assert!(parsed.is_model_visible());
+        assert!(!parsed.is_app_visible());
+    }
+
     #[test]
     fn test_csp_header_building() {
         let csp = McpUiResourceCsp {
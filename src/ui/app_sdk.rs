@@ -0,0 +1,615 @@
+//! MCP Apps JavaScript SDK
+//!
+//! The App SDK the host injects into every view. It exposes the standard
+//! `App` API of the MCP Apps SDK as `window.McpApps.App` and speaks only the
+//! methods defined by the spec. The SDK stays inert until the view calls
+//! `connect()`, so views that bundle their own copy of the official SDK are
+//! not disturbed.
+//!
+//! Views written against the host's earlier `window.mcp` bridge can still be
+//! served with the legacy shim, which maps the old API onto an auto-connected
+//! `App`.
+
+use crate::protocol::PROTOCOL_VERSION;
+
+/// Version reported by the injected SDK
+pub const APP_SDK_VERSION: &str = "2.0.0";
+
+/// Environment variable that enables the legacy `window.mcp` API (any value but `0`)
+pub const LEGACY_BRIDGE_ENV: &str = "MCP_LEGACY_BRIDGE";
+
+/// Whether the environment asks for the legacy `window.mcp` API
+pub fn legacy_bridge_from_env() -> bool {
+    std::env::var_os(LEGACY_BRIDGE_ENV).is_some_and(|value| value != "0")
+}
+
+/// Standard App API (`window.McpApps`)
+const APP_SDK_JS: &str = r#"
+(function() {
+    'use strict';
+
+    const SDK_VERSION = '__SDK_VERSION__';
+    const PROTOCOL_VERSION = '__PROTOCOL_VERSION__';
+    // Views run in an opaque or per-app origin; the host checks the source window
+    const parentOrigin = '*';
+
+    const METHOD_NOT_FOUND = -32601;
+    const INVALID_PARAMS = -32602;
+    const INTERNAL_ERROR = -32603;
+
+    function post(message) {
+        window.parent.postMessage(message, parentOrigin);
+    }
+
+    // Apply the host's theme variables and fonts (same rules as the initial <style>)
+    function applyHostStyles(context) {
+        if (!context) return;
+        const root = document.documentElement;
+        if (context.theme) {
+            root.setAttribute('data-theme', context.theme);
+            root.style.colorScheme = context.theme === 'dark' ? 'dark' : 'light';
+        }
+        const styles = context.styles;
+        if (!styles) return;
+
+        let css = '';
+        if (styles.css && typeof styles.css.fonts === 'string') {
+            css += styles.css.fonts + '\n';
+        }
+        const variables = styles.variables || {};
+        const rules = Object.keys(variables).filter(function(name) {
+            const value = variables[name];
+            return /^--[A-Za-z0-9_-]+$/.test(name) && typeof value === 'string' && !/[;{}<>]/.test(value);
+        }).map(function(name) {
+            return '    ' + name + ': ' + variables[name] + ';';
+        });
+        css += ':root {\n' + rules.join('\n') + '\n}\n';
+
+        let tag = document.getElementById('mcp-host-styles');
+        if (!tag) {
+            tag = document.createElement('style');
+            tag.id = 'mcp-host-styles';
+            (document.head || root).appendChild(tag);
+        }
+        tag.textContent = css;
+    }
+
+    // Size of the content rather than of the viewport it currently fills
+    function measureSize() {
+        const root = document.documentElement;
+        const height = root.style.height;
+        root.style.height = 'max-content';
+        const size = {
+            width: Math.ceil(window.innerWidth),
+            height: Math.ceil(root.getBoundingClientRect().height)
+        };
+        root.style.height = height;
+        return size;
+    }
+
+    // Tool handler return value as a CallToolResult
+    function toolResult(value) {
+        if (value && typeof value === 'object' && Array.isArray(value.content)) return value;
+        const text = typeof value === 'string' ? value : JSON.stringify(value === undefined ? null : value);
+        const result = { content: [{ type: 'text', text: text }] };
+        if (value && typeof value === 'object' && !Array.isArray(value)) result.structuredContent = value;
+        return result;
+    }
+
+    class App {
+        // appInfo: { name, version }; capabilities: McpUiAppCapabilities;
+        // options: { autoResize, applyHostStyles }
+        constructor(appInfo, capabilities, options) {
+            this.appInfo = appInfo || { name: document.title || 'mcp-app', version: SDK_VERSION };
+            this.capabilities = capabilities || {};
+            this.options = Object.assign({ autoResize: true, applyHostStyles: true }, options);
+
+            // Notification handlers
+            this.ontoolinput = null;
+            this.ontoolinputpartial = null;
+            this.ontoolresult = null;
+            this.ontoolcancelled = null;
+            this.onhostcontextchanged = null;
+            // Request handler; may return a promise, resolved before the view is removed
+            this.onteardown = null;
+
+            this._hostInfo = null;
+            this._hostCapabilities = null;
+            this._hostContext = window.mcpHostContext || null;
+            this._pending = new Map();
+            this._nextId = 1;
+            this._connecting = null;
+            // Tools the host may call, by name: { config, handler }
+            this._tools = new Map();
+        }
+
+        // Perform the ui/initialize handshake, then confirm with ui/notifications/initialized
+        connect() {
+            if (this._connecting) return this._connecting;
+            window.addEventListener('message', (event) => this._receive(event));
+            this._connecting = this.request('ui/initialize', {
+                protocolVersion: PROTOCOL_VERSION,
+                appInfo: this.appInfo,
+                appCapabilities: this.capabilities
+            }).then((result) => {
+                this._hostInfo = result.hostInfo || null;
+                this._hostCapabilities = result.hostCapabilities || null;
+                this._hostContext = result.hostContext || {};
+                if (this.options.applyHostStyles) applyHostStyles(this._hostContext);
+                this.notify('ui/notifications/initialized', {});
+                if (this.options.autoResize) this.setupSizeChangedNotifications();
+                return result;
+            });
+            return this._connecting;
+        }
+
+        getHostVersion() {
+            return this._hostInfo;
+        }
+
+        getHostCapabilities() {
+            return this._hostCapabilities;
+        }
+
+        getHostContext() {
+            return this._hostContext;
+        }
+
+        // Call a tool on the view's MCP server
+        callServerTool(params, timeoutMs) {
+            return this.request('tools/call', params, timeoutMs);
+        }
+
+        // Read a resource from the view's MCP server
+        readServerResource(params, timeoutMs) {
+            return this.request('resources/read', params, timeoutMs);
+        }
+
+        // Add a message to the conversation
+        sendMessage(params, timeoutMs) {
+            return this.request('ui/message', params, timeoutMs);
+        }
+
+        // Log to the host ({ level, logger, data })
+        sendLog(params) {
+            this.notify('notifications/message', params);
+        }
+
+        // Open a link (the host checks its policy and may ask the user first)
+        openLink(params, timeoutMs) {
+            return this.request('ui/open-link', params, timeoutMs || 120000);
+        }
+
+        // Replace the context the model sees for this view
+        updateModelContext(params, timeoutMs) {
+            return this.request('ui/update-model-context', params, timeoutMs);
+        }
+
+        // Ask for a display mode; resolves with the mode in effect
+        requestDisplayMode(params, timeoutMs) {
+            return this.request('ui/request-display-mode', params, timeoutMs);
+        }
+
+        // Report the content size so the host can fit the container to it
+        sendSizeChanged(params) {
+            this.notify('ui/notifications/size-changed', params);
+        }
+
+        // Report size changes as the content resizes; returns a function that stops reporting
+        setupSizeChangedNotifications() {
+            if (typeof ResizeObserver === 'undefined') return function() {};
+            let last = null;
+            let frame = 0;
+            const report = () => {
+                frame = 0;
+                const size = measureSize();
+                if (last && last.width === size.width && last.height === size.height) return;
+                last = size;
+                this.sendSizeChanged(size);
+            };
+            const observer = new ResizeObserver(function() {
+                if (!frame) frame = requestAnimationFrame(report);
+            });
+            observer.observe(document.documentElement);
+            if (document.body) observer.observe(document.body);
+            report();
+            return function() {
+                observer.disconnect();
+            };
+        }
+
+        // Expose a tool the host may call ({ description, inputSchema });
+        // register before connect() so the host discovers it during the handshake
+        registerTool(name, config, handler) {
+            this._tools.set(name, { config: config || {}, handler: handler });
+            if (!this.capabilities.tools) this.capabilities.tools = { listChanged: true };
+            this._toolsChanged();
+        }
+
+        unregisterTool(name) {
+            if (this._tools.delete(name)) this._toolsChanged();
+        }
+
+        _toolsChanged() {
+            if (this._hostInfo) this.notify('notifications/tools/list_changed', {});
+        }
+
+        // Send a request to the host and resolve with its result
+        request(method, params, timeoutMs) {
+            return new Promise((resolve, reject) => {
+                const id = this._nextId++;
+                const timer = setTimeout(() => {
+                    if (this._pending.delete(id)) reject(new Error(method + ' timed out'));
+                }, timeoutMs || 30000);
+                this._pending.set(id, { resolve: resolve, reject: reject, timer: timer });
+                post({ jsonrpc: '2.0', id: id, method: method, params: params || {} });
+            });
+        }
+
+        // Send a notification to the host
+        notify(method, params) {
+            post({ jsonrpc: '2.0', method: method, params: params || {} });
+        }
+
+        _receive(event) {
+            // Only the embedding host may talk to the view
+            if (event.source !== window.parent) return;
+            const data = event.data;
+            if (!data || typeof data !== 'object' || data.jsonrpc !== '2.0') return;
+
+            if (data.method === undefined) {
+                const pending = this._pending.get(data.id);
+                if (!pending) return;
+                this._pending.delete(data.id);
+                clearTimeout(pending.timer);
+                if (data.error) {
+                    const error = new Error(data.error.message || 'Unknown error');
+                    error.code = data.error.code;
+                    error.data = data.error.data;
+                    pending.reject(error);
+                } else {
+                    pending.resolve(data.result);
+                }
+                return;
+            }
+
+            if (data.id !== undefined) {
+                this._handleRequest(data);
+            } else {
+                this._handleNotification(data.method, data.params || {});
+            }
+        }
+
+        _handleRequest(data) {
+            const respond = function(result) {
+                post({ jsonrpc: '2.0', id: data.id, result: result || {} });
+            };
+            const fail = function(code, message) {
+                post({ jsonrpc: '2.0', id: data.id, error: { code: code, message: message } });
+            };
+
+            switch (data.method) {
+                case 'ui/resource-teardown': {
+                    // Give the app a chance to persist state before it is removed
+                    const handler = this.onteardown;
+                    Promise.resolve()
+                        .then(function() { return handler ? handler(data.params || {}) : {}; })
+                        .then(respond, function(err) { fail(INTERNAL_ERROR, String(err && err.message || err)); });
+                    break;
+                }
+                case 'ping':
+                    respond({});
+                    break;
+                case 'tools/list': {
+                    const tools = [];
+                    this._tools.forEach(function(tool, name) {
+                        tools.push({
+                            name: name,
+                            description: tool.config.description,
+                            inputSchema: tool.config.inputSchema || { type: 'object' }
+                        });
+                    });
+                    respond({ tools: tools });
+                    break;
+                }
+                case 'tools/call': {
+                    const params = data.params || {};
+                    const tool = this._tools.get(params.name);
+                    if (!tool) {
+                        fail(INVALID_PARAMS, 'Unknown tool: ' + params.name);
+                        break;
+                    }
+                    // Errors thrown by the tool are reported as a tool result
+                    Promise.resolve()
+                        .then(function() { return tool.handler(params.arguments || {}); })
+                        .then(function(value) { respond(toolResult(value)); }, function(err) {
+                            respond({ content: [{ type: 'text', text: String(err && err.message || err) }], isError: true });
+                        });
+                    break;
+                }
+                default:
+                    fail(METHOD_NOT_FOUND, 'Method not found: ' + data.method);
+            }
+        }
+
+        _handleNotification(method, params) {
+            const call = function(handler) {
+                if (typeof handler === 'function') handler(params);
+            };
+
+            switch (method) {
+                case 'ui/notifications/tool-input':
+                    call(this.ontoolinput);
+                    break;
+                case 'ui/notifications/tool-input-partial':
+                    call(this.ontoolinputpartial);
+                    break;
+                case 'ui/notifications/tool-result':
+                    call(this.ontoolresult);
+                    break;
+                case 'ui/notifications/tool-cancelled':
+                    call(this.ontoolcancelled);
+                    break;
+                case 'ui/notifications/host-context-changed':
+                    // Only changed fields are sent; merge them into the known context
+                    this._hostContext = Object.assign({}, this._hostContext, params);
+                    if (this.options.applyHostStyles && (params.styles || params.theme)) {
+                        applyHostStyles(this._hostContext);
+                    }
+                    call(this.onhostcontextchanged);
+                    break;
+            }
+        }
+    }
+
+    window.McpApps = {
+        App: App,
+        SDK_VERSION: SDK_VERSION,
+        PROTOCOL_VERSION: PROTOCOL_VERSION
+    };
+})();
+"#;
+
+/// Legacy `window.mcp` API on top of an auto-connected `App`
+///
+/// Views may preset `window.mcpAppCapabilities` and opt out of auto-resize
+/// with `window.mcpAutoResize = false`.
+const LEGACY_API_JS: &str = r#"
+(function() {
+    'use strict';
+
+    const app = new window.McpApps.App(
+        { name: document.title || 'mcp-app', version: window.McpApps.SDK_VERSION },
+        window.mcpAppCapabilities || { availableDisplayModes: ['inline', 'fullscreen', 'pip'] },
+        { autoResize: window.mcpAutoResize !== false }
+    );
+
+    const emit = function(name, detail) {
+        document.dispatchEvent(new CustomEvent(name, { detail: detail }));
+    };
+    const listen = function(name) {
+        return function(callback) {
+            document.addEventListener(name, function(e) {
+                callback(e.detail);
+            });
+        };
+    };
+
+    // Callbacks run before the host removes the view
+    const teardownHandlers = [];
+
+    app.ontoolinput = function(params) { emit('mcp:toolInput', params); };
+    app.ontoolinputpartial = function(params) { emit('mcp:toolInputPartial', params); };
+    app.ontoolresult = function(params) { emit('mcp:toolResult', params); };
+    app.ontoolcancelled = function(params) { emit('mcp:toolCancelled', params); };
+    app.onhostcontextchanged = function(changes) {
+        window.mcpHostContext = app.getHostContext();
+        emit('mcp:hostContextChanged', changes);
+        emit('mcp:context', window.mcpHostContext);
+        if (changes.displayMode) emit('mcp:displayModeChanged', { mode: changes.displayMode });
+    };
+    app.onteardown = function(params) {
+        return Promise.allSettled(teardownHandlers.map(function(handler) {
+            return Promise.resolve().then(function() { return handler(params); });
+        })).then(function() { return {}; });
+    };
+
+    window.mcpApp = app;
+    window.mcp = {
+        version: window.McpApps.SDK_VERSION,
+
+        callTool: function(name, args) {
+            return app.callServerTool({ name: name, arguments: args || {} });
+        },
+        updateContext: function(content, structuredContent) {
+            return app.updateModelContext({ content: content, structuredContent: structuredContent });
+        },
+        requestDisplayMode: function(mode) {
+            return app.requestDisplayMode({ mode: mode });
+        },
+        requestExpanded: function() {
+            return this.requestDisplayMode('fullscreen');
+        },
+        requestInline: function() {
+            return this.requestDisplayMode('inline');
+        },
+        log: function(level, message, logger) {
+            app.sendLog({ level: level, logger: logger || 'mcp-app', data: message });
+        },
+        openLink: function(url) {
+            return app.openLink({ url: url });
+        },
+        getContext: function() {
+            return window.mcpHostContext || null;
+        },
+        onContext: listen('mcp:context'),
+        onHostContextChanged: listen('mcp:hostContextChanged'),
+        onToolInput: listen('mcp:toolInput'),
+        onToolResult: listen('mcp:toolResult'),
+        onTeardown: function(callback) {
+            teardownHandlers.push(callback);
+        },
+        onDisplayModeChanged: listen('mcp:displayModeChanged')
+    };
+
+    function start() {
+        app.connect().then(function(result) {
+            window.mcpHostInfo = result.hostInfo;
+            window.mcpHostCapabilities = result.hostCapabilities;
+            window.mcpHostContext = app.getHostContext();
+            emit('mcp:context', window.mcpHostContext);
+            emit('mcp:initialized', result);
+        }).catch(function(err) {
+            console.error('MCP ui/initialize failed:', err);
+        });
+    }
+
+    if (document.readyState === 'loading') {
+        document.addEventListener('DOMContentLoaded', start);
+    } else {
+        start();
+    }
+})();
+"#;
+
+/// The App SDK as a `<script>` tag for a view's head
+///
+/// With `legacy` set, the earlier `window.mcp` API is also installed and the
+/// view is connected automatically.
+pub fn app_sdk_script(legacy: bool) -> String {
+    let sdk = APP_SDK_JS
+        .replace("__SDK_VERSION__", APP_SDK_VERSION)
+        .replace("__PROTOCOL_VERSION__", PROTOCOL_VERSION);
+    if legacy {
+        format!("<script>{}{}</script>", sdk, LEGACY_API_JS)
+    } else {
+        format!("<script>{}</script>", sdk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Methods a view may send under the spec
+    const VIEW_METHODS: &[&str] = &[
+        "ui/initialize",
+        "ui/notifications/initialized",
+        "tools/call",
+        "resources/read",
+        "notifications/message",
+        "ui/message",
+        "ui/open-link",
+        "ui/update-model-context",
+        "ui/request-display-mode",
+        "ui/notifications/size-changed",
+        "notifications/tools/list_changed",
+    ];
+
+    /// Methods a host may send to a view under the spec
+    const HOST_METHODS: &[&str] = &[
+        "ui/notifications/tool-input",
+        "ui/notifications/tool-input-partial",
+        "ui/notifications/tool-result",
+        "ui/notifications/tool-cancelled",
+        "ui/notifications/host-context-changed",
+        "ui/resource-teardown",
+        "ping",
+        "tools/list",
+        "tools/call",
+    ];
+
+    /// Method names of the host's earlier bridge
+    const LEGACY_METHODS: &[&str] = &[
+        "context/update",
+        "logging/message",
+        "display/mode",
+        "link/open",
+        "ui/ready",
+        "host/context",
+        "tool/result",
+    ];
+
+    /// First quoted argument of every `call(` in `js`
+    fn quoted_args<'a>(js: &'a str, call: &str) -> Vec<&'a str> {
+        js.match_indices(call)
+            .filter_map(|(at, _)| {
+                let rest = js[at + call.len()..].strip_prefix('\'')?;
+                rest.split_once('\'').map(|(method, _)| method)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sdk_speaks_spec_methods() {
+        let script = app_sdk_script(true);
+
+        let sent: Vec<&str> = quoted_args(&script, "this.request(")
+            .into_iter()
+            .chain(quoted_args(&script, "this.notify("))
+            .collect();
+        assert!(sent.len() >= VIEW_METHODS.len());
+        for method in &sent {
+            assert!(VIEW_METHODS.contains(method), "{} is not a spec method", method);
+        }
+        for method in VIEW_METHODS {
+            assert!(sent.contains(method), "{} is never sent", method);
+        }
+
+        let handled = quoted_args(&script, "case ");
+        for method in HOST_METHODS {
+            assert!(handled.contains(method), "{} is not handled", method);
+        }
+        for method in LEGACY_METHODS {
+            assert!(!script.contains(&format!("'{}'", method)), "{} is still used", method);
+        }
+
+        // The legacy shim goes through the App rather than posting messages itself
+        assert!(!LEGACY_API_JS.contains("postMessage"));
+    }
+
+    #[test]
+    fn test_app_api() {
+        let script = app_sdk_script(false);
+        for method in [
+            "connect",
+            "getHostVersion",
+            "getHostCapabilities",
+            "getHostContext",
+            "callServerTool",
+            "readServerResource",
+            "sendMessage",
+            "sendLog",
+            "openLink",
+            "updateModelContext",
+            "requestDisplayMode",
+            "sendSizeChanged",
+            "setupSizeChangedNotifications",
+            "registerTool",
+            "unregisterTool",
+        ] {
+            assert!(script.contains(&format!("        {}(", method)), "App.{} is missing", method);
+        }
+        for handler in ["ontoolinput", "ontoolinputpartial", "ontoolresult", "ontoolcancelled", "onhostcontextchanged", "onteardown"] {
+            assert!(script.contains(&format!("this.{} = null;", handler)), "App.{} is missing", handler);
+        }
+        assert!(script.contains(&format!("const PROTOCOL_VERSION = '{}';", PROTOCOL_VERSION)));
+        assert!(script.starts_with("<script>") && script.ends_with("</script>"));
+        assert_eq!(script.matches("</script>").count(), 1);
+    }
+
+    #[test]
+    fn test_legacy_api_is_opt_in() {
+        let spec = app_sdk_script(false);
+        assert!(!spec.contains("window.mcp ="));
+        // Nothing connects on its own, so a bundled SDK can initialize instead
+        assert!(!spec.contains(".connect()"));
+
+        let legacy = app_sdk_script(true);
+        assert!(legacy.contains("window.mcp = {"));
+        assert!(legacy.contains("app.connect()"));
+        for method in ["callTool", "updateContext", "requestDisplayMode", "log", "openLink", "onToolResult", "onTeardown"] {
+            assert!(legacy.contains(&format!("        {}: ", method)), "window.mcp.{} is missing", method);
+        }
+    }
+}
@@ -3,11 +3,17 @@
 //! Handles bidirectional communication between the host and UI views
 //! using JSON-RPC over postMessage (for WebView) or direct channels.
 
-use crate::protocol::{JsonRpcRequest, JsonRpcResponse, JsonRpcNotification, JsonRpcError, error_codes, Message};
+use crate::host::{AppOrigins, AuditLog, ConnectionManager, Conversation, HostState, LinkBroker, ModelContextStore, UiSession, ViewLogs, ViewToolTransport, ViewTools};
+use crate::protocol::{JsonRpcRequest, JsonRpcResponse, JsonRpcNotification, JsonRpcError, error_codes, Message, ServerCapabilities};
+use crate::ui::ViewSession;
 use serde_json::Value;
 use std::collections::HashMap;
 use tokio::sync::{mpsc, RwLock};
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Default time to wait for a view to answer a request
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Bridge for communicating with a UI view
 #[derive(Clone)]
@@ -64,6 +70,16 @@ impl UiBridge {
     
     /// Send a request to the view and wait for response
     pub async fn send_request(&self, method: impl Into<String>, params: Option<Value>) -> Result<Value, JsonRpcError> {
+        self.send_request_with_timeout(method, params, DEFAULT_REQUEST_TIMEOUT).await
+    }
+    
+    /// Send a request to the view and wait up to `timeout` for the response
+    pub async fn send_request_with_timeout(
+        &self,
+        method: impl Into<String>,
+        params: Option<Value>,
+        timeout: Duration,
+    ) -> Result<Value, JsonRpcError> {
         let id = {
             let mut next_id = self.next_id.write().await;
             let id = *next_id;
@@ -92,11 +108,15 @@ impl UiBridge {
         })?;
         
         // Wait for response
-        match tokio::time::timeout(tokio::time::Duration::from_secs(30), rx.recv()).await {
-            Ok(Some(result)) => result,
+        let result = match tokio::time::timeout(timeout, rx.recv()).await {
+            Ok(Some(result)) => return result,
             Ok(None) => Err(JsonRpcError::new(error_codes::INTERNAL_ERROR, "Channel closed")),
             Err(_) => Err(JsonRpcError::new(error_codes::INTERNAL_ERROR, "Request timeout")),
-        }
+        };
+        
+        // Drop the pending entry so a late response is ignored
+        self.pending_requests.write().await.remove(&id);
+        result
     }
     
     /// Send a notification to the view
@@ -111,6 +131,12 @@ impl UiBridge {
         self.outgoing_tx.send(value).map_err(|_| "Failed to send notification".to_string())
     }
     
+    /// Send a response to a request received from the view
+    pub fn send_response(&self, response: JsonRpcResponse) -> Result<(), String> {
+        let value = serde_json::to_value(&response).map_err(|e| e.to_string())?;
+        self.outgoing_tx.send(value).map_err(|_| "Failed to send response".to_string())
+    }
+
     /// Register a request handler
     pub async fn on_request<F>(&self, method: impl Into<String>, handler: F)
     where
@@ -218,16 +244,150 @@ impl UiBridge {
     }
 }
 
+#[async_trait::async_trait]
+impl ViewToolTransport for UiBridge {
+    async fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, JsonRpcError> {
+        self.send_request_with_timeout(method, Some(params), timeout).await
+    }
+}
+
 /// Bridge manager for multiple UI sessions
 #[derive(Debug, Clone)]
 pub struct BridgeManager {
     bridges: Arc<RwLock<HashMap<String, UiBridge>>>,
+    sessions: Arc<RwLock<HashMap<String, ViewSession>>>,
+    audit: Option<AuditLog>,
+    links: Option<LinkBroker>,
+    model_context: Option<ModelContextStore>,
+    conversation: Option<Conversation>,
+    logs: Option<ViewLogs>,
+    view_tools: Option<ViewTools>,
+    servers: Option<ConnectionManager>,
+    origins: Option<AppOrigins>,
 }
 
 impl BridgeManager {
     pub fn new() -> Self {
         Self {
             bridges: Arc::new(RwLock::new(HashMap::new())),
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            audit: None,
+            links: None,
+            model_context: None,
+            conversation: None,
+            logs: None,
+            view_tools: None,
+            servers: None,
+            origins: None,
+        }
+    }
+    
+    /// Builder method: Record every view session to an audit log
+    pub fn with_audit_log(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+    
+    /// Builder method: Let views open links through a link broker
+    pub fn with_link_broker(mut self, links: LinkBroker) -> Self {
+        self.links = Some(links);
+        self
+    }
+    
+    /// Builder method: Keep the context views provide for the model
+    pub fn with_model_context(mut self, model_context: ModelContextStore) -> Self {
+        self.model_context = Some(model_context);
+        self
+    }
+    
+    /// Builder method: Let views post messages to the host conversation
+    pub fn with_conversation(mut self, conversation: Conversation) -> Self {
+        self.conversation = Some(conversation);
+        self
+    }
+    
+    /// Builder method: Route view log messages to view logs
+    pub fn with_view_logs(mut self, logs: ViewLogs) -> Self {
+        self.logs = Some(logs);
+        self
+    }
+    
+    /// Builder method: Discover the tools views expose
+    pub fn with_view_tools(mut self, view_tools: ViewTools) -> Self {
+        self.view_tools = Some(view_tools);
+        self
+    }
+    
+    /// Builder method: Answer views' `tools/call` and `resources/read` from their servers
+    pub fn with_connection_manager(mut self, servers: ConnectionManager) -> Self {
+        self.servers = Some(servers);
+        self
+    }
+    
+    /// Builder method: Release the origins views hold when they are removed
+    pub fn with_app_origins(mut self, origins: AppOrigins) -> Self {
+        self.origins = Some(origins);
+        self
+    }
+    
+    /// Audit log shared by the view sessions, if any
+    pub fn audit_log(&self) -> Option<&AuditLog> {
+        self.audit.as_ref()
+    }
+    
+    /// Create a view session and register its bridge
+    pub async fn create_session(
+        &self,
+        session: UiSession,
+        host_state: HostState,
+        server_capabilities: Option<ServerCapabilities>,
+    ) -> ViewSession {
+        let view = ViewSession::new(session, host_state, server_capabilities)
+            .with_audit_log(self.audit.clone())
+            .with_link_broker(self.links.clone())
+            .with_model_context(self.model_context.clone())
+            .with_conversation(self.conversation.clone())
+            .with_view_logs(self.logs.clone())
+            .with_view_tools(self.view_tools.clone())
+            .with_connection_manager(self.servers.clone());
+        let session_id = view.session_id().to_string();
+        
+        self.bridges.write().await.insert(session_id.clone(), view.bridge().clone());
+        self.sessions.write().await.insert(session_id, view.clone());
+        
+        view
+    }
+    
+    /// Get a view session by session ID
+    pub async fn get_session(&self, session_id: &str) -> Option<ViewSession> {
+        self.sessions.read().await.get(session_id).cloned()
+    }
+    
+    /// Feed a chunk of tool arguments streamed by the agent to a view
+    ///
+    /// The view gets throttled `ui/notifications/tool-input-partial` previews.
+    pub async fn push_tool_input_chunk(&self, session_id: &str, chunk: &str) -> Result<(), String> {
+        let view = self.get_session(session_id).await.ok_or_else(|| format!("No view session {}", session_id))?;
+        view.push_tool_input_chunk(chunk).await
+    }
+
+    /// Finish the argument stream of a view and deliver the complete tool input
+    ///
+    /// Returns the parsed arguments so the caller can execute the tool call.
+    pub async fn finish_tool_input(&self, session_id: &str) -> Result<serde_json::Value, String> {
+        let view = self.get_session(session_id).await.ok_or_else(|| format!("No view session {}", session_id))?;
+        view.finish_tool_input().await
+    }
+
+    /// Push a new host state to every live view
+    ///
+    /// Each view receives only the context fields that changed for it.
+    pub async fn broadcast_host_state(&self, host_state: &HostState) {
+        let sessions: Vec<ViewSession> = self.sessions.read().await.values().cloned().collect();
+        for view in sessions {
+            if let Err(e) = view.set_host_state(host_state.clone()).await {
+                log::warn!("Failed to update host context for view {}: {}", view.session_id(), e);
+            }
         }
     }
     
@@ -247,9 +407,25 @@ impl BridgeManager {
         self.bridges.read().await.get(session_id).cloned()
     }
     
-    /// Remove a bridge
+    /// Remove a bridge and its view session
+    ///
+    /// The model context the view provided, its logs, its tools and its origin
+    /// go with it.
     pub async fn remove_bridge(&self, session_id: &str) {
         self.bridges.write().await.remove(session_id);
+        self.sessions.write().await.remove(session_id);
+        if let Some(model_context) = &self.model_context {
+            model_context.remove(session_id);
+        }
+        if let Some(logs) = &self.logs {
+            logs.remove(session_id);
+        }
+        if let Some(view_tools) = &self.view_tools {
+            view_tools.remove(session_id);
+        }
+        if let Some(origins) = &self.origins {
+            origins.release(session_id);
+        }
     }
 }
 
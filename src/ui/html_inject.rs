@@ -0,0 +1,217 @@
+//! HTML Injection
+//!
+//! Injects the host's CSP, bridge and context into view documents with an
+//! HTML5 tokenizer, so tags inside comments, scripts or attribute values are
+//! never mistaken for the document's own `<head>`, and tag names match in any
+//! case. CSP `<meta>` tags the view ships itself are stripped so the host's
+//! policy is the only one in effect.
+
+use lol_html::html_content::ContentType;
+use lol_html::{element, rewrite_str, RewriteStrSettings};
+use std::cell::Cell;
+
+/// A view document after injection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InjectedHtml {
+    /// A full document with the head content in place
+    Document(String),
+    /// Markup without `<html>`, `<head>` or `<body>`, for the caller to wrap
+    Fragment(String),
+}
+
+/// Element the head content is injected into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    /// Prepended to the document's first `<head>`
+    Head,
+    /// Prepended to `<html>` in a new `<head>`
+    Html,
+    /// Placed before `<body>` in a new `<head>`
+    Body,
+}
+
+/// Whether a `<meta http-equiv>` value sets a Content Security Policy
+fn is_csp_meta(http_equiv: &str) -> bool {
+    http_equiv.trim().eq_ignore_ascii_case("content-security-policy")
+}
+
+/// Find where a document's head content belongs
+fn find_target(html: &str) -> Result<Option<Target>, lol_html::errors::RewritingError> {
+    let has_head = Cell::new(false);
+    let has_html = Cell::new(false);
+    let has_body = Cell::new(false);
+    rewrite_str(
+        html,
+        RewriteStrSettings {
+            element_content_handlers: vec![
+                element!("head", |_| {
+                    has_head.set(true);
+                    Ok(())
+                }),
+                element!("html", |_| {
+                    has_html.set(true);
+                    Ok(())
+                }),
+                element!("body", |_| {
+                    has_body.set(true);
+                    Ok(())
+                }),
+            ],
+            ..RewriteStrSettings::new()
+        },
+    )?;
+
+    Ok(if has_head.get() {
+        Some(Target::Head)
+    } else if has_html.get() {
+        Some(Target::Html)
+    } else if has_body.get() {
+        Some(Target::Body)
+    } else {
+        None
+    })
+}
+
+/// Inject `head` at the start of a view document's head
+///
+/// The content goes first in the first real `<head>`, or in a new `<head>` if
+/// the document has none. Existing CSP `<meta>` tags are removed. Markup
+/// that is not a document is returned as a [`InjectedHtml::Fragment`].
+pub fn inject_head(html: &str, head: &str) -> InjectedHtml {
+    let target = match find_target(html) {
+        Ok(target) => target,
+        Err(e) => {
+            log::warn!("Treating view HTML as a fragment: {}", e);
+            return InjectedHtml::Fragment(html.to_string());
+        }
+    };
+
+    let injected = Cell::new(false);
+    let wrapped_head = format!("<head>\n{}\n</head>", head);
+    let mut handlers = vec![element!("meta[http-equiv]", |el| {
+        if el.get_attribute("http-equiv").is_some_and(|v| is_csp_meta(&v)) {
+            log::info!("Removed CSP meta tag from view: {:?}", el.get_attribute("content"));
+            el.remove();
+        }
+        Ok(())
+    })];
+    match target {
+        Some(Target::Head) => handlers.push(element!("head", |el| {
+            if !injected.replace(true) {
+                el.prepend(&format!("\n{}\n", head), ContentType::Html);
+            }
+            Ok(())
+        })),
+        Some(Target::Html) => handlers.push(element!("html", |el| {
+            if !injected.replace(true) {
+                el.prepend(&wrapped_head, ContentType::Html);
+            }
+            Ok(())
+        })),
+        Some(Target::Body) => handlers.push(element!("body", |el| {
+            if !injected.replace(true) {
+                el.before(&wrapped_head, ContentType::Html);
+            }
+            Ok(())
+        })),
+        None => {}
+    }
+
+    let rewritten = rewrite_str(
+        html,
+        RewriteStrSettings {
+            element_content_handlers: handlers,
+            ..RewriteStrSettings::new()
+        },
+    );
+    match (rewritten, target) {
+        (Ok(document), Some(_)) => InjectedHtml::Document(document),
+        (Ok(fragment), None) => InjectedHtml::Fragment(fragment),
+        (Err(e), _) => {
+            log::warn!("Treating view HTML as a fragment: {}", e);
+            InjectedHtml::Fragment(html.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEAD: &str = "<meta name=\"mcp-injected\">";
+
+    fn document(html: &str) -> String {
+        match inject_head(html, HEAD) {
+            InjectedHtml::Document(document) => document,
+            InjectedHtml::Fragment(_) => panic!("expected a document: {}", html),
+        }
+    }
+
+    /// The injected content must appear exactly once, after `marker`
+    fn assert_injected_after(html: &str, marker: &str) {
+        let document = document(html);
+        assert_eq!(document.matches(HEAD).count(), 1, "{}", document);
+        let at = document.find(HEAD).unwrap();
+        let marker_at = document.find(marker).unwrap_or_else(|| panic!("{} not in {}", marker, document));
+        assert!(marker_at < at, "{}", document);
+    }
+
+    #[test]
+    fn test_tricky_heads() {
+        let corpus: &[(&str, &str)] = &[
+            ("<html><head><title>t</title></head><body>x</body></html>", "<head>"),
+            ("<!DOCTYPE html><HTML><HEAD><TITLE>t</TITLE></HEAD><BODY>x</BODY></HTML>", "<HEAD>"),
+            (r#"<html><head class="dark" data-x="1"><title>t</title></head></html>"#, r#"data-x="1">"#),
+            ("<html><!-- <head> --><head id=real></head></html>", "<head id=real>"),
+            (r#"<html><head><script>var s = "<head>";</script></head></html>"#, "<html><head>"),
+            ("<html><head><style>/* <head> */</style></head></html>", "<html><head>"),
+            (r#"<html><head><title>&lt;head&gt;</title><meta content="<head>"></head></html>"#, "<html><head>"),
+            ("<html>\n<head\n  lang=en\n>\n</head></html>", "lang=en\n>"),
+            ("<html><head></head><body><head></head></body></html>", "<html><head>"),
+        ];
+        for (html, marker) in corpus {
+            assert_injected_after(html, marker);
+        }
+
+        // A script that mentions </head> must stay intact
+        let html = r#"<html><head><script>document.write("</head>");</script></head></html>"#;
+        assert!(document(html).contains(r#"document.write("</head>");"#));
+    }
+
+    #[test]
+    fn test_documents_without_head() {
+        let document_html = document("<!DOCTYPE html><html lang=en><body>x</body></html>");
+        assert!(document_html.contains(&format!("<html lang=en><head>\n{}\n</head><body>", HEAD)));
+
+        let document_body = document("<!doctype html><BODY class=x>hi</BODY>");
+        assert!(document_body.contains(&format!("<head>\n{}\n</head><BODY class=x>", HEAD)));
+
+        // Tags in comments and scripts do not make a document
+        for fragment in ["<div>Hello</div>", "<!-- <html><head> --><p>x</p>", "<script>'<body>'</script>", ""] {
+            assert_eq!(inject_head(fragment, HEAD), InjectedHtml::Fragment(fragment.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_csp_metas_are_stripped() {
+        let html = r#"<html><head>
+            <meta http-equiv="Content-Security-Policy" content="default-src *">
+            <META HTTP-EQUIV=" content-security-policy " CONTENT="script-src *">
+            <meta http-equiv="refresh" content="30">
+            <meta name="Content-Security-Policy" content="not a policy">
+        </head><body><meta http-equiv="content-security-policy" content="img-src *"></body></html>"#;
+        let document = document(html);
+        assert!(!document.contains("default-src *"));
+        assert!(!document.contains("script-src *"));
+        assert!(!document.contains("img-src *"));
+        assert!(document.contains(r#"http-equiv="refresh""#));
+        assert!(document.contains("not a policy"));
+
+        let InjectedHtml::Fragment(fragment) =
+            inject_head(r#"<meta http-equiv="Content-Security-Policy" content="default-src *"><p>x</p>"#, HEAD)
+        else {
+            panic!("expected a fragment");
+        };
+        assert_eq!(fragment, "<p>x</p>");
+    }
+}
@@ -1,15 +1,17 @@
 //! HTML View Component
 //!
-//! Renders spec-compliant HTML content with simulated sandboxing and
-//! full bidirectional communication via postMessage bridge.
+//! Renders spec-compliant HTML content inside a sandboxed `srcdoc` iframe
+//! with the host's App SDK injected for communication over postMessage.
 //! 
-//! Note: Full spec compliance requires true iframe sandboxing or WebView isolation.
-//! This implementation provides CSP injection and security metadata display as
-//! a pragmatic approximation for the Dioxus desktop environment.
+//! The iframe is sandboxed with `allow-scripts` only, so the view runs in a
+//! unique opaque origin with no access to the host document. Messages cross
+//! the boundary through `postMessage` and are only accepted from the expected
+//! window on either side.
 
 use dioxus::prelude::*;
+use crate::host::{styles_css, UiSessionEvent};
 use crate::protocol::*;
-use crate::ui::{UiMessageEvent, UiSessionState};
+use crate::ui::{app_sdk_script, inject_head, InjectedHtml, UiMessageEvent, UiSessionState, ViewSession, PROXY_SANDBOX, SANDBOX_PROXY_PATH};
 
 /// Props for HTML view
 #[derive(Props, Clone, PartialEq)]
@@ -25,280 +27,203 @@ pub struct HtmlViewProps {
     /// Host context to send to the view
     #[props(!optional)]
     pub host_context: Option<HostContext>,
+    /// Host-side lifecycle session for this view
+    #[props(!optional)]
+    pub session: Option<ViewSession>,
+    /// Domains the host approves for the view's CSP
+    #[props(!optional)]
+    pub approved_csp: Option<ApprovedCsp>,
+    /// Sandbox permissions the user granted the view
+    #[props(!optional)]
+    pub granted_permissions: Option<UiPermissions>,
+    /// Dedicated origin to serve the view from through the sandbox proxy
+    ///
+    /// Without one the view is rendered from `srcdoc` with an opaque origin.
+    #[props(!optional)]
+    pub app_origin: Option<String>,
+    /// Also install the legacy `window.mcp` bridge API
+    #[props(default)]
+    pub legacy_bridge: bool,
 }
 
-/// Generate the postMessage bridge JavaScript code
-fn generate_postmessage_bridge() -> String {
-    r#"
-<script>
-(function() {
-    'use strict';
-    
-    const MCP_BRIDGE_VERSION = '1.0.0';
-    const parentOrigin = '*'; // In production, restrict to host origin
-    
-    // Track pending requests
-    const pendingRequests = new Map();
-    let nextRequestId = 1;
-    
-    // Notify host that view is ready
-    function notifyReady() {
-        window.parent.postMessage({
-            jsonrpc: '2.0',
-            method: 'ui/ready',
-            params: {
-                bridgeVersion: MCP_BRIDGE_VERSION,
-                timestamp: Date.now()
-            }
-        }, parentOrigin);
+/// Sandbox flags for view iframes
+///
+/// Without `allow-same-origin` the view gets a unique opaque origin.
+pub const VIEW_SANDBOX: &str = "allow-scripts";
+
+/// Sandbox flags for views served from a dedicated origin
+///
+/// The inner frame keeps the proxy page's per-app origin, which is never the
+/// host's, so storage and CORS are scoped to the app.
+pub const DEDICATED_VIEW_SANDBOX: &str = "allow-scripts allow-same-origin";
+
+/// Frame style for views that have not reported a content size
+const FILL_FRAME_STYLE: &str = "width: 100%; height: 100%; min-height: 480px; border: none;";
+
+/// Host document script relaying JSON-RPC between a view iframe and the host session
+///
+/// Only messages whose source is the view's own iframe window are forwarded to
+/// Rust; messages from Rust are posted into the iframe. The frame is looked up
+/// on each message since it may mount after the relay starts. A `null` message
+/// from Rust removes the listener.
+const HOST_RELAY_SCRIPT: &str = r#"
+    const frameId = __FRAME_ID__;
+    const frameWindow = function() {
+        const frame = document.getElementById(frameId);
+        return frame ? frame.contentWindow : null;
+    };
+    const relay = function(event) {
+        const source = frameWindow();
+        if (source === null || event.source !== source) return;
+        const data = event.data;
+        if (!data || typeof data !== 'object' || data.jsonrpc !== '2.0') return;
+        dioxus.send(data);
+    };
+    window.addEventListener('message', relay);
+    while (true) {
+        const message = await dioxus.recv();
+        if (message === null) {
+            window.removeEventListener('message', relay);
+            break;
+        }
+        const target = frameWindow();
+        if (target !== null) {
+            // The view has an opaque origin, so no narrower target origin exists
+            target.postMessage(message, '*');
+        }
     }
-    
-    // Listen for messages from host
-    window.addEventListener('message', function(event) {
-        // Validate message structure
-        if (!event.data || typeof event.data !== 'object') return;
-        
+"#;
+
+/// Build the relay script for the iframe with the given element ID
+fn host_relay_script(frame_id: &str) -> String {
+    let frame_id = serde_json::to_string(frame_id).unwrap_or_else(|_| "\"\"".to_string());
+    HOST_RELAY_SCRIPT.replace("__FRAME_ID__", &frame_id)
+}
+
+/// Host document script relaying JSON-RPC through a sandbox proxy iframe
+///
+/// Like [`HOST_RELAY_SCRIPT`], but messages must also come from the proxy
+/// origin and are posted only to it. When the proxy announces
+/// `ui/notifications/sandbox-proxy-ready` it is answered with the
+/// `ui/notifications/sandbox-resource-ready` carrying the view.
+const PROXY_RELAY_SCRIPT: &str = r#"
+    const frameId = __FRAME_ID__;
+    const proxyOrigin = __PROXY_ORIGIN__;
+    const resource = __RESOURCE__;
+    const frameWindow = function() {
+        const frame = document.getElementById(frameId);
+        return frame ? frame.contentWindow : null;
+    };
+    const relay = function(event) {
+        const source = frameWindow();
+        if (source === null || event.source !== source || event.origin !== proxyOrigin) return;
         const data = event.data;
-        
-        // Handle responses to our requests
-        if (data.id !== undefined && pendingRequests.has(data.id)) {
-            const { resolve, reject } = pendingRequests.get(data.id);
-            pendingRequests.delete(data.id);
-            
-            if (data.error) {
-                reject(new Error(data.error.message || 'Unknown error'));
-            } else {
-                resolve(data.result);
-            }
+        if (!data || typeof data !== 'object' || data.jsonrpc !== '2.0') return;
+        if (data.method === 'ui/notifications/sandbox-proxy-ready') {
+            source.postMessage(resource, proxyOrigin);
             return;
         }
-        
-        // Handle notifications/requests from host
-        if (!data.method) return;
-        
-        switch (data.method) {
-            case 'host/context':
-                window.mcpHostContext = data.params;
-                document.dispatchEvent(new CustomEvent('mcp:context', { detail: data.params }));
-                break;
-                
-            case 'tool/result':
-                document.dispatchEvent(new CustomEvent('mcp:toolResult', { detail: data.params }));
-                break;
-                
-            case 'display/modeChanged':
-                document.dispatchEvent(new CustomEvent('mcp:displayModeChanged', { detail: data.params }));
-                break;
-                
-            case 'ping':
-                window.parent.postMessage({
-                    jsonrpc: '2.0',
-                    id: data.id,
-                    result: { pong: true, timestamp: Date.now() }
-                }, parentOrigin);
-                break;
+        dioxus.send(data);
+    };
+    window.addEventListener('message', relay);
+    while (true) {
+        const message = await dioxus.recv();
+        if (message === null) {
+            window.removeEventListener('message', relay);
+            break;
         }
-    });
-    
-    // MCP API exposed to views
-    window.mcp = {
-        version: MCP_BRIDGE_VERSION,
-        
-        // Call a tool on the server
-        callTool: function(name, args) {
-            return new Promise((resolve, reject) => {
-                const id = (nextRequestId++).toString();
-                pendingRequests.set(id, { resolve, reject });
-                
-                // Set timeout
-                setTimeout(() => {
-                    if (pendingRequests.has(id)) {
-                        pendingRequests.delete(id);
-                        reject(new Error('Tool call timeout'));
-                    }
-                }, 30000);
-                
-                window.parent.postMessage({
-                    jsonrpc: '2.0',
-                    id: id,
-                    method: 'tools/call',
-                    params: { name: name, arguments: args || {} }
-                }, parentOrigin);
-            });
-        },
-        
-        // Update model context
-        updateContext: function(content, structuredContent) {
-            window.parent.postMessage({
-                jsonrpc: '2.0',
-                method: 'context/update',
-                params: {
-                    content: content,
-                    structuredContent: structuredContent
-                }
-            }, parentOrigin);
-        },
-        
-        // Request display mode change
-        requestDisplayMode: function(mode) {
-            return new Promise((resolve, reject) => {
-                const id = (nextRequestId++).toString();
-                pendingRequests.set(id, { resolve, reject });
-                
-                setTimeout(() => {
-                    if (pendingRequests.has(id)) {
-                        pendingRequests.delete(id);
-                        reject(new Error('Display mode request timeout'));
-                    }
-                }, 5000);
-                
-                window.parent.postMessage({
-                    jsonrpc: '2.0',
-                    id: id,
-                    method: 'display/mode',
-                    params: { mode: mode }
-                }, parentOrigin);
-            });
-        },
-        
-        // Request expanded/fullscreen mode
-        requestExpanded: function() {
-            return this.requestDisplayMode('expanded');
-        },
-        
-        // Request inline mode
-        requestInline: function() {
-            return this.requestDisplayMode('inline');
-        },
-        
-        // Send log message to host
-        log: function(level, message, logger) {
-            window.parent.postMessage({
-                jsonrpc: '2.0',
-                method: 'logging/message',
-                params: { 
-                    level: level, 
-                    message: message,
-                    logger: logger || 'mcp-app'
-                }
-            }, parentOrigin);
-        },
-        
-        // Open a link (requires host approval)
-        openLink: function(url) {
-            window.parent.postMessage({
-                jsonrpc: '2.0',
-                method: 'link/open',
-                params: { url: url }
-            }, parentOrigin);
-        },
-        
-        // Get current host context
-        getContext: function() {
-            return window.mcpHostContext || null;
-        },
-        
-        // Listen for context updates
-        onContext: function(callback) {
-            document.addEventListener('mcp:context', function(e) {
-                callback(e.detail);
-            });
-        },
-        
-        // Listen for tool results
-        onToolResult: function(callback) {
-            document.addEventListener('mcp:toolResult', function(e) {
-                callback(e.detail);
-            });
-        },
-        
-        // Listen for display mode changes
-        onDisplayModeChanged: function(callback) {
-            document.addEventListener('mcp:displayModeChanged', function(e) {
-                callback(e.detail);
-            });
+        const target = frameWindow();
+        if (target !== null) {
+            target.postMessage(message, proxyOrigin);
         }
-    };
-    
-    // Notify ready when DOM is loaded
-    if (document.readyState === 'loading') {
-        document.addEventListener('DOMContentLoaded', notifyReady);
-    } else {
-        notifyReady();
     }
-})();
-</script>
-"#.to_string()
+"#;
+
+/// Build the proxy relay script for the given iframe, proxy origin and view resource
+fn proxy_relay_script(frame_id: &str, proxy_origin: &str, resource: &JsonRpcNotification) -> String {
+    let to_js = |value: serde_json::Value| serde_json::to_string(&value).unwrap_or_else(|_| "null".to_string());
+    PROXY_RELAY_SCRIPT
+        .replace("__FRAME_ID__", &to_js(frame_id.into()))
+        .replace("__PROXY_ORIGIN__", &to_js(proxy_origin.into()))
+        .replace("__RESOURCE__", &to_js(serde_json::to_value(resource).unwrap_or_default()))
 }
 
-/// Wrap HTML content with CSP meta tag and security context
-fn wrap_html_with_security(html: &str, metadata: &Option<UiResourceMeta>, host_context: &Option<HostContext>) -> String {
-    // Extract CSP from metadata or use default restrictive policy
-    let csp = metadata
+/// Effective CSP for a view's metadata under the host's approved domains
+fn view_csp_policy(metadata: &Option<UiResourceMeta>, approved: Option<&ApprovedCsp>) -> CspPolicy {
+    metadata
         .as_ref()
         .and_then(|m| m.ui.as_ref())
         .and_then(|u| u.csp.as_ref())
-        .map(|csp| csp.build_csp_header())
-        .unwrap_or_else(|| {
-            // Default restrictive CSP
-            "default-src 'none'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'; img-src 'self' data:; media-src 'self' data:; connect-src 'none'; frame-src 'none'; object-src 'none'".to_string()
-        });
+        .map(|csp| csp.effective_policy(approved))
+        .unwrap_or_default()
+}
+
+/// Build the sandbox-resource-ready notification for a view
+fn sandbox_resource(
+    html: &str,
+    sandbox: &str,
+    csp: &CspPolicy,
+    granted: &UiPermissions,
+    host_context: &Option<HostContext>,
+    legacy_bridge: bool,
+) -> JsonRpcNotification {
+    let mut resource = messages::sandbox_resource_ready_notification(
+        wrap_html_with_security(html, csp, host_context, legacy_bridge),
+        Some(csp.to_resource_csp()),
+        Some(UiResourcePermissions {
+            camera: granted.camera,
+            microphone: granted.microphone,
+            geolocation: granted.geolocation,
+            clipboard_write: granted.clipboard_write,
+        }),
+    );
+    if let Some(params) = resource.params.as_mut() {
+        params["sandbox"] = sandbox.into();
+    }
+    resource
+}
+
+/// Wrap HTML content with CSP meta tag and security context
+///
+/// The CSP meta and the App SDK go first in the document's head, ahead of
+/// any script the view ships; the view's own CSP meta tags are dropped.
+fn wrap_html_with_security(html: &str, csp: &CspPolicy, host_context: &Option<HostContext>, legacy_bridge: bool) -> String {
+    // An empty policy renders the default restrictive CSP
+    let csp = csp.to_header();
     
-    let bridge = generate_postmessage_bridge();
+    let bridge = app_sdk_script(legacy_bridge);
     
     // Serialize host context for injection
     let context_script = host_context.as_ref().map(|ctx| {
         match serde_json::to_string(ctx) {
             Ok(json) => format!(
                 r#"<script>window.mcpHostContext = {};</script>"#,
-                json
+                json.replace("</", "<\\/")
             ),
             Err(_) => String::new(),
         }
     }).unwrap_or_default();
     
-    // Check if HTML already has proper structure
-    let has_html_tag = html.contains("<html") || html.contains("<!DOCTYPE");
+    // Theme variables and fonts; the bridge keeps this tag in sync
+    let theme_style = host_context.as_ref()
+        .and_then(|ctx| ctx.styles.as_ref())
+        .map(|styles| format!(r#"<style id="mcp-host-styles">{}</style>"#, styles_css(styles)))
+        .unwrap_or_default();
+    let context_script = format!("{}\n{}", theme_style, context_script);
     
-    if has_html_tag {
-        // Inject CSP meta tag, bridge, and context
-        let csp_meta = format!(r#"<meta http-equiv="Content-Security-Policy" content="{}">"#, csp);
-        
-        let inject_head = format!("{}\n{}\n{}", csp_meta, context_script, bridge);
-        
-        if html.contains("<head>") {
-            html.replacen("<head>", &format!("<head>\n{}", inject_head), 1)
-        } else if html.contains("</head>") {
-            html.replacen("</head>", &format!("{}\n</head>", inject_head), 1)
-        } else if html.contains("<html") {
-            html.replacen("<html", &format!("<head>{}</head>\n<html", inject_head), 1)
-        } else {
-            format!(
-                r#"<!DOCTYPE html>
-<html>
-<head>
-{}
-<meta charset="utf-8">
-<meta name="viewport" content="width=device-width, initial-scale=1">
-</head>
-<body>
-{}
-</body>
-</html>"#,
-                inject_head, html
-            )
-        }
-    } else {
+    let csp_meta = format!(r#"<meta http-equiv="Content-Security-Policy" content="{}">"#, html_escape(&csp));
+    let head = format!("{}\n{}\n{}", csp_meta, bridge, context_script);
+    
+    match inject_head(html, &head) {
+        InjectedHtml::Document(document) => document,
         // Wrap fragment in complete HTML document
-        format!(
+        InjectedHtml::Fragment(fragment) => format!(
             r#"<!DOCTYPE html>
 <html>
 <head>
-<meta http-equiv="Content-Security-Policy" content="{}">
+{}
 <meta charset="utf-8">
 <meta name="viewport" content="width=device-width, initial-scale=1">
-{}
-{}
 <style>
 /* Reset and base styles for MCP Apps */
 * {{
@@ -307,9 +232,10 @@ fn wrap_html_with_security(html: &str, metadata: &Option<UiResourceMeta>, host_c
 body {{
     margin: 0;
     padding: 16px;
-    font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Oxygen, Ubuntu, sans-serif;
+    font-family: var(--font-sans, -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Oxygen, Ubuntu, sans-serif);
     line-height: 1.5;
-    color: #1f2937;
+    color: var(--color-text-primary, #1f2937);
+    background: var(--color-background-primary, #ffffff);
 }}
 </style>
 </head>
@@ -317,8 +243,8 @@ body {{
 {}
 </body>
 </html>"#,
-            csp, context_script, bridge, html
-        )
+            head, fragment
+        ),
     }
 }
 
@@ -327,34 +253,133 @@ body {{
 /// This component renders HTML content with:
 /// - CSP (Content Security Policy) injection
 /// - Security metadata display
-/// - Simulated sandbox environment
+/// - A sandboxed `srcdoc` iframe with an opaque origin
 /// - Full bidirectional postMessage bridge
-/// 
-/// Note: True iframe sandboxing requires WebView integration for full spec compliance.
 #[component]
 pub fn HtmlView(props: HtmlViewProps) -> Element {
     let html = props.html.clone();
     let metadata = props.metadata.clone();
     let host_context = props.host_context.clone();
-    let metadata_for_csp = metadata.clone();
+    let legacy_bridge = props.legacy_bridge;
     let metadata_for_perms = metadata.clone();
     let metadata_for_border = metadata.clone();
     let on_message = props.on_message.clone();
+    let frame_id = use_hook(|| format!("mcp-view-{}", uuid::Uuid::new_v4()));
+    // Inline container size fitted to the view's content
+    let mut container_size = use_signal(|| None::<(u32, u32)>);
     
-    // Wrap HTML with security context
-    let secured_html = use_memo(move || {
-        wrap_html_with_security(&html, &metadata, &host_context)
+    // Declared CSP intersected with what the host approves
+    let csp_policy = use_hook(|| {
+        let policy = view_csp_policy(&props.metadata, props.approved_csp.as_ref());
+        for dropped in &policy.dropped {
+            log::warn!(
+                "Dropped CSP origin {} ({:?}): {:?}",
+                dropped.origin, dropped.directive, dropped.reason
+            );
+        }
+        policy
     });
+    // Only what the user consented to reaches the iframe `allow` attribute
+    let granted = props.granted_permissions.clone().unwrap_or_default();
+    let allow = granted.allow_attribute();
+    // Views with a dedicated origin sit behind the double-iframe sandbox proxy
+    let proxy_origin = props.app_origin.clone();
     
-    // Extract CSP info for display
-    let csp_info = use_memo(move || {
-        metadata_for_csp.as_ref()
-            .and_then(|m| m.ui.as_ref())
-            .and_then(|u| u.csp.as_ref())
-            .map(|csp| csp.build_csp_header())
-            .unwrap_or_else(|| "Default CSP (restrictive)".to_string())
+    // Relay JSON-RPC between the view and its host session
+    let relay = use_hook(|| {
+        let session = props.session.clone();
+        let mut eval = match proxy_origin.as_deref() {
+            Some(origin) => {
+                let resource = sandbox_resource(
+                    &props.html,
+                    DEDICATED_VIEW_SANDBOX,
+                    &csp_policy,
+                    &granted,
+                    &props.host_context,
+                    props.legacy_bridge,
+                );
+                document::eval(&proxy_relay_script(&frame_id, origin, &resource))
+            }
+            None => document::eval(&host_relay_script(&frame_id)),
+        };
+        
+        let Some(session) = session else {
+            return Some(eval);
+        };
+        
+        spawn(async move {
+            let Some(mut outgoing) = session.take_outgoing().await else {
+                log::warn!("View session {} already has a renderer", session.session_id());
+                return;
+            };
+            let mut events = session.subscribe();
+            
+            loop {
+                tokio::select! {
+                    message = outgoing.recv() => match message {
+                        Some(message) => {
+                            if let Err(e) = eval.send(message) {
+                                log::error!("Failed to deliver message to view: {:?}", e);
+                            }
+                        }
+                        None => break,
+                    },
+                    message = eval.recv::<serde_json::Value>() => match message {
+                        Ok(message) => match session.handle_message(message).await {
+                            Ok(Some(unhandled)) => {
+                                if let Some(handler) = &on_message {
+                                    handler.call(UiMessageEvent::JsonRpc(unhandled));
+                                }
+                            }
+                            Ok(None) => {}
+                            Err(e) => log::error!("Error handling view message: {}", e),
+                        },
+                        Err(e) => {
+                            log::warn!("View relay closed: {:?}", e);
+                            break;
+                        }
+                    },
+                    event = events.recv() => match event {
+                        Ok(UiSessionEvent::SizeChanged { width, height, .. }) => {
+                            container_size.set(Some((width, height)));
+                            if let Some(handler) = &on_message {
+                                handler.call(UiMessageEvent::SizeChanged { width, height });
+                            }
+                        }
+                        Ok(UiSessionEvent::DisplayModeChanged { mode, .. }) if mode != DisplayMode::Inline => {
+                            container_size.set(None);
+                        }
+                        // The session holds the sender, so the channel only lags
+                        _ => {}
+                    },
+                }
+            }
+        });
+        
+        Some(eval)
+    });
+    
+    use_drop(move || {
+        if let Some(eval) = relay {
+            let _ = eval.send(serde_json::Value::Null);
+        }
+    });
+    
+    // Wrap HTML with security context
+    let secured_html = use_memo({
+        let csp_policy = csp_policy.clone();
+        move || wrap_html_with_security(&html, &csp_policy, &host_context, legacy_bridge)
     });
     
+    // CSP info for display
+    let csp_info = csp_policy.to_header();
+    let dropped_csp: Vec<String> = csp_policy.dropped.iter()
+        .map(|d| match &d.reason {
+            CspDropReason::Invalid(e) => format!("{} ({:?}): {}", d.origin, d.directive, e),
+            CspDropReason::NotApproved => format!("{} ({:?}): not approved by host", d.origin, d.directive),
+        })
+        .collect();
+    
     // Extract permissions
     let permissions = use_memo(move || {
         metadata_for_perms.as_ref()
@@ -376,6 +401,15 @@ pub fn HtmlView(props: HtmlViewProps) -> Element {
         ""
     };
     
+    // Fitted views size their frame to the content; others fill the content area
+    let (container_class, frame_style) = match *container_size.read() {
+        Some((width, height)) => (
+            "overflow-hidden",
+            format!("width: 100%; max-width: {}px; height: {}px; border: none;", width, height),
+        ),
+        None => ("flex-1 overflow-hidden", FILL_FRAME_STYLE.to_string()),
+    };
+    
     // Track if security panel is expanded
     let mut show_security = use_signal(|| false);
     let is_expanded = *show_security.read();
@@ -402,14 +436,28 @@ pub fn HtmlView(props: HtmlViewProps) -> Element {
             }
             
             // HTML Content Container
-            // Using a scoped wrapper to simulate isolation
             div {
-                class: "flex-1 overflow-auto {border_class}",
+                class: "{container_class} {border_class}",
                 
-                // The actual HTML content with injected bridge
-                div {
-                    class: "mcp-html-content",
-                    dangerous_inner_html: "{secured_html}"
+                // The actual HTML content with injected bridge, isolated in its own origin
+                if let Some(origin) = proxy_origin.as_deref() {
+                    iframe {
+                        id: "{frame_id}",
+                        "sandbox": PROXY_SANDBOX,
+                        // The outer proxy frame must delegate any permission the inner view gets
+                        allow: "{allow}",
+                        src: format!("{origin}{SANDBOX_PROXY_PATH}"),
+                        title: "MCP App Sandbox",
+                        style: "{frame_style}",
+                    }
+                } else {
+                    SandboxedIframe {
+                        id: frame_id,
+                        html: secured_html.read().clone(),
+                        sandbox: VIEW_SANDBOX.to_string(),
+                        allow: allow.clone(),
+                        style: frame_style.clone(),
+                    }
                 }
             }
             
@@ -434,6 +482,16 @@ pub fn HtmlView(props: HtmlViewProps) -> Element {
                             }
                         }
                         
+                        // Dropped CSP origins
+                        if !dropped_csp.is_empty() {
+                            div {
+                                div { class: "font-semibold text-gray-600 mb-1", "Dropped CSP Origins:" }
+                                for entry in dropped_csp.iter() {
+                                    div { class: "break-all text-red-600", "{entry}" }
+                                }
+                            }
+                        }
+                        
                         // Permissions Section
                         if let Some(perms) = permissions.read().as_ref() {
                             div {
@@ -456,12 +514,26 @@ pub fn HtmlView(props: HtmlViewProps) -> Element {
                                     }
                                 }
                             }
+                            div {
+                                div { class: "font-semibold text-gray-600 mb-1", "Granted Permissions:" }
+                                div {
+                                    class: "break-all text-gray-500 bg-gray-100 p-2 rounded",
+                                    if allow.is_empty() { "None" } else { "allow=\"{allow}\"" }
+                                }
+                            }
                         }
                         
-                        // Implementation Note
+                        // Sandbox Section
                         div {
-                            class: "mt-3 pt-3 border-t border-gray-200 text-gray-400 italic",
-                            "Note: This is a simulated sandbox. True iframe isolation requires WebView integration."
+                            div { class: "font-semibold text-gray-600 mb-1", "Sandbox:" }
+                            div {
+                                class: "break-all text-gray-500 bg-gray-100 p-2 rounded",
+                                if let Some(origin) = proxy_origin.as_deref() {
+                                    "proxy {origin} → iframe sandbox=\"{DEDICATED_VIEW_SANDBOX}\" (origin {origin})"
+                                } else {
+                                    "iframe sandbox=\"{VIEW_SANDBOX}\" (opaque origin)"
+                                }
+                            }
                         }
                     }
                 }
@@ -526,25 +598,18 @@ pub fn WebViewBridge(props: WebViewBridgeProps) -> Element {
 
 /// Helper component to render an iframe with sandbox attributes
 /// 
-/// Note: Dioxus doesn't directly support iframes with srcdoc in RSX.
-/// This is a workaround using dangerously_set_inner_html.
+/// The document is passed through `srcdoc`, so the frame never loads a URL
+/// and its origin is decided by the sandbox flags alone.
 #[component]
-pub fn SandboxedIframe(html: String, sandbox: String) -> Element {
-    let iframe_html = format!(
-        r##"<iframe 
-            sandbox="{}" 
-            srcdoc="{}" 
-            style="width: 100%; height: 100%; border: none;"
-            title="MCP App View"
-        ></iframe>"##,
-        sandbox,
-        html_escape(&html)
-    );
-    
+pub fn SandboxedIframe(id: String, html: String, sandbox: String, allow: String, style: String) -> Element {
     rsx! {
-        div {
-            class: "w-full h-full",
-            dangerous_inner_html: "{iframe_html}"
+        iframe {
+            id: "{id}",
+            "sandbox": "{sandbox}",
+            allow: "{allow}",
+            srcdoc: "{html}",
+            title: "MCP App View",
+            style: "{style}",
         }
     }
 }
@@ -569,23 +634,68 @@ mod tests {
         assert_eq!(html_escape(r#""quoted""#), "&quot;quoted&quot;");
     }
     
+    #[test]
+    fn test_host_relay_script_targets_frame() {
+        let script = host_relay_script("mcp-view-1");
+        assert!(script.contains(r#"const frameId = "mcp-view-1";"#));
+        assert!(script.contains("event.source !== source"));
+        assert!(!script.contains("__FRAME_ID__"));
+    }
+    
+    #[test]
+    fn test_proxy_relay_script_delivers_resource() {
+        let granted = UiPermissions { camera: Some(Empty {}), ..Default::default() };
+        let resource = sandbox_resource("<div>Hi</div>", DEDICATED_VIEW_SANDBOX, &CspPolicy::default(), &granted, &None, false);
+        let params = resource.params.as_ref().unwrap();
+        assert_eq!(resource.method, "ui/notifications/sandbox-resource-ready");
+        assert_eq!(params["sandbox"], DEDICATED_VIEW_SANDBOX);
+        assert!(params["html"].as_str().unwrap().contains("<div>Hi</div>"));
+        assert!(params["permissions"]["camera"].is_object());
+        assert!(params["permissions"]["microphone"].is_null());
+        
+        let script = proxy_relay_script("mcp-view-1", "http://127.0.0.1:8765", &resource);
+        assert!(script.contains(r#"const proxyOrigin = "http://127.0.0.1:8765";"#));
+        assert!(script.contains("event.origin !== proxyOrigin"));
+        assert!(!script.contains("__RESOURCE__"));
+    }
+    
     #[test]
     fn test_wrap_html_with_security() {
         let html = "<div>Hello</div>";
-        let wrapped = wrap_html_with_security(html, &None, &None);
+        let wrapped = wrap_html_with_security(html, &CspPolicy::default(), &None, false);
         
         assert!(wrapped.contains("<!DOCTYPE html>"));
         assert!(wrapped.contains("Content-Security-Policy"));
         assert!(wrapped.contains("<div>Hello</div>"));
-        assert!(wrapped.contains("window.mcp"));
+        assert!(wrapped.contains("window.McpApps"));
     }
     
     #[test]
     fn test_csp_injection_existing_head() {
         let html = r#"<!DOCTYPE html><html><head><title>Test</title></head><body>Hello</body></html>"#;
-        let wrapped = wrap_html_with_security(html, &None, &None);
+        let wrapped = wrap_html_with_security(html, &CspPolicy::default(), &None, false);
         
         // CSP should be injected after <head>
         assert!(wrapped.contains("<head>\n<meta http-equiv=\"Content-Security-Policy\""));
     }
+    
+    #[test]
+    fn test_bridge_precedes_view_scripts() {
+        let html = r#"<HTML><Head lang="en"><meta http-equiv="Content-Security-Policy" content="default-src *"><script>new McpApps.App().connect();</script></Head></HTML>"#;
+        let wrapped = wrap_html_with_security(html, &CspPolicy::default(), &None, false);
+        
+        assert_eq!(wrapped.matches("http-equiv=\"Content-Security-Policy\"").count(), 1);
+        assert!(!wrapped.contains("default-src *"));
+        assert!(wrapped.find("window.McpApps = {").unwrap() < wrapped.find("new McpApps.App()").unwrap());
+    }
+    
+    #[test]
+    fn test_theme_styles_are_injected() {
+        let context = Some(crate::host::HostState::default().with_theme("dark").to_host_context());
+        let wrapped = wrap_html_with_security("<div>Hi</div>", &CspPolicy::default(), &context, false);
+        
+        assert!(wrapped.contains(r#"<style id="mcp-host-styles">"#));
+        assert!(wrapped.contains("--color-background-primary: #111827;"));
+        assert!(wrapped.contains("var(--color-text-primary"));
+    }
 }
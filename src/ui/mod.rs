@@ -4,12 +4,22 @@
 //! spec-compliant HTML content and the custom Rhai scripting extension.
 
 pub mod rhai_renderer;
+pub mod rhai_limits;
 pub mod html_view;
+pub mod html_inject;
+pub mod app_sdk;
 pub mod bridge;
+pub mod session;
+pub mod sandbox_proxy;
 
 pub use rhai_renderer::*;
+pub use rhai_limits::*;
 pub use html_view::*;
+pub use html_inject::*;
+pub use app_sdk::*;
 pub use bridge::*;
+pub use session::*;
+pub use sandbox_proxy::*;
 
 use crate::protocol::*;
 use dioxus::prelude::*;
@@ -33,13 +43,22 @@ pub enum UiContent {
     },
     /// Error state
     Error(String),
+    /// A Rhai script was stopped for breaching a resource limit
+    ScriptLimitExceeded {
+        limit: ScriptLimit,
+        message: String,
+    },
     /// Loading state
     Loading,
 }
 
 impl UiContent {
     /// Create UI content from a UiResourceContent
-    pub fn from_resource_content(content: UiResourceContent, tool_result: Option<String>) -> Self {
+    ///
+    /// Tool data is not part of the content: HTML views receive it through
+    /// `ui/notifications/tool-input` and `tool-result`, Rhai scripts through
+    /// [`UiContent::set_tool_result`].
+    pub fn from_resource_content(content: UiResourceContent) -> Self {
         let html = content.text.or(content.blob.map(|b| {
             // Decode base64 if needed
             String::from_utf8_lossy(&base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &b).unwrap_or_default()).to_string()
@@ -51,7 +70,7 @@ impl UiContent {
                 // It's likely a Rhai script
                 return UiContent::RhaiScript {
                     script: html,
-                    context: tool_result.unwrap_or_else(|| "{}".to_string()),
+                    context: "{}".to_string(),
                 };
             }
             
@@ -64,6 +83,17 @@ impl UiContent {
         }
     }
     
+    /// Deliver a tool result to content that renders it natively
+    ///
+    /// Rhai scripts are re-evaluated with the result as their `data` context.
+    /// HTML content is left untouched since the view receives the result over
+    /// its bridge.
+    pub fn set_tool_result(&mut self, result: &serde_json::Value) {
+        if let UiContent::RhaiScript { context, .. } = self {
+            *context = result.to_string();
+        }
+    }
+    
     /// Check if content is HTML
     pub fn is_html(&self) -> bool {
         matches!(self, UiContent::Html { .. })
@@ -73,6 +103,24 @@ impl UiContent {
     pub fn is_rhai(&self) -> bool {
         matches!(self, UiContent::RhaiScript { .. })
     }
+    
+    /// Source and metadata of the view this content renders, if any
+    pub fn view_source(&self) -> Option<(&str, Option<&UiResourceMeta>)> {
+        match self {
+            UiContent::Html { content, metadata } => Some((content, metadata.as_ref())),
+            UiContent::RhaiScript { script, .. } => Some((script, None)),
+            _ => None,
+        }
+    }
+}
+
+impl From<ScriptError> for UiContent {
+    fn from(e: ScriptError) -> Self {
+        match e {
+            ScriptError::Limit { limit, message } => UiContent::ScriptLimitExceeded { limit, message },
+            ScriptError::Script(e) => UiContent::Error(e),
+        }
+    }
 }
 
 /// Props for UI content renderer
@@ -86,6 +134,24 @@ pub struct UiContentProps {
     /// Host context to send to the view
     #[props(!optional)]
     pub host_context: Option<HostContext>,
+    /// Host-side lifecycle session for the view
+    #[props(!optional)]
+    pub session: Option<ViewSession>,
+    /// Domains the host approves for the view's CSP
+    #[props(!optional)]
+    pub approved_csp: Option<ApprovedCsp>,
+    /// Sandbox permissions the user granted the view
+    #[props(!optional)]
+    pub granted_permissions: Option<UiPermissions>,
+    /// Dedicated origin to serve the view from
+    #[props(!optional)]
+    pub app_origin: Option<String>,
+    /// Calls tools for Rhai event handlers
+    #[props(!optional)]
+    pub tool_caller: Option<ToolCaller>,
+    /// Also install the legacy `window.mcp` bridge API in HTML views
+    #[props(default)]
+    pub legacy_bridge: bool,
 }
 
 /// UI message event from the view
@@ -120,6 +186,11 @@ pub fn UiContentRenderer(props: UiContentProps) -> Element {
                     metadata: metadata.clone(),
                     on_message: props.on_message.clone(),
                     host_context: props.host_context.clone(),
+                    session: props.session.clone(),
+                    approved_csp: props.approved_csp.clone(),
+                    granted_permissions: props.granted_permissions.clone(),
+                    app_origin: props.app_origin.clone(),
+                    legacy_bridge: props.legacy_bridge,
                 }
             }
         }
@@ -128,16 +199,15 @@ pub fn UiContentRenderer(props: UiContentProps) -> Element {
                 RhaiRenderer {
                     script: script.clone(),
                     context: context.clone(),
+                    theme: props.host_context.as_ref()
+                        .and_then(|c| c.theme.clone())
+                        .unwrap_or_else(|| "light".to_string()),
+                    tool_caller: props.tool_caller.clone(),
                 }
             }
         }
-        UiContent::Error(e) => {
-            rsx! {
-                div {
-                    class: "p-4 bg-red-50 border border-red-200 rounded-lg text-red-700",
-                    "Error: {e}"
-                }
-            }
+        UiContent::Error(_) | UiContent::ScriptLimitExceeded { .. } => {
+            rsx! { ContentError { content: props.content.clone() } }
         }
         UiContent::Loading => {
             rsx! {
@@ -150,6 +220,27 @@ pub fn UiContentRenderer(props: UiContentProps) -> Element {
     }
 }
 
+/// Error states of UI content
+#[component]
+pub fn ContentError(content: UiContent) -> Element {
+    match content {
+        UiContent::ScriptLimitExceeded { limit, message } => rsx! {
+            div {
+                class: "p-4 bg-amber-50 border border-amber-300 rounded-lg text-amber-800",
+                div { class: "font-semibold", "Script stopped: {limit} limit exceeded" }
+                div { class: "mt-1 text-sm font-mono break-all", "{message}" }
+            }
+        },
+        UiContent::Error(e) => rsx! {
+            div {
+                class: "p-4 bg-red-50 border border-red-200 rounded-lg text-red-700",
+                "Error: {e}"
+            }
+        },
+        _ => rsx! {},
+    }
+}
+
 /// UI session state for tracking active sessions
 #[derive(Debug, Clone, PartialEq)]
 pub struct UiSessionState {
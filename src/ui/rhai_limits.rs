@@ -0,0 +1,249 @@
+//! Rhai Script Limits
+//!
+//! Rhai scripts come from MCP servers and run on the UI thread, so every
+//! script engine is bounded: operations per evaluation, function call depth,
+//! expression nesting, string/array/map sizes, and wall-clock time. A script
+//! that breaches a limit is stopped and reported as
+//! [`UiContent::ScriptLimitExceeded`](crate::ui::UiContent::ScriptLimitExceeded).
+
+use rhai::{Engine, EvalAltResult, ParseError, ParseErrorType};
+use std::cell::Cell;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Environment variable overriding the operations per render or event handler
+pub const SCRIPT_MAX_OPERATIONS_ENV: &str = "MCP_RHAI_MAX_OPERATIONS";
+
+/// Environment variable overriding the script timeout, in milliseconds
+pub const SCRIPT_TIMEOUT_ENV: &str = "MCP_RHAI_TIMEOUT_MS";
+
+/// Operations between checks of the wall-clock timeout
+const TIMEOUT_CHECK_INTERVAL: u64 = 1024;
+
+thread_local! {
+    /// When the evaluation running now started
+    static STARTED: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Resource limits for Rhai UI scripts
+///
+/// Zero disables a limit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptLimits {
+    /// Operations per render or event handler
+    pub max_operations: u64,
+    /// Nested function calls
+    pub max_call_depth: usize,
+    /// Expression nesting at the top level
+    pub max_expr_depth: usize,
+    /// Expression nesting within functions
+    pub max_function_expr_depth: usize,
+    /// Bytes in a string
+    pub max_string_size: usize,
+    /// Items in an array
+    pub max_array_size: usize,
+    /// Entries in an object map
+    pub max_map_size: usize,
+    /// Wall-clock time per render or event handler
+    pub timeout: Duration,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 1_000_000,
+            max_call_depth: 32,
+            // Nested `el` calls make UI scripts deeper than most
+            max_expr_depth: 128,
+            max_function_expr_depth: 64,
+            max_string_size: 1024 * 1024,
+            max_array_size: 10_000,
+            max_map_size: 10_000,
+            timeout: Duration::from_millis(500),
+        }
+    }
+}
+
+impl ScriptLimits {
+    /// Default limits, with the operations and timeout taken from the environment when set
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok()?.parse().ok()
+        }
+
+        let defaults = Self::default();
+        Self {
+            max_operations: var(SCRIPT_MAX_OPERATIONS_ENV).unwrap_or(defaults.max_operations),
+            timeout: var(SCRIPT_TIMEOUT_ENV).map(Duration::from_millis).unwrap_or(defaults.timeout),
+            ..defaults
+        }
+    }
+
+    /// Apply the limits to an engine
+    ///
+    /// The timeout only applies to evaluations run through [`run_timed`].
+    pub fn apply(&self, engine: &mut Engine) {
+        engine.set_max_operations(self.max_operations);
+        engine.set_max_call_levels(self.max_call_depth);
+        engine.set_max_expr_depths(self.max_expr_depth, self.max_function_expr_depth);
+        engine.set_max_string_size(self.max_string_size);
+        engine.set_max_array_size(self.max_array_size);
+        engine.set_max_map_size(self.max_map_size);
+
+        let timeout = self.timeout;
+        if !timeout.is_zero() {
+            engine.on_progress(move |operations| {
+                if operations % TIMEOUT_CHECK_INTERVAL != 0 {
+                    return None;
+                }
+                let started = STARTED.get()?;
+                (started.elapsed() > timeout).then(|| ScriptLimit::Timeout.as_str().into())
+            });
+        }
+    }
+}
+
+/// Run an evaluation under the engine's wall-clock timeout
+pub fn run_timed<T>(eval: impl FnOnce() -> T) -> T {
+    let outer = STARTED.replace(Some(Instant::now()));
+    let result = eval();
+    STARTED.set(outer);
+    result
+}
+
+/// A resource limit a script can breach
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptLimit {
+    Operations,
+    CallDepth,
+    ExprDepth,
+    DataSize,
+    Timeout,
+}
+
+impl ScriptLimit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScriptLimit::Operations => "operations",
+            ScriptLimit::CallDepth => "call depth",
+            ScriptLimit::ExprDepth => "expression depth",
+            ScriptLimit::DataSize => "data size",
+            ScriptLimit::Timeout => "timeout",
+        }
+    }
+}
+
+impl fmt::Display for ScriptLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why a script failed to compile, render or handle an event
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptError {
+    /// The script breached a resource limit and was stopped
+    Limit { limit: ScriptLimit, message: String },
+    /// Any other script error
+    Script(String),
+}
+
+impl ScriptError {
+    /// The limit breached, if any
+    pub fn limit(&self) -> Option<ScriptLimit> {
+        match self {
+            ScriptError::Limit { limit, .. } => Some(*limit),
+            ScriptError::Script(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Limit { limit, message } => write!(f, "Script exceeded the {} limit: {}", limit, message),
+            ScriptError::Script(e) => f.write_str(e),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+impl From<String> for ScriptError {
+    fn from(e: String) -> Self {
+        ScriptError::Script(e)
+    }
+}
+
+impl From<ParseError> for ScriptError {
+    fn from(e: ParseError) -> Self {
+        let limit = match e.err_type() {
+            ParseErrorType::ExprTooDeep => Some(ScriptLimit::ExprDepth),
+            ParseErrorType::LiteralTooLarge(..) => Some(ScriptLimit::DataSize),
+            _ => None,
+        };
+        match limit {
+            Some(limit) => ScriptError::Limit { limit, message: e.to_string() },
+            None => ScriptError::Script(e.to_string()),
+        }
+    }
+}
+
+impl From<Box<EvalAltResult>> for ScriptError {
+    fn from(e: Box<EvalAltResult>) -> Self {
+        match breached_limit(&e) {
+            Some(limit) => ScriptError::Limit { limit, message: e.to_string() },
+            None => ScriptError::Script(e.to_string()),
+        }
+    }
+}
+
+/// Limit behind an evaluation error, looking through the function calls it passed up
+fn breached_limit(e: &EvalAltResult) -> Option<ScriptLimit> {
+    match e {
+        EvalAltResult::ErrorTooManyOperations(_) => Some(ScriptLimit::Operations),
+        EvalAltResult::ErrorStackOverflow(_) => Some(ScriptLimit::CallDepth),
+        EvalAltResult::ErrorDataTooLarge(..) => Some(ScriptLimit::DataSize),
+        EvalAltResult::ErrorTerminated(..) => Some(ScriptLimit::Timeout),
+        EvalAltResult::ErrorParsing(ParseErrorType::ExprTooDeep, _) => Some(ScriptLimit::ExprDepth),
+        EvalAltResult::ErrorInFunctionCall(_, _, inner, _) | EvalAltResult::ErrorInModule(_, inner, _) => {
+            breached_limit(inner)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(limits: ScriptLimits) -> Engine {
+        let mut engine = Engine::new();
+        limits.apply(&mut engine);
+        engine
+    }
+
+    fn breach(engine: &Engine, script: &str) -> Option<ScriptLimit> {
+        let error: ScriptError = match engine.compile(script) {
+            Ok(ast) => run_timed(|| engine.run_ast(&ast)).unwrap_err().into(),
+            Err(e) => e.into(),
+        };
+        error.limit()
+    }
+
+    #[test]
+    fn test_limit_breaches_are_classified() {
+        let limited = engine(ScriptLimits { max_operations: 10_000, max_string_size: 1024, ..Default::default() });
+        assert_eq!(breach(&limited, "loop {}"), Some(ScriptLimit::Operations));
+        assert_eq!(breach(&limited, "fn down(n) { down(n + 1) } down(0)"), Some(ScriptLimit::CallDepth));
+        assert_eq!(breach(&limited, r#"let s = "x"; loop { s += s; }"#), Some(ScriptLimit::DataSize));
+        let nested = format!("{}1{}", "(".repeat(200), ")".repeat(200));
+        assert_eq!(breach(&limited, &nested), Some(ScriptLimit::ExprDepth));
+        assert_eq!(breach(&limited, "undefined_function()"), None);
+
+        let timed = engine(ScriptLimits { max_operations: 0, timeout: Duration::from_millis(20), ..Default::default() });
+        assert_eq!(breach(&timed, "loop {}"), Some(ScriptLimit::Timeout));
+        // Outside `run_timed` there is no deadline to check
+        assert!(timed.run("let n = 0; while n < 100000 { n += 1; }").is_ok());
+    }
+}
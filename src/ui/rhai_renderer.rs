@@ -2,10 +2,27 @@
 //!
 //! Custom extension that renders UI using Rhai scripts instead of HTML.
 //! This is a non-standard extension but provides a native feel.
+//!
+//! Scripts are interactive through `on_click`, `on_input`, `on_change` and
+//! `on_submit` props. A handler is either the name of a script function,
+//! called with `this` bound to the view's `state` map, or a closure, which
+//! sees `state` like the rest of the script. Script functions cannot see
+//! `data`, so handlers that need the tool data are closures. Handlers may take the event
+//! value (an input's text, a form's fields) as their one parameter, and may
+//! call `call_tool(name, args)` or `call_tool(name, args, on_result)` to call
+//! a tool on the view's server. The view re-renders after every handler.
+//!
+//! Scripts run within [`ScriptLimits`], without `eval`, modules or any
+//! filesystem access.
 
 use dioxus::prelude::*;
-use rhai::{Engine, Scope, Map, Array, Dynamic};
-use std::collections::HashMap;
+use crate::ui::{run_timed, ScriptError, ScriptLimits, UiContent, ContentError};
+use rhai::{Engine, Scope, Map, Array, Dynamic, CallFnOptions, FnPtr, AST};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
 
 /// UI node types for Rhai rendering
 #[derive(Clone, Debug, PartialEq)]
@@ -13,11 +30,41 @@ pub enum UiNode {
     Element {
         tag: String,
         props: HashMap<String, String>,
+        /// Event handlers by prop name, such as `on_click`
+        handlers: HashMap<String, Handler>,
         children: Vec<UiNode>,
     },
     Text(String),
 }
 
+/// Script code run when a UI event fires
+#[derive(Clone, Debug)]
+pub enum Handler {
+    /// Script function, called with `this` bound to `state`
+    Named(String),
+    /// Closure, which captures `state` and other variables itself
+    Closure(FnPtr),
+}
+
+impl Handler {
+    fn from_dynamic(value: Dynamic) -> Option<Self> {
+        if value.is_fnptr() {
+            return value.try_cast::<FnPtr>().map(Handler::Closure);
+        }
+        value.into_string().ok().map(Handler::Named)
+    }
+}
+
+impl PartialEq for Handler {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Handler::Named(a), Handler::Named(b)) => a == b,
+            // Closures of the same definition may capture different values
+            _ => false,
+        }
+    }
+}
+
 impl UiNode {
     pub fn from_dynamic(val: Dynamic) -> Result<Self, String> {
         if val.is_string() {
@@ -39,8 +86,15 @@ impl UiNode {
             .ok_or("props must be map")?;
 
         let mut props = HashMap::new();
+        let mut handlers = HashMap::new();
         for (k, v) in props_dyn {
-            props.insert(k.into(), v.to_string());
+            if k.starts_with("on_") {
+                let handler = Handler::from_dynamic(v)
+                    .ok_or_else(|| format!("{} must be a function name or closure", k))?;
+                handlers.insert(k.into(), handler);
+            } else {
+                props.insert(k.into(), v.to_string());
+            }
         }
 
         let children_dyn = map.get("children")
@@ -54,16 +108,21 @@ impl UiNode {
             children.push(UiNode::from_dynamic(child)?);
         }
 
-        Ok(UiNode::Element { tag, props, children })
+        Ok(UiNode::Element { tag, props, handlers, children })
     }
 }
 
-pub fn create_rhai_engine() -> Engine {
+/// Engine for view scripts: the UI functions and `call_tool` within `limits`,
+/// without `eval` or modules, and with `print` and `debug` going to the host log
+pub fn create_rhai_engine(limits: &ScriptLimits) -> Engine {
     let mut engine = Engine::new();
+    limits.apply(&mut engine);
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.on_print(|s| log::debug!("[Rhai] {}", s));
+    engine.on_debug(|s, _, pos| log::debug!("[Rhai] {} {}", pos, s));
+    register_tool_calls(&mut engine);
 
-    // Increase limits for complex UI structures
-    engine.set_max_expr_depths(0, 0); // Disable depth limits
-    
     engine.register_fn("el", |tag: &str, props: Map, children: Array| -> Map {
         let mut map = Map::new();
         map.insert("tag".into(), tag.into());
@@ -83,34 +142,411 @@ pub fn create_rhai_engine() -> Engine {
     engine
 }
 
+/// Theme tokens for scripts: `theme.name` plus Tailwind classes such as `theme.bg`
+fn theme_map(theme: &str) -> Map {
+    let mut map = Map::new();
+    map.insert("name".into(), theme.into());
+    for (token, classes) in crate::host::theme_tokens(theme) {
+        map.insert((*token).into(), (*classes).into());
+    }
+    map
+}
+
+/// A tool call a script made from an event handler
+#[derive(Debug, Clone)]
+pub struct ScriptToolCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+    /// Handler given the tool's result
+    pub on_result: Option<Handler>,
+}
+
+/// Compiled scripts kept per thread
+pub const MAX_CACHED_SCRIPTS: usize = 64;
+
+thread_local! {
+    /// Engine shared by every script rendered on this thread
+    static ENGINE: RefCell<Rc<Engine>> = RefCell::new(Rc::new(create_rhai_engine(&ScriptLimits::default())));
+    /// Compiled scripts by SHA-256 of their source
+    static AST_CACHE: RefCell<AstCache> = RefCell::default();
+    /// Tool calls made by the script running now
+    static TOOL_CALLS: RefCell<Vec<ScriptToolCall>> = RefCell::default();
+}
+
+/// Compiled scripts, evicting the least recently used beyond [`MAX_CACHED_SCRIPTS`]
+#[derive(Default)]
+struct AstCache {
+    asts: HashMap<[u8; 32], Rc<AST>>,
+    /// Keys from least to most recently used
+    recency: VecDeque<[u8; 32]>,
+}
+
+impl AstCache {
+    fn get(&mut self, key: &[u8; 32]) -> Option<Rc<AST>> {
+        let ast = self.asts.get(key).cloned()?;
+        self.touch(key);
+        Some(ast)
+    }
+
+    fn insert(&mut self, key: [u8; 32], ast: Rc<AST>) {
+        if self.asts.insert(key, ast).is_some() {
+            self.touch(&key);
+            return;
+        }
+        self.recency.push_back(key);
+        while self.asts.len() > MAX_CACHED_SCRIPTS {
+            let Some(evicted) = self.recency.pop_front() else { break };
+            self.asts.remove(&evicted);
+        }
+    }
+
+    fn touch(&mut self, key: &[u8; 32]) {
+        if let Some(i) = self.recency.iter().position(|k| k == key) {
+            self.recency.remove(i);
+            self.recency.push_back(*key);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.asts.clear();
+        self.recency.clear();
+    }
+}
+
+/// Register `call_tool`, which queues calls for the host to make
+fn register_tool_calls(engine: &mut Engine) {
+    fn queue(name: &str, args: Map, on_result: Option<Handler>) -> Result<(), Box<rhai::EvalAltResult>> {
+        let arguments = rhai::serde::from_dynamic::<serde_json::Value>(&Dynamic::from_map(args))?;
+        TOOL_CALLS.with_borrow_mut(|calls| calls.push(ScriptToolCall { name: name.to_string(), arguments, on_result }));
+        Ok(())
+    }
+
+    engine.register_fn("call_tool", |name: &str, args: Map| queue(name, args, None));
+    engine.register_fn("call_tool", |name: &str, args: Map, on_result: Dynamic| {
+        let handler = Handler::from_dynamic(on_result).ok_or("on_result must be a function name or closure")?;
+        queue(name, args, Some(handler))
+    });
+}
+
+/// Set the limits for scripts rendered on this thread from now on
+///
+/// Scripts already running keep their limits until they are reloaded.
+pub fn set_script_limits(limits: &ScriptLimits) {
+    ENGINE.set(Rc::new(create_rhai_engine(limits)));
+    // Expression depth is checked when compiling
+    AST_CACHE.with_borrow_mut(|cache| cache.clear());
+}
+
+/// Compile a script, or reuse its AST if the same source was compiled before
+fn compile_cached(engine: &Engine, script: &str) -> Result<Rc<AST>, ScriptError> {
+    use sha2::{Digest, Sha256};
+
+    let key: [u8; 32] = Sha256::digest(script.as_bytes()).into();
+    if let Some(ast) = AST_CACHE.with_borrow_mut(|cache| cache.get(&key)) {
+        return Ok(ast);
+    }
+    let ast = Rc::new(engine.compile(script)?);
+    AST_CACHE.with_borrow_mut(|cache| cache.insert(key, ast.clone()));
+    Ok(ast)
+}
+
+/// What a render was evaluated from
+#[derive(PartialEq)]
+struct RenderInputs {
+    context: String,
+    theme: String,
+    /// Handlers run before the render
+    generation: u64,
+}
+
+/// A compiled script and the state its handlers share
+///
+/// Scripts run on a shared engine and their ASTs are cached by content, so
+/// a runtime is cheap to create. The script is evaluated again, with a fresh
+/// scope, only when the context, theme or state changes.
+pub struct ScriptRuntime {
+    engine: Rc<Engine>,
+    ast: Rc<AST>,
+    /// The `state` map, shared with closures that capture it
+    state: Dynamic,
+    generation: Cell<u64>,
+    last_render: RefCell<Option<(RenderInputs, UiNode)>>,
+}
+
+impl ScriptRuntime {
+    pub fn new(script: &str) -> Result<Self, ScriptError> {
+        let engine = ENGINE.with_borrow(Rc::clone);
+        let ast = compile_cached(&engine, script)?;
+        Ok(Self {
+            engine,
+            ast,
+            state: Dynamic::from_map(Map::new()).into_shared(),
+            generation: Cell::new(0),
+            last_render: RefCell::new(None),
+        })
+    }
+
+    /// Current `state` map
+    pub fn state(&self) -> Map {
+        self.state.flatten_clone().try_cast::<Map>().unwrap_or_default()
+    }
+
+    /// Evaluate the script with the tool data as `data`
+    pub fn render(&self, context: &str, theme: &str) -> Result<UiNode, ScriptError> {
+        let inputs = RenderInputs {
+            context: context.to_string(),
+            theme: theme.to_string(),
+            generation: self.generation.get(),
+        };
+        if let Some((last, node)) = self.last_render.borrow().as_ref() {
+            if *last == inputs {
+                return Ok(node.clone());
+            }
+        }
+
+        let mut scope = Scope::new();
+        if let Ok(ctx_val) = serde_json::from_str::<serde_json::Value>(context) {
+            scope.push("data", rhai::serde::to_dynamic(&ctx_val).unwrap_or(Dynamic::UNIT));
+        }
+        scope.push("theme", theme_map(theme));
+        scope.push_dynamic("state", self.state.clone());
+
+        let result = run_timed(|| self.engine.eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast));
+        let ignored = TOOL_CALLS.take();
+        if !ignored.is_empty() {
+            log::warn!("Ignored {} tool calls made while rendering; call tools from event handlers", ignored.len());
+        }
+        let node = UiNode::from_dynamic(result?)?;
+        *self.last_render.borrow_mut() = Some((inputs, node.clone()));
+        Ok(node)
+    }
+
+    /// Run an event handler
+    ///
+    /// Returns the tool calls the handler made.
+    pub fn dispatch(&self, handler: &Handler, event: Dynamic) -> Result<Vec<ScriptToolCall>, ScriptError> {
+        TOOL_CALLS.take();
+        let result = run_timed(|| match handler {
+            Handler::Named(name) => self.call_named(name, event),
+            Handler::Closure(f) => {
+                let params = self.ast.iter_functions().find(|d| d.name == f.fn_name()).map(|d| d.params.len());
+                // Captured variables are passed ahead of the closure's own parameters
+                if params.is_some_and(|n| n > f.curry().len()) {
+                    f.call::<Dynamic>(&self.engine, &self.ast, (event,))
+                } else {
+                    f.call::<Dynamic>(&self.engine, &self.ast, ())
+                }
+                .map_err(ScriptError::from)
+            }
+        });
+        // A failed handler may still have changed state
+        self.generation.set(self.generation.get() + 1);
+        let calls = TOOL_CALLS.take();
+        result.map(|_| calls)
+    }
+
+    fn call_named(&self, name: &str, event: Dynamic) -> Result<Dynamic, ScriptError> {
+        let takes_event = self.ast.iter_functions().any(|d| d.name == name && d.params.len() == 1);
+        let mut this = self.state.flatten_clone();
+        let mut scope = Scope::new();
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut this);
+        let result = if takes_event {
+            self.engine.call_fn_with_options::<Dynamic>(options, &mut scope, &self.ast, name, (event,))
+        } else {
+            self.engine.call_fn_with_options::<Dynamic>(options, &mut scope, &self.ast, name, ())
+        }?;
+
+        let state = this.try_cast::<Map>().ok_or_else(|| format!("{} must leave state a map", name))?;
+        // Clones of a shared value write through to it
+        let mut shared = self.state.clone();
+        if let Some(mut shared) = shared.write_lock::<Map>() {
+            *shared = state;
+        }
+        Ok(result)
+    }
+}
+
+type ToolFuture = Pin<Box<dyn Future<Output = Result<serde_json::Value, String>>>>;
+
+/// Calls tools on the view's server for natively rendered content
+#[derive(Clone)]
+pub struct ToolCaller(Rc<dyn Fn(String, serde_json::Value) -> ToolFuture>);
+
+impl ToolCaller {
+    pub fn new<F, Fut>(call: F) -> Self
+    where
+        F: Fn(String, serde_json::Value) -> Fut + 'static,
+        Fut: Future<Output = Result<serde_json::Value, String>> + 'static,
+    {
+        Self(Rc::new(move |name, arguments| Box::pin(call(name, arguments))))
+    }
+
+    /// Call a tool, resolving with its `CallToolResult` as JSON
+    pub async fn call(&self, name: String, arguments: serde_json::Value) -> Result<serde_json::Value, String> {
+        (self.0)(name, arguments).await
+    }
+}
+
+impl PartialEq for ToolCaller {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl std::fmt::Debug for ToolCaller {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolCaller").finish_non_exhaustive()
+    }
+}
+
+/// Script source and the runtime compiled from it
+type CompiledScript = Option<(String, Rc<ScriptRuntime>)>;
+
+/// A rendered script, shared with the event handlers of its nodes
+#[derive(Clone)]
+struct ScriptHandle {
+    runtime: Rc<RefCell<CompiledScript>>,
+    tool_caller: Rc<RefCell<Option<ToolCaller>>>,
+    /// Limit a handler breached, which stops the script until it is reloaded
+    stopped: Rc<RefCell<Option<ScriptError>>>,
+    /// Bumped to re-render after handlers change state
+    revision: Signal<u64>,
+}
+
+impl ScriptHandle {
+    /// Runtime for `script`, created anew (with fresh state) when the script changes
+    fn runtime_for(&self, script: &str) -> Result<Rc<ScriptRuntime>, ScriptError> {
+        let mut current = self.runtime.borrow_mut();
+        if let Some((source, runtime)) = current.as_ref() {
+            if source == script {
+                return Ok(runtime.clone());
+            }
+        }
+        self.stopped.take();
+        let runtime = Rc::new(ScriptRuntime::new(script)?);
+        *current = Some((script.to_string(), runtime.clone()));
+        Ok(runtime)
+    }
+
+    /// Run a handler, re-render, and make the tool calls it asked for
+    fn run(&self, handler: &Handler, event: Dynamic) {
+        let Some(runtime) = self.runtime.borrow().as_ref().map(|(_, r)| r.clone()) else {
+            return;
+        };
+        match runtime.dispatch(handler, event) {
+            Ok(calls) => {
+                for call in calls {
+                    self.call_tool(call);
+                }
+            }
+            Err(e) => {
+                log::warn!("Rhai event handler failed: {}", e);
+                if e.limit().is_some() {
+                    *self.stopped.borrow_mut() = Some(e);
+                }
+            }
+        }
+        let mut revision = self.revision;
+        *revision.write() += 1;
+    }
+
+    /// Call a tool and pass the result, or an error result, to the script
+    fn call_tool(&self, call: ScriptToolCall) {
+        let caller = self.tool_caller.borrow().clone();
+        let handle = self.clone();
+        spawn(async move {
+            let result = match caller {
+                Some(caller) => caller.call(call.name.clone(), call.arguments).await,
+                None => Err("This view cannot call tools".to_string()),
+            };
+            let result = result.unwrap_or_else(|e| {
+                log::warn!("Rhai view tool call {} failed: {}", call.name, e);
+                serde_json::json!({ "content": [{ "type": "text", "text": e }], "isError": true })
+            });
+            if let Some(on_result) = &call.on_result {
+                handle.run(on_result, rhai::serde::to_dynamic(&result).unwrap_or(Dynamic::UNIT));
+            }
+        });
+    }
+}
+
 #[component]
-pub fn RhaiRenderer(script: String, context: String) -> Element {
+pub fn RhaiRenderer(script: String, context: String, theme: String, tool_caller: Option<ToolCaller>) -> Element {
     // We evaluate the Rhai script directly in the render pass.
     // Since the script and context are passed as props, Dioxus will re-run this function
-    // whenever they change. We avoid use_memo here because Dioxus 0.7+ memoization
-    // primarily tracks signals, and we want to ensure we always render the latest props.
-    // The Rhai engine creation and parsing is fast enough for UI updates.
-    
-    let engine = create_rhai_engine();
-    let mut scope = Scope::new();
-
-    // Parse context JSON and add to scope
-    if let Ok(ctx_val) = serde_json::from_str::<serde_json::Value>(&context) {
-            let dynamic_ctx = rhai::serde::to_dynamic(&ctx_val).unwrap_or(Dynamic::UNIT);
-            scope.push("data", dynamic_ctx);
-    }
+    // whenever they change. The runtime keeps the script's state, which event handlers
+    // change before bumping `revision` to render again, and skips evaluating the script
+    // when neither the context nor the state changed.
+    let revision = use_signal(|| 0u64);
+    let handle = use_context_provider(|| ScriptHandle {
+        runtime: Rc::default(),
+        tool_caller: Rc::default(),
+        stopped: Rc::default(),
+        revision,
+    });
+    *handle.tool_caller.borrow_mut() = tool_caller;
+    revision.read();
 
-    let result = match engine.eval_with_scope::<Dynamic>(&mut scope, &script) {
-        Ok(result) => UiNode::from_dynamic(result),
-        Err(e) => Err(e.to_string()),
-    };
+    let result = handle.runtime_for(&script).and_then(|runtime| match handle.stopped.borrow().clone() {
+        Some(e) => Err(e),
+        None => runtime.render(&context, &theme),
+    });
 
     match result {
         Ok(root) => rsx! { RenderUiNode { node: root } },
-        Err(e) => rsx! {
-            div {
-                class: "text-red-500 p-4 border border-red-500 rounded bg-red-50",
-                "Error rendering Rhai UI: {e}"
+        Err(ScriptError::Script(e)) => rsx! {
+            ContentError { content: UiContent::Error(format!("Error rendering Rhai UI: {}", e)) }
+        },
+        Err(e) => {
+            log::warn!("Rhai UI stopped: {}", e);
+            rsx! { ContentError { content: e.into() } }
+        }
+    }
+}
+
+/// Event listeners for a node's handlers
+struct NodeEvents {
+    script: Option<ScriptHandle>,
+    handlers: HashMap<String, Handler>,
+}
+
+impl NodeEvents {
+    fn handler(&self, name: &str) -> Option<(ScriptHandle, Handler)> {
+        self.script.clone().zip(self.handlers.get(name).cloned())
+    }
+
+    fn on_click(&self) -> impl FnMut(MouseEvent) + 'static {
+        let handler = self.handler("on_click");
+        move |_| {
+            if let Some((script, handler)) = &handler {
+                script.run(handler, Dynamic::UNIT);
+            }
+        }
+    }
+
+    /// Listener passing the element's value, for `on_input` and `on_change`
+    fn on_value(&self, name: &str) -> impl FnMut(FormEvent) + 'static {
+        let handler = self.handler(name);
+        move |event| {
+            if let Some((script, handler)) = &handler {
+                script.run(handler, event.value().into());
+            }
+        }
+    }
+
+    /// Listener passing the form's text fields by name
+    fn on_submit(&self) -> impl FnMut(FormEvent) + 'static {
+        let handler = self.handler("on_submit");
+        move |event| {
+            event.prevent_default();
+            if let Some((script, handler)) = &handler {
+                let mut fields = Map::new();
+                for (name, value) in event.values() {
+                    if let FormValue::Text(text) = value {
+                        fields.insert(name.into(), text.into());
+                    }
+                }
+                script.run(handler, fields.into());
             }
         }
     }
@@ -118,61 +554,69 @@ pub fn RhaiRenderer(script: String, context: String) -> Element {
 
 #[component]
 fn RenderUiNode(node: UiNode) -> Element {
+    let script = try_use_context::<ScriptHandle>();
+
     match node {
         UiNode::Text(t) => rsx! { "{t}" },
-        UiNode::Element { tag, props, children } => {
+        UiNode::Element { tag, props, handlers, children } => {
             let class = props.get("class").cloned().unwrap_or_default();
+            let disabled = props.get("disabled").is_some_and(|d| d == "true");
+            let events = NodeEvents { script: script.clone(), handlers };
             
             match tag.as_str() {
                 "div" => rsx! {
-                    div { class: "{class}",
+                    div { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "span" => rsx! {
-                    span { class: "{class}",
+                    span { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "h1" => rsx! {
-                    h1 { class: "{class}",
+                    h1 { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "h2" => rsx! {
-                    h2 { class: "{class}",
+                    h2 { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "h3" => rsx! {
-                    h3 { class: "{class}",
+                    h3 { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "p" => rsx! {
-                    p { class: "{class}",
+                    p { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "button" => rsx! {
-                    button { class: "{class}",
+                    button {
+                        class: "{class}",
+                        disabled,
+                        onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "a" => rsx! {
-                    a { 
+                    a {
                         class: "{class}",
+                        onclick: events.on_click(),
                         href: props.get("href").cloned().unwrap_or_default(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "ul" => rsx! {
-                    ul { class: "{class}",
+                    ul { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "li" => rsx! {
-                    li { class: "{class}",
+                    li { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
@@ -183,67 +627,85 @@ fn RenderUiNode(node: UiNode) -> Element {
                         alt: props.get("alt").cloned().unwrap_or_default(),
                     }
                 },
-                 "input" => rsx! {
+                "input" => rsx! {
                     input {
                         class: "{class}",
                         value: props.get("value").cloned().unwrap_or_default(),
                         r#type: props.get("type").cloned().unwrap_or("text".to_string()),
+                        name: props.get("name").cloned().unwrap_or_default(),
+                        placeholder: props.get("placeholder").cloned().unwrap_or_default(),
+                        disabled,
+                        onclick: events.on_click(),
+                        oninput: events.on_value("on_input"),
+                        onchange: events.on_value("on_change"),
                     }
                 },
                 "label" => rsx! {
-                    label { class: "{class}",
+                    label { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "form" => rsx! {
-                    form { class: "{class}",
+                    form {
+                        class: "{class}",
+                        onsubmit: events.on_submit(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "textarea" => rsx! {
-                    textarea { class: "{class}",
+                    textarea {
+                        class: "{class}",
+                        name: props.get("name").cloned().unwrap_or_default(),
+                        disabled,
+                        oninput: events.on_value("on_input"),
+                        onchange: events.on_value("on_change"),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "select" => rsx! {
-                    select { class: "{class}",
+                    select {
+                        class: "{class}",
+                        name: props.get("name").cloned().unwrap_or_default(),
+                        disabled,
+                        oninput: events.on_value("on_input"),
+                        onchange: events.on_value("on_change"),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "option" => rsx! {
-                    option { 
+                    option {
                         class: "{class}",
                         value: props.get("value").cloned().unwrap_or_default(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "table" => rsx! {
-                    table { class: "{class}",
+                    table { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "thead" => rsx! {
-                    thead { class: "{class}",
+                    thead { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "tbody" => rsx! {
-                    tbody { class: "{class}",
+                    tbody { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "tr" => rsx! {
-                    tr { class: "{class}",
+                    tr { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "th" => rsx! {
-                    th { class: "{class}",
+                    th { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
                 "td" => rsx! {
-                    td { class: "{class}",
+                    td { class: "{class}", onclick: events.on_click(),
                         {children.into_iter().map(|child| rsx! { RenderUiNode { node: child } })}
                     }
                 },
@@ -262,7 +724,7 @@ mod tests {
 
     #[test]
     fn test_rhai_script_rendering() {
-        let engine = create_rhai_engine();
+        let engine = create_rhai_engine(&ScriptLimits::default());
         let mut scope = Scope::new();
         let script = r#"
             el("div", #{ "class": "container" }, [
@@ -274,7 +736,7 @@ mod tests {
         let ui_node = UiNode::from_dynamic(result).unwrap();
 
         match ui_node {
-            UiNode::Element { tag, props, children } => {
+            UiNode::Element { tag, props, children, .. } => {
                 assert_eq!(tag, "div");
                 assert_eq!(props.get("class").unwrap(), "container");
                 assert_eq!(children.len(), 1);
@@ -294,4 +756,164 @@ mod tests {
             _ => panic!("Expected div"),
         }
     }
+
+    #[test]
+    fn test_theme_tokens_in_scope() {
+        let engine = create_rhai_engine(&ScriptLimits::default());
+        let mut scope = Scope::new();
+        scope.push("theme", theme_map("dark"));
+
+        let result = engine
+            .eval_with_scope::<Dynamic>(&mut scope, r#"el("div", #{ "class": theme.bg + " " + theme.text }, [])"#)
+            .unwrap();
+        match UiNode::from_dynamic(result).unwrap() {
+            UiNode::Element { props, .. } => assert_eq!(props.get("class").unwrap(), "bg-gray-900 text-gray-100"),
+            _ => panic!("Expected element"),
+        }
+        assert_eq!(scope.get_value::<Map>("theme").unwrap()["name"].to_string(), "dark");
+    }
+
+    /// Handlers of the first node, then of its first child, and so on
+    fn handler(node: &UiNode, name: &str) -> Handler {
+        match node {
+            UiNode::Element { handlers, children, .. } => match handlers.get(name) {
+                Some(handler) => handler.clone(),
+                None => handler(&children[0], name),
+            },
+            UiNode::Text(_) => panic!("No {} handler", name),
+        }
+    }
+
+    fn first_text(node: &UiNode) -> String {
+        match node {
+            UiNode::Element { children, .. } => first_text(&children[0]),
+            UiNode::Text(t) => t.clone(),
+        }
+    }
+
+    #[test]
+    fn test_named_handler_updates_state() {
+        let runtime = ScriptRuntime::new(r#"
+            fn increment() { this.count = (this.count ?? 0) + 1; }
+            el("button", #{ "on_click": "increment" }, [ text(state.count ?? 0) ])
+        "#).unwrap();
+
+        let node = runtime.render("{}", "light").unwrap();
+        assert_eq!(first_text(&node), "0");
+        let on_click = handler(&node, "on_click");
+        assert_eq!(on_click, Handler::Named("increment".into()));
+
+        assert!(runtime.dispatch(&on_click, Dynamic::UNIT).unwrap().is_empty());
+        runtime.dispatch(&on_click, Dynamic::UNIT).unwrap();
+        assert_eq!(runtime.state()["count"].as_int().unwrap(), 2);
+        assert_eq!(first_text(&runtime.render("{}", "light").unwrap()), "2");
+    }
+
+    #[test]
+    fn test_closure_handler_receives_event() {
+        let runtime = ScriptRuntime::new(r#"
+            el("div", #{}, [
+                el("input", #{ "on_input": |value| state.query = value }, []),
+                text(state.query ?? ""),
+            ])
+        "#).unwrap();
+
+        let node = runtime.render("{}", "light").unwrap();
+        assert!(matches!(handler(&node, "on_input"), Handler::Closure(_)));
+        runtime.dispatch(&handler(&node, "on_input"), "rain".into()).unwrap();
+        assert_eq!(runtime.state()["query"].to_string(), "rain");
+
+        assert!(ScriptRuntime::new(r#"el("div", #{ "on_click": 42 }, [])"#).unwrap().render("{}", "light").is_err());
+    }
+
+    #[test]
+    fn test_handlers_queue_tool_calls() {
+        let runtime = ScriptRuntime::new(r#"
+            fn show(result) { this.forecast = result.structuredContent.summary; }
+            let refresh = || call_tool("get_weather", #{ "location": data.location }, "show");
+            el("button", #{ "on_click": refresh }, [ text(state.forecast ?? "...") ])
+        "#).unwrap();
+
+        let node = runtime.render(r#"{ "location": "Oslo" }"#, "light").unwrap();
+        let calls = runtime.dispatch(&handler(&node, "on_click"), Dynamic::UNIT).unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "get_weather");
+        assert_eq!(calls[0].arguments, serde_json::json!({ "location": "Oslo" }));
+        assert_eq!(calls[0].on_result, Some(Handler::Named("show".into())));
+
+        let result = rhai::serde::to_dynamic(serde_json::json!({ "structuredContent": { "summary": "Snow" } })).unwrap();
+        runtime.dispatch(calls[0].on_result.as_ref().unwrap(), result).unwrap();
+        assert_eq!(first_text(&runtime.render(r#"{ "location": "Oslo" }"#, "light").unwrap()), "Snow");
+    }
+
+    #[test]
+    fn test_scripts_are_compiled_and_evaluated_once() {
+        let script = r#"
+            state.renders = (state.renders ?? 0) + 1;
+            el("button", #{ "on_click": || () }, [ text(data.n) ])
+        "#;
+        let first = ScriptRuntime::new(script).unwrap();
+        let second = ScriptRuntime::new(script).unwrap();
+        assert!(Rc::ptr_eq(&first.ast, &second.ast));
+        assert!(Rc::ptr_eq(&first.engine, &second.engine));
+
+        let renders = |runtime: &ScriptRuntime| runtime.state()["renders"].as_int().unwrap();
+        let node = first.render(r#"{ "n": 1 }"#, "light").unwrap();
+        first.render(r#"{ "n": 1 }"#, "light").unwrap();
+        assert_eq!(renders(&first), 1);
+        first.render(r#"{ "n": 2 }"#, "light").unwrap();
+        assert_eq!(renders(&first), 2);
+        // Handlers may change state, so the script is evaluated again
+        first.dispatch(&handler(&node, "on_click"), Dynamic::UNIT).unwrap();
+        first.render(r#"{ "n": 2 }"#, "light").unwrap();
+        assert_eq!(renders(&first), 3);
+        // State is per runtime
+        second.render(r#"{ "n": 2 }"#, "light").unwrap();
+        assert_eq!(renders(&second), 1);
+
+        assert!(ScriptRuntime::new(r#"eval("1")"#).is_err());
+    }
+
+    #[test]
+    fn test_least_recently_used_scripts_are_evicted() {
+        let script = |n: usize| format!(r#"el("p", #{{}}, [ text({n}) ])"#);
+        let runtimes: Vec<_> = (0..MAX_CACHED_SCRIPTS).map(|n| ScriptRuntime::new(&script(n)).unwrap()).collect();
+
+        // Reusing the oldest script keeps it over the next oldest
+        assert!(Rc::ptr_eq(&runtimes[0].ast, &ScriptRuntime::new(&script(0)).unwrap().ast));
+        ScriptRuntime::new(&script(MAX_CACHED_SCRIPTS)).unwrap();
+        assert!(Rc::ptr_eq(&runtimes[0].ast, &ScriptRuntime::new(&script(0)).unwrap().ast));
+        assert!(Rc::ptr_eq(&runtimes[2].ast, &ScriptRuntime::new(&script(2)).unwrap().ast));
+        assert!(!Rc::ptr_eq(&runtimes[1].ast, &ScriptRuntime::new(&script(1)).unwrap().ast));
+    }
+
+    #[tokio::test]
+    async fn test_scripts_are_sandboxed() {
+        let server = crate::server::EmbeddedServer::new();
+        for uri in ["ui://weather-server/dashboard", "ui://portfolio-server/gallery", "ui://system-server/status", "ui://notes-server/editor"] {
+            let script = match &server.read_resource(uri).await.unwrap().contents[0] {
+                rmcp::model::ResourceContents::TextResourceContents { text, .. } => text.clone(),
+                _ => panic!("Expected a text resource"),
+            };
+            assert!(ScriptRuntime::new(&script).unwrap().render("{}", "light").is_ok(), "{uri} breached the default limits");
+        }
+
+        let error = ScriptRuntime::new(r#"import "secrets" as s; el("div", #{}, [])"#).unwrap().render("{}", "light").unwrap_err();
+        assert_eq!(error.limit(), None);
+        // Engines made outside the shared runtime are sandboxed the same way
+        let engine = create_rhai_engine(&ScriptLimits::default());
+        assert!(engine.compile(r#"eval("1")"#).is_err());
+        assert!(engine.eval::<Dynamic>(r#"import "secrets" as s; 1"#).is_err());
+
+        let runaway = ScriptRuntime::new(r#"
+            fn spin() { loop {} }
+            el("button", #{ "on_click": "spin" }, [])
+        "#).unwrap();
+        let node = runaway.render("{}", "light").unwrap();
+        let error = runaway.dispatch(&handler(&node, "on_click"), Dynamic::UNIT).unwrap_err();
+        assert!(matches!(
+            UiContent::from(error),
+            UiContent::ScriptLimitExceeded { limit: crate::ui::ScriptLimit::Operations, .. }
+        ));
+    }
 }
@@ -0,0 +1,265 @@
+//! Sandbox Proxy
+//!
+//! Implements the double-iframe architecture recommended by the MCP Apps spec
+//! for web hosts. The host embeds an outer proxy page served from a separate
+//! origin; the proxy announces `ui/notifications/sandbox-proxy-ready`, receives
+//! the view HTML (with the host's CSP already in it) and permissions in
+//! `ui/notifications/sandbox-resource-ready`, builds the inner sandboxed iframe
+//! and relays messages in both directions.
+//!
+//! This module has no dependencies on the rest of the crate so the standalone
+//! `sandbox-proxy` binary can include it directly.
+
+/// Address the proxy server listens on by default
+pub const DEFAULT_SANDBOX_PROXY_ADDR: &str = "127.0.0.1:8765";
+
+/// Origin the host expects the proxy at unless `MCP_SANDBOX_PROXY_ORIGIN` is set at build time
+pub const DEFAULT_SANDBOX_PROXY_ORIGIN: &str = "http://127.0.0.1:8765";
+
+/// Path the proxy page is served from
+pub const SANDBOX_PROXY_PATH: &str = "/sandbox";
+
+/// Sandbox flags for the outer proxy iframe
+///
+/// The proxy keeps its own (separate) origin so the host can verify
+/// `event.origin` on messages it relays.
+pub const PROXY_SANDBOX: &str = "allow-scripts allow-same-origin";
+
+/// Origin of the sandbox proxy the host should embed
+pub fn sandbox_proxy_origin() -> &'static str {
+    option_env!("MCP_SANDBOX_PROXY_ORIGIN").unwrap_or(DEFAULT_SANDBOX_PROXY_ORIGIN)
+}
+
+/// The outer proxy page
+///
+/// Messages from the host are only accepted from the parent window at the
+/// embedding origin, and messages from the view only from the inner frame.
+pub const PROXY_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>MCP App Sandbox</title>
+<style>
+html, body, iframe { margin: 0; padding: 0; border: 0; width: 100%; height: 100%; overflow: hidden; }
+</style>
+</head>
+<body>
+<script>
+(function() {
+    'use strict';
+
+    // Origin of the embedding host
+    const hostOrigin = (location.ancestorOrigins && location.ancestorOrigins.length > 0)
+        ? location.ancestorOrigins[0]
+        : (document.referrer ? new URL(document.referrer).origin : null);
+
+    let inner = null;
+    // Target origin for messages to the view
+    let innerOrigin = '*';
+
+    function toHost(message) {
+        if (hostOrigin) {
+            window.parent.postMessage(message, hostOrigin);
+        }
+    }
+
+    function allowAttribute(permissions) {
+        permissions = permissions || {};
+        const allow = [];
+        if (permissions.camera) allow.push('camera');
+        if (permissions.microphone) allow.push('microphone');
+        if (permissions.geolocation) allow.push('geolocation');
+        if (permissions.clipboardWrite) allow.push('clipboard-write');
+        return allow.join('; ');
+    }
+
+    function load(params) {
+        if (inner) {
+            inner.remove();
+        }
+        inner = document.createElement('iframe');
+        inner.setAttribute('sandbox', params.sandbox || 'allow-scripts');
+        const allow = allowAttribute(params.permissions);
+        if (allow) {
+            inner.setAttribute('allow', allow);
+        }
+        // The host already put the view's CSP in the document; `params.csp` is
+        // informational, and applying it again would stack a second policy
+        inner.srcdoc = params.html || '';
+        // Without allow-same-origin the view has an opaque origin, which only '*' matches
+        const sandbox = (params.sandbox || '').split(/\s+/);
+        innerOrigin = sandbox.indexOf('allow-same-origin') >= 0 ? location.origin : '*';
+        document.body.appendChild(inner);
+    }
+
+    window.addEventListener('message', function(event) {
+        const data = event.data;
+        if (!data || typeof data !== 'object' || data.jsonrpc !== '2.0') return;
+
+        if (event.source === window.parent) {
+            if (event.origin !== hostOrigin) return;
+            if (data.method === 'ui/notifications/sandbox-resource-ready') {
+                load(data.params || {});
+            } else if (inner) {
+                inner.contentWindow.postMessage(data, innerOrigin);
+            }
+        } else if (inner && event.source === inner.contentWindow) {
+            toHost(data);
+        }
+    });
+
+    toHost({ jsonrpc: '2.0', method: 'ui/notifications/sandbox-proxy-ready', params: {} });
+})();
+</script>
+</body>
+</html>
+"#;
+
+/// Local HTTP server for the proxy page, used by the `sandbox-proxy` binary
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server {
+    use super::{PROXY_PAGE, SANDBOX_PROXY_PATH};
+    use std::io;
+    use std::net::SocketAddr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+    use tokio::task::JoinHandle;
+
+    /// Largest request head the server will read
+    const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+    /// Minimal HTTP server for the sandbox proxy page
+    ///
+    /// Serves [`PROXY_PAGE`] at [`SANDBOX_PROXY_PATH`] and nothing else. The
+    /// server stops when the handle is dropped.
+    #[derive(Debug)]
+    pub struct SandboxProxyServer {
+        addr: SocketAddr,
+        task: JoinHandle<()>,
+    }
+
+    impl SandboxProxyServer {
+        /// Bind the server and start accepting connections
+        pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+            let listener = TcpListener::bind(addr).await?;
+            let addr = listener.local_addr()?;
+
+            let task = tokio::spawn(async move {
+                loop {
+                    match listener.accept().await {
+                        Ok((stream, _)) => {
+                            tokio::spawn(async move {
+                                if let Err(e) = handle_connection(stream).await {
+                                    log::debug!("Sandbox proxy connection error: {}", e);
+                                }
+                            });
+                        }
+                        Err(e) => log::warn!("Sandbox proxy accept failed: {}", e),
+                    }
+                }
+            });
+
+            Ok(Self { addr, task })
+        }
+
+        /// Address the server is listening on
+        pub fn local_addr(&self) -> SocketAddr {
+            self.addr
+        }
+
+        /// Origin of the proxy page
+        pub fn origin(&self) -> String {
+            format!("http://{}", self.addr)
+        }
+
+        /// URL of the proxy page
+        pub fn url(&self) -> String {
+            format!("{}{}", self.origin(), SANDBOX_PROXY_PATH)
+        }
+    }
+
+    impl Drop for SandboxProxyServer {
+        fn drop(&mut self) {
+            self.task.abort();
+        }
+    }
+
+    async fn handle_connection(mut stream: TcpStream) -> io::Result<()> {
+        let mut head = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            head.extend_from_slice(&buf[..n]);
+            if head.len() > MAX_REQUEST_HEAD {
+                return write_response(&mut stream, "431 Request Header Fields Too Large", "").await;
+            }
+        }
+
+        let head = String::from_utf8_lossy(&head);
+        let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+        let method = request_line.next().unwrap_or_default();
+        let path = request_line.next().unwrap_or_default();
+        let path = path.split('?').next().unwrap_or_default();
+
+        match (method, path) {
+            ("GET", p) if p == SANDBOX_PROXY_PATH => write_response(&mut stream, "200 OK", PROXY_PAGE).await,
+            ("GET", _) => write_response(&mut stream, "404 Not Found", "").await,
+            _ => write_response(&mut stream, "405 Method Not Allowed", "").await,
+        }
+    }
+
+    async fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+        let response = format!(
+            "HTTP/1.1 {}\r\n\
+             Content-Type: text/html; charset=utf-8\r\n\
+             Content-Length: {}\r\n\
+             Cache-Control: no-store\r\n\
+             X-Content-Type-Options: nosniff\r\n\
+             Connection: close\r\n\
+             \r\n\
+             {}",
+            status,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use super::server::SandboxProxyServer;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    async fn get(server: &SandboxProxyServer, path: &str) -> String {
+        let mut stream = TcpStream::connect(server.local_addr()).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, server.local_addr());
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_proxy_server_serves_page() {
+        let server = SandboxProxyServer::bind("127.0.0.1:0").await.unwrap();
+        assert!(server.url().ends_with(SANDBOX_PROXY_PATH));
+
+        let response = get(&server, "/sandbox?v=1").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("ui/notifications/sandbox-proxy-ready"));
+        assert!(response.contains("ui/notifications/sandbox-resource-ready"));
+        // The host's document goes in as is, with its single CSP
+        assert!(response.contains("inner.srcdoc = params.html || '';"));
+        assert!(!response.contains("Content-Security-Policy"));
+
+        let response = get(&server, "/other").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found"));
+    }
+}
@@ -0,0 +1,1306 @@
+//! View Session Lifecycle
+//!
+//! Drives the host side of the MCP Apps lifecycle for a single view:
+//! answers `ui/initialize`, waits for `ui/notifications/initialized`, and
+//! holds back tool data until the view is ready to receive it.
+
+use crate::host::{AuditEvent, AuditLog, ConnectionManager, Conversation, HostState, LinkBroker, ModelContextStore, AUDITED_METHODS, PartialToolInput, UiSession, UiSessionEvent, UiSessionState, ViewLogs, ViewTools};
+use crate::protocol::{
+    error_codes, messages, negotiate_capabilities, DisplayMode, HostContext,
+    ContainerDimensions, JsonRpcNotification, LoggingMessageNotification, McpUiInitializeRequest, OpenLinkRequest, RequestDisplayModeRequest,
+    ServerCapabilities, SizeChangedNotification, UiMessageRequest, UiPermissions, UpdateModelContextRequest, UiResourceMeta, PROTOCOL_VERSION,
+};
+use crate::ui::UiBridge;
+use rmcp::model::{CallToolRequestParams, CallToolResult, ReadResourceRequestParams};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use tokio::time::Instant;
+
+/// Default time a view gets to answer `ui/resource-teardown`
+pub const DEFAULT_TEARDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Host-side handle for a single rendered view
+#[derive(Clone)]
+pub struct ViewSession {
+    /// Session data and lifecycle phase
+    session: Arc<RwLock<UiSession>>,
+    /// Bridge to the view
+    bridge: UiBridge,
+    /// Latest host state, used to build the view's host context
+    host_state: Arc<RwLock<HostState>>,
+    /// Host context the view currently knows about
+    sent_context: Arc<RwLock<Option<HostContext>>>,
+    /// Capabilities of the server that owns the resource
+    server_capabilities: ServerCapabilities,
+    /// Notifications held back until the view is initialized
+    pending: Arc<Mutex<Vec<JsonRpcNotification>>>,
+    /// Tool arguments being streamed by the agent
+    partial_input: Arc<Mutex<PartialToolInput>>,
+    /// Host → View message stream, taken once by the renderer
+    outgoing_rx: Arc<Mutex<Option<mpsc::UnboundedReceiver<Value>>>>,
+    /// Session events for the host UI
+    events: broadcast::Sender<UiSessionEvent>,
+    /// Security audit log for view loads and view requests
+    audit: Option<AuditLog>,
+    /// Sandbox permissions the user granted this view
+    granted_permissions: Arc<RwLock<UiPermissions>>,
+    /// Confirms and opens links the view asks for
+    links: Option<LinkBroker>,
+    /// Keeps the context the view provides for the model
+    model_context: Option<ModelContextStore>,
+    /// Conversation the view posts `ui/message` into
+    conversation: Option<Conversation>,
+    /// Host log and buffer for the view's log messages
+    logs: Option<ViewLogs>,
+    /// Registry the view's own tools are discovered into
+    view_tools: Option<ViewTools>,
+    /// Servers the view's `tools/call` and `resources/read` go to
+    servers: Option<ConnectionManager>,
+    /// Inline container size last announced to the host UI
+    container_size: Arc<Mutex<Option<(u32, u32)>>>,
+}
+
+impl std::fmt::Debug for ViewSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ViewSession")
+            .field("bridge", &self.bridge)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ViewSession {
+    fn eq(&self, other: &Self) -> bool {
+        self.bridge.id == other.bridge.id
+    }
+}
+
+impl ViewSession {
+    /// Create a new view session with its own bridge
+    pub fn new(
+        session: UiSession,
+        host_state: HostState,
+        server_capabilities: Option<ServerCapabilities>,
+    ) -> Self {
+        let (bridge, outgoing_rx, _incoming_tx) = UiBridge::new(session.id.clone());
+
+        Self {
+            session: Arc::new(RwLock::new(session)),
+            bridge,
+            host_state: Arc::new(RwLock::new(host_state)),
+            sent_context: Arc::new(RwLock::new(None)),
+            server_capabilities: server_capabilities.unwrap_or_default(),
+            pending: Arc::new(Mutex::new(Vec::new())),
+            partial_input: Arc::new(Mutex::new(PartialToolInput::default())),
+            outgoing_rx: Arc::new(Mutex::new(Some(outgoing_rx))),
+            events: broadcast::channel(32).0,
+            audit: None,
+            granted_permissions: Arc::new(RwLock::new(UiPermissions::default())),
+            links: None,
+            model_context: None,
+            conversation: None,
+            logs: None,
+            view_tools: None,
+            servers: None,
+            container_size: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Builder method: Set the minimum interval between partial input notifications
+    pub fn with_partial_input_interval(mut self, interval: Duration) -> Self {
+        self.partial_input = Arc::new(Mutex::new(PartialToolInput::new(interval)));
+        self
+    }
+    
+    /// Builder method: Record view loads and view requests to an audit log
+    pub fn with_audit_log(mut self, audit: Option<AuditLog>) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    /// Builder method: Handle `ui/open-link` with a link broker
+    pub fn with_link_broker(mut self, links: Option<LinkBroker>) -> Self {
+        self.links = links;
+        self
+    }
+
+    /// Builder method: Store `ui/update-model-context` updates
+    pub fn with_model_context(mut self, model_context: Option<ModelContextStore>) -> Self {
+        self.model_context = model_context;
+        self
+    }
+
+    /// Builder method: Post `ui/message` requests to a conversation
+    pub fn with_conversation(mut self, conversation: Option<Conversation>) -> Self {
+        self.conversation = conversation;
+        self
+    }
+
+    /// Builder method: Route the view's log messages to view logs
+    pub fn with_view_logs(mut self, logs: Option<ViewLogs>) -> Self {
+        self.logs = logs;
+        self
+    }
+
+    /// Builder method: Discover the tools the view exposes
+    pub fn with_view_tools(mut self, view_tools: Option<ViewTools>) -> Self {
+        self.view_tools = view_tools;
+        self
+    }
+
+    /// Builder method: Answer `tools/call` and `resources/read` from the view's server
+    pub fn with_connection_manager(mut self, servers: Option<ConnectionManager>) -> Self {
+        self.servers = servers;
+        self
+    }
+
+    /// Session ID
+    pub fn session_id(&self) -> &str {
+        &self.bridge.session_id
+    }
+
+    /// Bridge to the view
+    pub fn bridge(&self) -> &UiBridge {
+        &self.bridge
+    }
+
+    /// Current lifecycle phase
+    pub async fn state(&self) -> UiSessionState {
+        self.session.read().await.state.clone()
+    }
+
+    /// Snapshot of the session data
+    pub async fn session(&self) -> UiSession {
+        self.session.read().await.clone()
+    }
+
+    /// Take the Host → View message stream
+    ///
+    /// Returns `None` if the stream was already taken by another renderer.
+    pub async fn take_outgoing(&self) -> Option<mpsc::UnboundedReceiver<Value>> {
+        self.outgoing_rx.lock().await.take()
+    }
+
+    /// Sandbox permissions the user granted this view
+    pub async fn granted_permissions(&self) -> UiPermissions {
+        self.granted_permissions.read().await.clone()
+    }
+
+    /// Set the sandbox permissions granted to this view
+    ///
+    /// Must be called before the view initializes; only permissions the host
+    /// can grant are kept.
+    pub async fn set_granted_permissions(&self, granted: UiPermissions) {
+        let grantable = self.host_state.read().await.grantable_permissions.clone();
+        *self.granted_permissions.write().await = grantable.intersect(&granted);
+    }
+
+    /// Size of the inline container, if the view has reported its content size
+    ///
+    /// `None` while the view is displayed fullscreen or picture-in-picture,
+    /// where the host sizes the container.
+    pub async fn container_size(&self) -> Option<(u32, u32)> {
+        *self.container_size.lock().await
+    }
+
+    /// Subscribe to events raised by this session
+    pub fn subscribe(&self) -> broadcast::Receiver<UiSessionEvent> {
+        self.events.subscribe()
+    }
+
+    /// Handle a message received from the view
+    ///
+    /// Lifecycle messages and responses are consumed here. Any other request
+    /// or notification is returned to the caller for routing.
+    pub async fn handle_message(&self, message: Value) -> Result<Option<Value>, String> {
+        if message.get("result").is_some() || message.get("error").is_some() {
+            self.bridge.process_message(message).await?;
+            return Ok(None);
+        }
+
+        let method = message.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+        if AUDITED_METHODS.contains(&method) {
+            self.audit(|session| {
+                AuditEvent::view_request(&session.id, &session.server_id, &session.resource_uri, method, message.get("params").cloned())
+            })
+            .await;
+        }
+
+        match method {
+            "ui/initialize" => {
+                let id = message.get("id").cloned().ok_or("ui/initialize must be a request")?;
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                self.handle_initialize(id, params).await?;
+                Ok(None)
+            }
+            "ui/notifications/initialized" => {
+                self.handle_initialized().await?;
+                Ok(None)
+            }
+            "ui/request-display-mode" => {
+                let id = message.get("id").cloned().ok_or("ui/request-display-mode must be a request")?;
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                let response = match serde_json::from_value::<RequestDisplayModeRequest>(params) {
+                    Ok(request) => {
+                        let mode = self.request_display_mode(request.mode).await?;
+                        messages::request_display_mode_response(id, mode)
+                    }
+                    Err(e) => messages::error_response(
+                        id,
+                        error_codes::INVALID_PARAMS,
+                        format!("Invalid ui/request-display-mode params: {}", e),
+                    ),
+                };
+                self.bridge.send_response(response)?;
+                Ok(None)
+            }
+            "ui/notifications/size-changed" => {
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                match serde_json::from_value::<SizeChangedNotification>(params) {
+                    Ok(size) => self.resize(size.width, size.height).await?,
+                    Err(e) => log::warn!("View {} sent invalid size-changed params: {}", self.session_id(), e),
+                }
+                Ok(None)
+            }
+            "ui/update-model-context" | "context/update" if self.model_context.is_some() => {
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                let result = match serde_json::from_value::<UpdateModelContextRequest>(params) {
+                    Ok(request) => self.update_model_context(request).await,
+                    Err(e) => Err((error_codes::INVALID_PARAMS, format!("Invalid content format: {}", e))),
+                };
+                // The legacy alias is a notification
+                if let Some(id) = message.get("id").cloned() {
+                    self.bridge.send_response(match result {
+                        Ok(()) => messages::success_response(id),
+                        Err((code, message)) => messages::error_response(id, code, message),
+                    })?;
+                }
+                Ok(None)
+            }
+            "ui/open-link" | "link/open" if self.links.is_some() => {
+                let id = message.get("id").cloned();
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                // The user may take a while to confirm, so don't hold up other messages
+                let view = self.clone();
+                tokio::spawn(async move {
+                    let result = match serde_json::from_value::<OpenLinkRequest>(params) {
+                        Ok(request) => view.open_link(&request.url).await,
+                        Err(e) => Err((error_codes::INVALID_PARAMS, format!("Invalid ui/open-link params: {}", e))),
+                    };
+                    let Some(id) = id else { return };
+                    let response = match result {
+                        Ok(()) => messages::success_response(id),
+                        Err((code, message)) => messages::error_response(id, code, message),
+                    };
+                    if let Err(e) = view.bridge.send_response(response) {
+                        log::warn!("Failed to answer ui/open-link for view {}: {}", view.session_id(), e);
+                    }
+                });
+                Ok(None)
+            }
+            "notifications/message" | "logging/message" if self.logs.is_some() => {
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                match serde_json::from_value::<LoggingMessageNotification>(params) {
+                    Ok(log) => {
+                        if let Some(logs) = &self.logs {
+                            logs.log(&*self.session.read().await, log);
+                        }
+                    }
+                    Err(e) => log::warn!("View {} sent an invalid log message: {}", self.session_id(), e),
+                }
+                Ok(None)
+            }
+            "notifications/tools/list_changed" if self.view_tools.is_some() => {
+                if let Some(view_tools) = self.view_tools.clone() {
+                    let session_id = self.session_id().to_string();
+                    // The view answers tools/list through this same message loop
+                    tokio::spawn(async move {
+                        if let Err(e) = view_tools.refresh(&session_id).await {
+                            log::warn!("Failed to list tools of view {}: {}", session_id, e);
+                        }
+                    });
+                }
+                Ok(None)
+            }
+            "tools/call" | "resources/read" if self.servers.is_some() => {
+                let id = message.get("id").cloned();
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                let method = method.to_string();
+                // The server may take a while, so don't hold up other messages
+                let view = self.clone();
+                tokio::spawn(async move {
+                    let result = match method.as_str() {
+                        "tools/call" => match serde_json::from_value::<CallToolRequestParams>(params) {
+                            Ok(request) => {
+                                let arguments = request.arguments.map(Value::Object).unwrap_or_default();
+                                view.forward_tool_call(&request.name, arguments).await.map(|r| serde_json::to_value(r).unwrap_or_default())
+                            }
+                            Err(e) => Err((error_codes::INVALID_PARAMS, format!("Invalid tools/call params: {}", e))),
+                        },
+                        _ => match serde_json::from_value::<ReadResourceRequestParams>(params) {
+                            Ok(request) => view.forward_resource_read(&request.uri).await.map(|r| serde_json::to_value(r).unwrap_or_default()),
+                            Err(e) => Err((error_codes::INVALID_PARAMS, format!("Invalid resources/read params: {}", e))),
+                        },
+                    };
+                    let Some(id) = id else { return };
+                    let response = match result {
+                        Ok(result) => messages::result_response(id, result),
+                        Err((code, message)) => messages::error_response(id, code, message),
+                    };
+                    if let Err(e) = view.bridge.send_response(response) {
+                        log::warn!("Failed to answer {} for view {}: {}", method, view.session_id(), e);
+                    }
+                });
+                Ok(None)
+            }
+            "ui/message" if self.conversation.is_some() => {
+                let id = message.get("id").cloned();
+                let params = message.get("params").cloned().unwrap_or(Value::Null);
+                // The user may have to confirm the message, so don't hold up other messages
+                let view = self.clone();
+                tokio::spawn(async move {
+                    let result = match serde_json::from_value::<UiMessageRequest>(params) {
+                        Ok(request) => view.post_message(request).await,
+                        Err(e) => Err((error_codes::INVALID_PARAMS, format!("Invalid message format: {}", e))),
+                    };
+                    let Some(id) = id else { return };
+                    let response = match result {
+                        Ok(()) => messages::success_response(id),
+                        Err((code, message)) => messages::error_response(id, code, message),
+                    };
+                    if let Err(e) = view.bridge.send_response(response) {
+                        log::warn!("Failed to answer ui/message for view {}: {}", view.session_id(), e);
+                    }
+                });
+                Ok(None)
+            }
+            _ => Ok(Some(message)),
+        }
+    }
+
+    /// Call a tool on the view's server for natively rendered content
+    ///
+    /// Audited like a `tools/call` request from the view itself.
+    pub async fn call_server_tool(&self, name: &str, arguments: Value) -> Result<CallToolResult, (i32, String)> {
+        let params = serde_json::json!({ "name": name, "arguments": arguments });
+        self.audit(|session| {
+            AuditEvent::view_request(&session.id, &session.server_id, &session.resource_uri, "tools/call", Some(params))
+        })
+        .await;
+        self.forward_tool_call(name, arguments).await
+    }
+
+    /// Call a tool on the view's server, if the server lets views call it
+    async fn forward_tool_call(&self, name: &str, arguments: Value) -> Result<CallToolResult, (i32, String)> {
+        let Some(servers) = &self.servers else {
+            return Err((error_codes::METHOD_NOT_FOUND, "Host does not call server tools".to_string()));
+        };
+        let server_id = self.session.read().await.server_id.clone();
+        let app_tools = servers.get_app_tools(&server_id).await.map_err(|e| (e.code(), e.to_string()))?;
+        if !app_tools.iter().any(|t| t.name == name) {
+            log::info!("View {} called tool {} it may not call", self.session_id(), name);
+            return Err((error_codes::INVALID_PARAMS, format!("Tool {} is not available to views", name)));
+        }
+        servers.call_tool(&server_id, name, arguments).await.map_err(|e| (e.code(), e.to_string()))
+    }
+
+    /// Read a resource from the view's server
+    async fn forward_resource_read(&self, uri: &str) -> Result<rmcp::model::ReadResourceResult, (i32, String)> {
+        let Some(servers) = &self.servers else {
+            return Err((error_codes::METHOD_NOT_FOUND, "Host does not read server resources".to_string()));
+        };
+        let server_id = self.session.read().await.server_id.clone();
+        servers.read_resource(&server_id, uri).await.map_err(|e| (e.code(), e.to_string()))
+    }
+
+    /// Answer a request nothing on the host handled with `METHOD_NOT_FOUND`
+    ///
+    /// Notifications need no answer and are ignored.
+    pub fn reject_unhandled(&self, message: &Value) -> Result<(), String> {
+        let Some(id) = message.get("id").cloned() else { return Ok(()) };
+        let method = message.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+        self.bridge.send_response(messages::error_response(
+            id,
+            error_codes::METHOD_NOT_FOUND,
+            format!("Method not found: {}", method),
+        ))
+    }
+
+    /// Post a message from the view to the host conversation
+    async fn post_message(&self, request: UiMessageRequest) -> Result<(), (i32, String)> {
+        let Some(conversation) = &self.conversation else {
+            return Err((error_codes::METHOD_NOT_FOUND, "Host has no conversation".to_string()));
+        };
+        let session = self.session().await;
+        conversation.post_from_view(&session, request).await.map(|_| ()).map_err(|e| {
+            log::info!("View {} message not posted: {}", self.session_id(), e);
+            (e.code(), e.to_string())
+        })
+    }
+
+    /// Replace the context this view provides for the model
+    async fn update_model_context(&self, request: UpdateModelContextRequest) -> Result<(), (i32, String)> {
+        let Some(store) = &self.model_context else {
+            return Err((error_codes::METHOD_NOT_FOUND, "Host does not keep model context".to_string()));
+        };
+        let session = self.session().await;
+        store.update(&session, request).map(|_| ()).map_err(|e| {
+            log::info!("View {} context update rejected: {}", self.session_id(), e);
+            (e.code(), e.to_string())
+        })
+    }
+
+    /// Check, confirm and open a link on behalf of the view
+    async fn open_link(&self, url: &str) -> Result<(), (i32, String)> {
+        let Some(links) = &self.links else {
+            return Err((error_codes::METHOD_NOT_FOUND, "Host does not open links".to_string()));
+        };
+        let session = self.session().await;
+        let policy = self.host_state.read().await.link_policy(&session.server_id);
+        let app_name = session.app_info.map(|a| a.name).unwrap_or(session.resource_uri);
+
+        links.open(&policy, &session.server_id, &app_name, url).await.map_err(|e| {
+            log::info!("View {} link {} not opened: {}", self.session_id(), url, e);
+            (e.code(), e.to_string())
+        })
+    }
+
+    /// Record that the view's resource was loaded
+    ///
+    /// Captures the content hash, the effective CSP after validation and host
+    /// approval, and the permissions requested versus granted.
+    pub async fn record_view_loaded(&self, content: &str, metadata: Option<&UiResourceMeta>) {
+        let host_state = self.host_state.read().await.clone();
+        let details = metadata.and_then(|m| m.ui.as_ref());
+        let csp = details
+            .and_then(|d| d.csp.as_ref())
+            .map(|csp| csp.effective_policy(host_state.approved_csp.as_ref()))
+            .unwrap_or_default();
+        let granted = self.granted_permissions().await;
+
+        self.audit(|session| {
+            AuditEvent::view_loaded(&session.id, &session.server_id, &session.resource_uri, content, metadata, &csp, &granted)
+        })
+        .await;
+    }
+
+    async fn audit(&self, event: impl FnOnce(&UiSession) -> AuditEvent) {
+        let Some(audit) = &self.audit else { return };
+        let event = event(&*self.session.read().await);
+        if let Err(e) = audit.record(event) {
+            log::warn!("Failed to write audit record for view {}: {}", self.session_id(), e);
+        }
+    }
+
+    /// Answer ui/initialize with host info, negotiated capabilities and context
+    async fn handle_initialize(&self, id: Value, params: Value) -> Result<(), String> {
+        let request = match serde_json::from_value::<McpUiInitializeRequest>(params) {
+            Ok(request) => request,
+            Err(e) => {
+                return self.bridge.send_response(messages::error_response(
+                    id,
+                    error_codes::INVALID_PARAMS,
+                    format!("Invalid ui/initialize params: {}", e),
+                ));
+            }
+        };
+
+        let mut session = self.session.write().await;
+        if session.state != UiSessionState::Initializing {
+            return self.bridge.send_response(messages::error_response(
+                id,
+                error_codes::INVALID_REQUEST,
+                "View is already initialized",
+            ));
+        }
+
+        if request.protocol_version != PROTOCOL_VERSION {
+            log::warn!(
+                "View {} requested protocol {}, host speaks {}",
+                session.id, request.protocol_version, PROTOCOL_VERSION
+            );
+        }
+
+        let host_state = self.host_state.read().await;
+        let mut host_caps = host_state.to_capabilities();
+        // The view is told only what the user granted it
+        if let Some(sandbox) = host_caps.sandbox.as_mut() {
+            sandbox.permissions = Some(self.granted_permissions().await);
+        }
+        let negotiated = negotiate_capabilities(
+            &host_caps,
+            &host_state.supported_display_modes,
+            &self.server_capabilities,
+            Some(&request.app_capabilities),
+        );
+
+        log::info!(
+            "View {} ({} {}) initializing for {}",
+            session.id, request.app_info.name, request.app_info.version, session.resource_uri
+        );
+
+        session.app_info = Some(request.app_info);
+        session.app_capabilities = Some(request.app_capabilities);
+        session.negotiated_capabilities = Some(negotiated);
+        session.state = UiSessionState::Handshaking;
+
+        let context = Self::build_host_context(&session, &host_state);
+        *self.sent_context.write().await = Some(context.clone());
+
+        self.bridge.send_response(messages::ui_initialize_response(
+            id,
+            host_state.name.clone(),
+            host_state.version.clone(),
+            host_caps,
+            Some(context),
+        ))
+    }
+
+    /// Inline container size for the view's reported content
+    fn fit_container(session: &UiSession, host_state: &HostState) -> Option<(u32, u32)> {
+        if session.display_mode != DisplayMode::Inline {
+            return None;
+        }
+        let (width, height) = session.content_size?;
+        Some(host_state.container_dimensions.fit(width, height))
+    }
+
+    /// Host context as seen by this view
+    fn build_host_context(session: &UiSession, host_state: &HostState) -> HostContext {
+        let mut context = host_state.to_host_context();
+        context.tool_info = session.tool_info.clone();
+        context.display_mode = Some(session.display_mode);
+        if let Some(negotiated) = &session.negotiated_capabilities {
+            context.available_display_modes = Some(negotiated.display_modes.clone());
+        }
+        if let Some((width, height)) = Self::fit_container(session, host_state) {
+            context.container_dimensions = Some(ContainerDimensions {
+                width: Some(width),
+                height: Some(height),
+                ..host_state.container_dimensions.clone()
+            });
+        }
+        context
+    }
+
+    /// Apply a new host state and tell the view what changed
+    pub async fn set_host_state(&self, host_state: HostState) -> Result<(), String> {
+        *self.host_state.write().await = host_state;
+        self.update_container_size().await;
+        self.sync_host_context().await
+    }
+
+    /// Fit the inline container to the content size reported by the view
+    ///
+    /// The container grows with the content up to the host's maximum
+    /// dimensions, and the view is told its new container size.
+    pub async fn resize(&self, width: u32, height: u32) -> Result<(), String> {
+        self.session.write().await.content_size = Some((width, height));
+        self.update_container_size().await;
+        self.sync_host_context().await
+    }
+
+    /// Recompute the inline container size and raise `SizeChanged` if it moved
+    async fn update_container_size(&self) {
+        let size = {
+            let session = self.session.read().await;
+            Self::fit_container(&session, &*self.host_state.read().await)
+        };
+        let previous = std::mem::replace(&mut *self.container_size.lock().await, size);
+        if let Some((width, height)) = size.filter(|_| size != previous) {
+            let _ = self.events.send(UiSessionEvent::SizeChanged {
+                session_id: self.session_id().to_string(),
+                width,
+                height,
+            });
+        }
+    }
+
+    /// Change how the view is displayed and tell the view
+    pub async fn set_display_mode(&self, mode: DisplayMode) -> Result<(), String> {
+        let previous = std::mem::replace(&mut self.session.write().await.display_mode, mode);
+        if previous != mode {
+            let _ = self.events.send(UiSessionEvent::DisplayModeChanged {
+                session_id: self.session_id().to_string(),
+                mode,
+            });
+        }
+        self.update_container_size().await;
+        self.sync_host_context().await
+    }
+
+    /// Decide the display mode for a view's request and apply it
+    ///
+    /// The request is granted only if both the host and the view support the
+    /// mode; otherwise the view keeps its current mode. Returns the mode in
+    /// effect afterwards.
+    pub async fn request_display_mode(&self, requested: DisplayMode) -> Result<DisplayMode, String> {
+        let (allowed, current) = {
+            let session = self.session.read().await;
+            let allowed = session.negotiated_capabilities.as_ref()
+                .map(|n| n.display_modes.contains(&requested))
+                .unwrap_or(false);
+            (allowed, session.display_mode)
+        };
+
+        if !allowed {
+            log::info!("View {} requested unsupported display mode {:?}", self.session_id(), requested);
+            return Ok(current);
+        }
+
+        self.set_display_mode(requested).await?;
+        Ok(requested)
+    }
+
+    /// Send `ui/notifications/host-context-changed` with the fields that differ
+    /// from the context the view last received
+    ///
+    /// Views that have not been sent a context yet pick up the latest state in
+    /// their ui/initialize response, so nothing is sent to them.
+    async fn sync_host_context(&self) -> Result<(), String> {
+        let changes = {
+            let session = self.session.read().await;
+            if matches!(session.state, UiSessionState::Teardown | UiSessionState::Error(_)) {
+                return Ok(());
+            }
+
+            let mut sent = self.sent_context.write().await;
+            let Some(previous) = sent.as_ref() else {
+                return Ok(());
+            };
+            let current = Self::build_host_context(&session, &*self.host_state.read().await);
+            let Some(changes) = previous.diff(&current) else {
+                return Ok(());
+            };
+            *sent = Some(current);
+            changes
+        };
+
+        self.notify(messages::host_context_changed_notification(changes)).await
+    }
+
+    /// Mark the view ready and flush notifications held back during the handshake
+    async fn handle_initialized(&self) -> Result<(), String> {
+        {
+            let mut session = self.session.write().await;
+            if session.state != UiSessionState::Handshaking {
+                log::warn!("View {} sent initialized in state {:?}", session.id, session.state);
+                return Ok(());
+            }
+            session.state = UiSessionState::Ready;
+        }
+
+        let pending = std::mem::take(&mut *self.pending.lock().await);
+        for notification in pending {
+            self.deliver(notification).await?;
+        }
+        self.discover_tools().await;
+        Ok(())
+    }
+
+    /// List the tools the view exposes, if it declared any
+    async fn discover_tools(&self) {
+        let Some(view_tools) = self.view_tools.clone() else { return };
+        let session = self.session().await;
+        if !session.app_capabilities.as_ref().is_some_and(|c| c.exposes_tools()) {
+            return;
+        }
+        let transport = Arc::new(self.bridge.clone());
+        // The view answers tools/list through this same message loop
+        tokio::spawn(async move {
+            if let Err(e) = view_tools.register(&session, transport).await {
+                log::warn!("Failed to list tools of view {}: {}", session.id, e);
+            }
+        });
+    }
+
+    /// Send a notification now, or queue it until the view is initialized
+    pub async fn notify(&self, notification: JsonRpcNotification) -> Result<(), String> {
+        if self.state().await.is_initialized() {
+            self.deliver(notification).await
+        } else {
+            self.pending.lock().await.push(notification);
+            Ok(())
+        }
+    }
+
+    /// Deliver tool input arguments to the view
+    pub async fn send_tool_input(&self, arguments: Value) -> Result<(), String> {
+        self.notify(messages::tool_input_notification(arguments)).await
+    }
+
+    /// Deliver a partial tool input preview to the view
+    ///
+    /// Previews are best-effort: they are dropped rather than queued while the
+    /// view is still initializing, since the final tool-input supersedes them.
+    pub async fn send_tool_input_partial(&self, arguments: Value) -> Result<(), String> {
+        if self.state().await.is_initialized() {
+            self.deliver(messages::tool_input_partial_notification(arguments)).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Feed a chunk of tool arguments streamed by the agent
+    ///
+    /// The accumulated text is repaired into a JSON object and sent as
+    /// `ui/notifications/tool-input-partial`, throttled to the configured rate.
+    pub async fn push_tool_input_chunk(&self, chunk: &str) -> Result<(), String> {
+        let preview = self.partial_input.lock().await.push(chunk, Instant::now());
+        match preview {
+            Some(arguments) => self.send_tool_input_partial(arguments).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Finish the argument stream and deliver the complete tool input
+    ///
+    /// Returns the parsed arguments so the caller can execute the tool call.
+    pub async fn finish_tool_input(&self) -> Result<Value, String> {
+        let arguments = {
+            let mut partial = self.partial_input.lock().await;
+            let parsed = partial.finish();
+            *partial = PartialToolInput::new(partial.interval());
+            parsed.map_err(|e| format!("Invalid tool arguments: {}", e))?
+        };
+
+        self.send_tool_input(arguments.clone()).await?;
+        Ok(arguments)
+    }
+
+    /// Deliver a tool result to the view
+    pub async fn send_tool_result(&self, result: Value) -> Result<(), String> {
+        self.notify(messages::tool_result_notification(result)).await
+    }
+
+    /// Ask the view to tear down and wait up to `timeout` for it to finish
+    ///
+    /// Views that never completed the handshake cannot answer and are torn
+    /// down immediately. The session ends in `Teardown` whether or not the view
+    /// responded; an error only reports that it did not acknowledge in time.
+    pub async fn teardown(&self, reason: Option<&str>, timeout: Duration) -> Result<(), String> {
+        let was_initialized = {
+            let mut session = self.session.write().await;
+            let was_initialized = session.state.is_initialized();
+            session.state = UiSessionState::Teardown;
+            was_initialized
+        };
+        self.pending.lock().await.clear();
+
+        if !was_initialized {
+            return Ok(());
+        }
+
+        let params = messages::resource_teardown_request(Value::Null, reason).params;
+        self.bridge
+            .send_request_with_timeout("ui/resource-teardown", params, timeout)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("View {} teardown failed: {}", self.session_id(), e.message))
+    }
+
+    /// Write a notification to the bridge and advance the phase for tool data
+    async fn deliver(&self, notification: JsonRpcNotification) -> Result<(), String> {
+        let next_state = match notification.method.as_str() {
+            "ui/notifications/tool-input" => Some(UiSessionState::Loading),
+            "ui/notifications/tool-result" => Some(UiSessionState::Active),
+            _ => None,
+        };
+
+        self.bridge.send_notification(notification.method, notification.params)?;
+
+        if let Some(state) = next_state {
+            self.session.write().await.state = state;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::McpUiAppCapabilities;
+    use serde_json::json;
+
+    fn initialize_message(modes: Vec<DisplayMode>) -> Value {
+        let caps = McpUiAppCapabilities {
+            available_display_modes: Some(modes),
+            ..Default::default()
+        };
+        let request = messages::ui_initialize_request("test-app", "1.0.0", caps).with_id(json!("1"));
+        serde_json::to_value(request).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_initialize_handshake_gates_tool_data() {
+        let view = ViewSession::new(
+            UiSession::new("s1", "embedded", "ui://test/view"),
+            HostState::default(),
+            None,
+        );
+        let mut outgoing = view.take_outgoing().await.unwrap();
+
+        // Tool input before the handshake is held back
+        view.send_tool_input(json!({ "location": "Paris" })).await.unwrap();
+        assert!(outgoing.try_recv().is_err());
+
+        view.handle_message(initialize_message(vec![DisplayMode::Inline])).await.unwrap();
+        assert_eq!(view.state().await, UiSessionState::Handshaking);
+
+        let response = outgoing.try_recv().unwrap();
+        assert_eq!(response["id"], json!("1"));
+        assert_eq!(response["result"]["protocolVersion"], json!(PROTOCOL_VERSION));
+        assert_eq!(response["result"]["hostInfo"]["name"], json!("mcp-apps-host"));
+        assert_eq!(response["result"]["hostContext"]["availableDisplayModes"], json!(["inline"]));
+        assert!(outgoing.try_recv().is_err());
+
+        view.handle_message(json!({
+            "jsonrpc": "2.0",
+            "method": "ui/notifications/initialized",
+            "params": {}
+        })).await.unwrap();
+
+        let input = outgoing.try_recv().unwrap();
+        assert_eq!(input["method"], json!("ui/notifications/tool-input"));
+        assert_eq!(input["params"]["arguments"]["location"], json!("Paris"));
+        assert_eq!(view.state().await, UiSessionState::Loading);
+
+        view.send_tool_result(json!({ "content": [] })).await.unwrap();
+        let result = outgoing.try_recv().unwrap();
+        assert_eq!(result["method"], json!("ui/notifications/tool-result"));
+        assert_eq!(view.state().await, UiSessionState::Active);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_rejects_bad_params_and_repeats() {
+        let view = ViewSession::new(
+            UiSession::new("s2", "embedded", "ui://test/view"),
+            HostState::default(),
+            None,
+        );
+        let mut outgoing = view.take_outgoing().await.unwrap();
+
+        view.handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "ui/initialize",
+            "params": { "bogus": true }
+        })).await.unwrap();
+        let response = outgoing.try_recv().unwrap();
+        assert_eq!(response["error"]["code"], json!(error_codes::INVALID_PARAMS));
+        assert_eq!(view.state().await, UiSessionState::Initializing);
+
+        view.handle_message(initialize_message(vec![DisplayMode::Inline])).await.unwrap();
+        outgoing.try_recv().unwrap();
+        view.handle_message(initialize_message(vec![DisplayMode::Inline])).await.unwrap();
+        let response = outgoing.try_recv().unwrap();
+        assert_eq!(response["error"]["code"], json!(error_codes::INVALID_REQUEST));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_tool_input() {
+        let view = ViewSession::new(
+            UiSession::new("s4", "embedded", "ui://test/view"),
+            HostState::default(),
+            None,
+        ).with_partial_input_interval(Duration::from_secs(60));
+        let mut outgoing = view.take_outgoing().await.unwrap();
+
+        // Previews before the handshake are dropped
+        view.send_tool_input_partial(json!({})).await.unwrap();
+        view.handle_message(initialize_message(vec![DisplayMode::Inline])).await.unwrap();
+        view.handle_message(json!({ "jsonrpc": "2.0", "method": "ui/notifications/initialized" })).await.unwrap();
+        outgoing.try_recv().unwrap();
+        assert!(outgoing.try_recv().is_err());
+
+        view.push_tool_input_chunk(r#"{"location": "Pa"#).await.unwrap();
+        let partial = outgoing.try_recv().unwrap();
+        assert_eq!(partial["method"], json!("ui/notifications/tool-input-partial"));
+        assert_eq!(partial["params"]["arguments"], json!({ "location": "Pa" }));
+
+        // Further chunks inside the throttle window are not sent
+        view.push_tool_input_chunk(r#"ris"}"#).await.unwrap();
+        assert!(outgoing.try_recv().is_err());
+
+        let arguments = view.finish_tool_input().await.unwrap();
+        assert_eq!(arguments, json!({ "location": "Paris" }));
+        let input = outgoing.try_recv().unwrap();
+        assert_eq!(input["method"], json!("ui/notifications/tool-input"));
+        assert_eq!(input["params"]["arguments"], json!({ "location": "Paris" }));
+    }
+
+    async fn initialized_view(id: &str) -> (ViewSession, mpsc::UnboundedReceiver<Value>) {
+        let view = ViewSession::new(
+            UiSession::new(id, "embedded", "ui://test/view"),
+            HostState::default(),
+            None,
+        );
+        let mut outgoing = view.take_outgoing().await.unwrap();
+        view.handle_message(initialize_message(vec![DisplayMode::Inline])).await.unwrap();
+        view.handle_message(json!({ "jsonrpc": "2.0", "method": "ui/notifications/initialized" })).await.unwrap();
+        outgoing.try_recv().unwrap();
+        (view, outgoing)
+    }
+
+    #[tokio::test]
+    async fn test_host_context_changes_are_sent_as_diffs() {
+        let (view, mut outgoing) = initialized_view("h1").await;
+
+        view.set_host_state(HostState::default().with_theme("dark")).await.unwrap();
+        let changed = outgoing.try_recv().unwrap();
+        assert_eq!(changed["method"], json!("ui/notifications/host-context-changed"));
+        // The theme's style variables change along with it
+        assert_eq!(changed["params"]["theme"], json!("dark"));
+        assert_eq!(changed["params"]["styles"]["variables"]["--color-background-primary"], json!("#111827"));
+        assert_eq!(changed["params"].as_object().unwrap().len(), 2);
+
+        // Nothing is sent when the state is unchanged
+        view.set_host_state(HostState::default().with_theme("dark")).await.unwrap();
+        assert!(outgoing.try_recv().is_err());
+
+        view.set_display_mode(DisplayMode::Fullscreen).await.unwrap();
+        let changed = outgoing.try_recv().unwrap();
+        assert_eq!(changed["params"], json!({ "displayMode": "fullscreen" }));
+
+        // Views that have not been initialized get the latest state on ui/initialize
+        let pending = ViewSession::new(
+            UiSession::new("h2", "embedded", "ui://test/view"),
+            HostState::default(),
+            None,
+        );
+        let mut pending_outgoing = pending.take_outgoing().await.unwrap();
+        pending.set_host_state(HostState::default().with_theme("dark")).await.unwrap();
+        assert!(pending_outgoing.try_recv().is_err());
+        pending.handle_message(initialize_message(vec![DisplayMode::Inline])).await.unwrap();
+        let response = pending_outgoing.try_recv().unwrap();
+        assert_eq!(response["result"]["hostContext"]["theme"], json!("dark"));
+    }
+
+    #[tokio::test]
+    async fn test_size_changed_resizes_inline_container() {
+        let (view, mut outgoing) = initialized_view("r1").await;
+        let mut events = view.subscribe();
+
+        view.handle_message(json!({
+            "jsonrpc": "2.0",
+            "method": "ui/notifications/size-changed",
+            "params": { "width": 640, "height": 1200 }
+        }))
+        .await
+        .unwrap();
+
+        // The container grows to the content, capped at the maximum height
+        assert_eq!(view.container_size().await, Some((640, 600)));
+        let Ok(UiSessionEvent::SizeChanged { width, height, .. }) = events.try_recv() else {
+            panic!("expected a size change");
+        };
+        assert_eq!((width, height), (640, 600));
+        let changed = outgoing.try_recv().unwrap();
+        assert_eq!(
+            changed["params"],
+            json!({ "containerDimensions": { "width": 640, "height": 600, "maxWidth": 800, "maxHeight": 600 } })
+        );
+
+        // Reporting the same size again changes nothing
+        view.resize(640, 1200).await.unwrap();
+        assert!(events.try_recv().is_err());
+        assert!(outgoing.try_recv().is_err());
+
+        // Outside inline mode the host sizes the container
+        view.set_display_mode(DisplayMode::Fullscreen).await.unwrap();
+        assert_eq!(view.container_size().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_request_display_mode_negotiation() {
+        let view = ViewSession::new(
+            UiSession::new("d1", "embedded", "ui://test/view"),
+            HostState::default().with_display_modes(vec![DisplayMode::Inline, DisplayMode::Pip]),
+            None,
+        );
+        let mut outgoing = view.take_outgoing().await.unwrap();
+        let mut events = view.subscribe();
+        view.handle_message(initialize_message(vec![DisplayMode::Inline, DisplayMode::Fullscreen, DisplayMode::Pip])).await.unwrap();
+        view.handle_message(json!({ "jsonrpc": "2.0", "method": "ui/notifications/initialized" })).await.unwrap();
+        outgoing.try_recv().unwrap();
+
+        // The host does not support fullscreen, so the view stays inline
+        view.handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "ui/request-display-mode",
+            "params": { "mode": "fullscreen" }
+        })).await.unwrap();
+        let response = outgoing.try_recv().unwrap();
+        assert_eq!(response["result"]["mode"], json!("inline"));
+        assert!(outgoing.try_recv().is_err());
+        assert!(events.try_recv().is_err());
+
+        view.handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "ui/request-display-mode",
+            "params": { "mode": "pip" }
+        })).await.unwrap();
+        let changed = outgoing.try_recv().unwrap();
+        assert_eq!(changed["method"], json!("ui/notifications/host-context-changed"));
+        assert_eq!(changed["params"], json!({ "displayMode": "pip" }));
+        let response = outgoing.try_recv().unwrap();
+        assert_eq!(response["id"], json!(3));
+        assert_eq!(response["result"]["mode"], json!("pip"));
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            UiSessionEvent::DisplayModeChanged { mode: DisplayMode::Pip, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_teardown_waits_for_view() {
+        let (view, mut outgoing) = initialized_view("t1").await;
+
+        let responder = view.clone();
+        tokio::spawn(async move {
+            let request = outgoing.recv().await.unwrap();
+            assert_eq!(request["method"], json!("ui/resource-teardown"));
+            assert_eq!(request["params"]["reason"], json!("switching"));
+            responder.handle_message(json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": {}
+            })).await.unwrap();
+        });
+
+        view.teardown(Some("switching"), Duration::from_secs(5)).await.unwrap();
+        assert_eq!(view.state().await, UiSessionState::Teardown);
+    }
+
+    #[tokio::test]
+    async fn test_teardown_times_out() {
+        let (view, _outgoing) = initialized_view("t2").await;
+
+        let result = view.teardown(None, Duration::from_millis(20)).await;
+        assert!(result.is_err());
+        assert_eq!(view.state().await, UiSessionState::Teardown);
+
+        // Views that never initialized are torn down without waiting
+        let uninitialized = ViewSession::new(
+            UiSession::new("t3", "embedded", "ui://test/view"),
+            HostState::default(),
+            None,
+        );
+        uninitialized.teardown(None, Duration::from_secs(60)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unhandled_messages_are_returned() {
+        let view = ViewSession::new(
+            UiSession::new("s3", "embedded", "ui://test/view"),
+            HostState::default(),
+            None,
+        );
+        let message = json!({ "jsonrpc": "2.0", "id": 2, "method": "ui/open-link", "params": { "url": "https://example.com" } });
+        let returned = view.handle_message(message.clone()).await.unwrap();
+        assert_eq!(returned, Some(message));
+    }
+
+    #[tokio::test]
+    async fn test_view_requests_are_audited() {
+        let path = std::env::temp_dir().join(format!("mcp-audit-{}.jsonl", uuid::Uuid::new_v4()));
+        let audit = AuditLog::open(&path).unwrap();
+        let view = ViewSession::new(
+            UiSession::new("s1", "embedded", "ui://test/view"),
+            HostState::default(),
+            None,
+        )
+        .with_audit_log(Some(audit.clone()));
+
+        view.record_view_loaded("<p>hi</p>", None).await;
+        view.handle_message(json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": { "name": "refresh" } }))
+            .await
+            .unwrap();
+        view.handle_message(json!({ "jsonrpc": "2.0", "method": "ui/notifications/size-changed", "params": {} }))
+            .await
+            .unwrap();
+
+        let records = audit.query(&crate::host::AuditQuery::new().session("s1")).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].event.kind(), "viewLoaded");
+        let AuditEvent::ViewRequest { method, params, .. } = &records[1].event else {
+            panic!("expected a view request");
+        };
+        assert_eq!(method, "tools/call");
+        assert_eq!(params, &Some(json!({ "name": "refresh" })));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_open_link_errors_are_reported() {
+        struct NoOpener;
+        impl crate::host::LinkOpener for NoOpener {
+            fn open(&self, _url: &str) -> Result<(), String> {
+                Err("no opener in tests".to_string())
+            }
+        }
+
+        let host_state = HostState::default()
+            .with_link_policy("embedded", crate::host::LinkPolicy::default().deny("https://blocked.example/*"));
+        let view = ViewSession::new(UiSession::new("l1", "embedded", "ui://test/view"), host_state, None)
+            .with_link_broker(Some(LinkBroker::new(NoOpener)));
+        let mut outgoing = view.take_outgoing().await.unwrap();
+
+        for (url, code) in [
+            ("javascript:alert(1)", error_codes::INVALID_PARAMS),
+            ("https://blocked.example/page", error_codes::REQUEST_DENIED),
+        ] {
+            let returned = view
+                .handle_message(json!({ "jsonrpc": "2.0", "id": url, "method": "ui/open-link", "params": { "url": url } }))
+                .await
+                .unwrap();
+            assert!(returned.is_none());
+
+            let response = outgoing.recv().await.unwrap();
+            assert_eq!(response["id"], json!(url));
+            assert_eq!(response["error"]["code"], json!(code));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_model_context_updates_are_stored() {
+        let store = ModelContextStore::new().with_limits(256, 1024);
+        let view = ViewSession::new(UiSession::new("m1", "weather", "ui://weather/view"), HostState::default(), None)
+            .with_model_context(Some(store.clone()));
+        let mut outgoing = view.take_outgoing().await.unwrap();
+
+        let update = |id: u64, params: Value| json!({ "jsonrpc": "2.0", "id": id, "method": "ui/update-model-context", "params": params });
+        view.handle_message(update(1, json!({ "structuredContent": { "city": "Paris" } }))).await.unwrap();
+        assert_eq!(outgoing.try_recv().unwrap()["result"], json!({}));
+        assert_eq!(store.get("m1").unwrap().structured_content, Some(json!({ "city": "Paris" })));
+
+        view.handle_message(update(2, json!({ "content": "not blocks" }))).await.unwrap();
+        assert_eq!(outgoing.try_recv().unwrap()["error"]["code"], json!(error_codes::INVALID_PARAMS));
+
+        view.handle_message(update(3, json!({ "structuredContent": { "rows": "x".repeat(300) } }))).await.unwrap();
+        assert_eq!(outgoing.try_recv().unwrap()["error"]["code"], json!(error_codes::SERVER_ERROR));
+        // Rejected updates keep the previous context
+        assert_eq!(store.get("m1").unwrap().structured_content, Some(json!({ "city": "Paris" })));
+    }
+
+    #[tokio::test]
+    async fn test_ui_message_is_posted_to_the_conversation() {
+        let conversation = Conversation::new().with_rate_limit(1, Duration::from_secs(60));
+        let view = ViewSession::new(UiSession::new("c1", "weather", "ui://weather/view"), HostState::default(), None)
+            .with_conversation(Some(conversation.clone()));
+        let mut outgoing = view.take_outgoing().await.unwrap();
+
+        let post = |id: u64, params: Value| json!({ "jsonrpc": "2.0", "id": id, "method": "ui/message", "params": params });
+        let text = json!({ "role": "user", "content": [{ "type": "text", "text": "Forecast for Paris?" }] });
+        assert!(view.handle_message(post(1, text.clone())).await.unwrap().is_none());
+        assert_eq!(outgoing.recv().await.unwrap()["result"], json!({}));
+        assert_eq!(conversation.messages()[0].text(), "Forecast for Paris?");
+
+        view.handle_message(post(2, json!({ "role": "user" }))).await.unwrap();
+        assert_eq!(outgoing.recv().await.unwrap()["error"]["code"], json!(error_codes::INVALID_PARAMS));
+
+        view.handle_message(post(3, text)).await.unwrap();
+        assert_eq!(outgoing.recv().await.unwrap()["error"]["code"], json!(error_codes::SERVER_ERROR));
+        assert_eq!(conversation.messages().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_server_requests_are_forwarded() {
+        let servers = ConnectionManager::new(HostState::default());
+        servers.connect_embedded().await.unwrap();
+        let path = std::env::temp_dir().join(format!("mcp-audit-{}.jsonl", uuid::Uuid::new_v4()));
+        let audit = AuditLog::open(&path).unwrap();
+        let view = ViewSession::new(UiSession::new("f1", "embedded", "ui://weather-server/dashboard"), HostState::default(), None)
+            .with_connection_manager(Some(servers))
+            .with_audit_log(Some(audit.clone()));
+        let mut outgoing = view.take_outgoing().await.unwrap();
+
+        let call = |id: u64, name: &str| json!({ "jsonrpc": "2.0", "id": id, "method": "tools/call", "params": { "name": name } });
+        view.handle_message(call(1, "refresh_weather")).await.unwrap();
+        let response = outgoing.recv().await.unwrap();
+        assert_eq!(response["id"], json!(1));
+        assert!(response["result"]["content"].is_array());
+
+        // Only tools the server lets views call are forwarded
+        view.handle_message(call(2, "delete_everything")).await.unwrap();
+        assert_eq!(outgoing.recv().await.unwrap()["error"]["code"], json!(error_codes::INVALID_PARAMS));
+
+        let read = json!({ "jsonrpc": "2.0", "id": 3, "method": "resources/read", "params": { "uri": "ui://weather-server/dashboard" } });
+        view.handle_message(read).await.unwrap();
+        let response = outgoing.recv().await.unwrap();
+        assert_eq!(response["result"]["contents"][0]["uri"], json!("ui://weather-server/dashboard"));
+
+        // Anything else the host leaves unanswered is rejected
+        let unknown = json!({ "jsonrpc": "2.0", "id": 4, "method": "sampling/createMessage", "params": {} });
+        let unhandled = view.handle_message(unknown).await.unwrap().unwrap();
+        view.reject_unhandled(&unhandled).unwrap();
+        assert_eq!(outgoing.recv().await.unwrap()["error"]["code"], json!(error_codes::METHOD_NOT_FOUND));
+        view.reject_unhandled(&json!({ "jsonrpc": "2.0", "method": "notifications/unknown" })).unwrap();
+        assert!(outgoing.try_recv().is_err());
+
+        // Calls made by natively rendered content are audited too
+        assert!(view.call_server_tool("refresh_weather", json!({})).await.is_ok());
+        let records = audit.query(&crate::host::AuditQuery::new().session("f1").method("tools/call")).unwrap();
+        assert_eq!(records.len(), 3);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_view_log_messages_are_buffered() {
+        let logs = ViewLogs::new();
+        let view = ViewSession::new(UiSession::new("g1", "weather", "ui://weather/view"), HostState::default(), None)
+            .with_view_logs(Some(logs.clone()));
+
+        let log = json!({ "jsonrpc": "2.0", "method": "notifications/message", "params": { "level": "warning", "data": "slow" } });
+        assert!(view.handle_message(log).await.unwrap().is_none());
+        let invalid = json!({ "jsonrpc": "2.0", "method": "notifications/message", "params": { "level": "loud", "data": "x" } });
+        assert!(view.handle_message(invalid).await.unwrap().is_none());
+
+        let entries = logs.entries("g1");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].level, crate::protocol::LoggingLevel::Warning);
+        assert_eq!(entries[0].message, "slow");
+    }
+
+    #[tokio::test]
+    async fn test_view_tools_are_discovered_after_initialization() {
+        let view_tools = ViewTools::new();
+        let view = ViewSession::new(UiSession::new("t1", "charts", "ui://charts/line"), HostState::default(), None)
+            .with_view_tools(Some(view_tools.clone()));
+        let mut outgoing = view.take_outgoing().await.unwrap();
+
+        let mut initialize = initialize_message(vec![DisplayMode::Inline]);
+        initialize["params"]["appCapabilities"]["tools"] = json!({ "listChanged": true });
+        view.handle_message(initialize).await.unwrap();
+        outgoing.recv().await.unwrap();
+        view.handle_message(json!({ "jsonrpc": "2.0", "method": "ui/notifications/initialized", "params": {} }))
+            .await
+            .unwrap();
+
+        let answer = |request: Value, tools: Value| {
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": { "tools": tools } })
+        };
+        let tool = |name: &str| json!({ "name": name, "inputSchema": { "type": "object" } });
+
+        let request = outgoing.recv().await.unwrap();
+        assert_eq!(request["method"], json!("tools/list"));
+        view.handle_message(answer(request, json!([tool("get_selection")]))).await.unwrap();
+
+        view.handle_message(json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" })).await.unwrap();
+        let request = outgoing.recv().await.unwrap();
+        assert_eq!(request["method"], json!("tools/list"));
+        let mut updates = view_tools.subscribe();
+        view.handle_message(answer(request, json!([tool("get_selection"), tool("get_range")]))).await.unwrap();
+
+        let crate::host::ViewToolsEvent::Updated(set) = updates.recv().await.unwrap() else {
+            panic!("expected a tool list update");
+        };
+        let names: Vec<&str> = set.tools.iter().map(|t| t.name.as_ref()).collect();
+        assert_eq!(names, ["get_selection", "get_range"]);
+    }
+}